@@ -1,10 +1,11 @@
 pub mod middleware;
 
-use std::net::SocketAddr;
+use std::{collections::HashMap, net::SocketAddr};
 
 use actix_web::{
-    http::{header::HeaderMap, Uri},
-    ResponseError,
+    dev::{Path, ResourceDef},
+    http::{header::HeaderMap, Method, Uri},
+    web, HttpRequest, ResponseError,
 };
 use async_trait::async_trait;
 
@@ -35,8 +36,8 @@ pub trait CheckPermission {
         req_addr: SocketAddr,
         headers: HeaderMap,
         uri: Uri,
-        account_id: Option<u32>,
-        path: ParsedPath,
+        subject: Subject,
+        route: DeclaredRoute,
         method: String,
     ) -> Result<(), Box<dyn ResponseError>>;
 }
@@ -52,15 +53,16 @@ where
     /// Name defines wich model we should check
     /// for attrs
     ///
-    /// TODO: maybe Resource Id is not always Int(u32) ?
+    /// Resource id is the id of the resource in the url,
+    /// None is returned when there is no such resource
     async fn get_model(
         &self,
         resource_name: &str,
-        resource_id: u32,
-    ) -> Box<dyn ModelPermission<T, A>>;
+        resource_id: &str,
+    ) -> Option<Box<dyn ModelPermission<T, A>>>;
 }
 
-#[derive(Default, Debug, Clone)]
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
 /// This is the Natiq Way of reading URLs
 ///
 /// Overall format: `/{controller}/{action}/{id}`
@@ -120,6 +122,222 @@ impl<'a> From<&'a str> for ParsedPath {
     }
 }
 
+/// Who the request is made by
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Subject {
+    /// Request without a (valid) token
+    Anonymous,
+
+    /// Account id of the token
+    Account(u32),
+}
+
+impl From<Option<u32>> for Subject {
+    fn from(value: Option<u32>) -> Self {
+        match value {
+            Some(account_id) => Self::Account(account_id),
+            None => Self::Anonymous,
+        }
+    }
+}
+
+impl Subject {
+    pub fn account_id(&self) -> Option<u32> {
+        match self {
+            Self::Anonymous => None,
+            Self::Account(account_id) => Some(*account_id),
+        }
+    }
+
+    pub fn is_anonymous(&self) -> bool {
+        matches!(self, Self::Anonymous)
+    }
+
+    /// Pseudo subjects that the permissions of this subject can be granted to
+    pub fn pseudo_subjects(&self) -> &'static [PseudoSubject] {
+        match self {
+            Self::Anonymous => &[PseudoSubject::Everyone],
+            Self::Account(_) => &[PseudoSubject::Everyone, PseudoSubject::Authenticated],
+        }
+    }
+}
+
+/// Permissions can be granted to these instead of an account
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PseudoSubject {
+    /// Every request, with or without token
+    Everyone,
+
+    /// Every request with a valid token
+    Authenticated,
+}
+
+impl PseudoSubject {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Everyone => "everyone",
+            Self::Authenticated => "authenticated",
+        }
+    }
+}
+
+/// Where the route reads the id of the requested resource from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResourceId {
+    /// Named path parameter
+    ///
+    /// example: `translation_uuid` of `/translation/text/{translation_uuid}`
+    ///
+    /// It's matched with the declared pattern of the route
+    Path(&'static str),
+
+    /// Query parameter
+    Query(&'static str),
+
+    /// The route is not about a single resource
+    Nothing,
+}
+
+#[derive(Default, Debug, Clone)]
+/// Authorization metadata that the route declares
+///
+/// Everything that is not declared is read from the `ParsedPath`
+pub struct RouteMetadata {
+    /// Permission object, example: `translation`
+    pub object: Option<&'static str>,
+
+    /// Permission action of each request method, example: `(POST, "edit")`
+    pub actions: Vec<(Method, &'static str)>,
+
+    pub resource_id: Option<ResourceId>,
+
+    /// Model that the resource id is of, the object by default
+    ///
+    /// example: `organization_name` when the id is the uuid of an organization name
+    pub model: Option<&'static str>,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+/// The route metadata resolved for a request
+pub struct DeclaredRoute {
+    /// Parsed request path, anything not declared is read from it
+    pub path: ParsedPath,
+
+    pub object: Option<String>,
+    pub action: Option<String>,
+
+    /// None when the resource id is not declared,
+    /// Some(None) when the request has no resource
+    pub resource_id: Option<Option<String>>,
+
+    pub model: Option<String>,
+}
+
+impl RouteMetadata {
+    /// Declares the permission object of the route
+    pub fn object(mut self, object: &'static str) -> Self {
+        self.object = Some(object);
+        self
+    }
+
+    /// Declares the permission action of the request method
+    pub fn action(mut self, method: Method, action: &'static str) -> Self {
+        self.actions.push((method, action));
+        self
+    }
+
+    /// Declares where the id of the requested resource is
+    pub fn resource_id(mut self, resource_id: ResourceId) -> Self {
+        self.resource_id = Some(resource_id);
+        self
+    }
+
+    /// Declares the model of the resource id, when it's not the object itself
+    pub fn model(mut self, model: &'static str) -> Self {
+        self.model = Some(model);
+        self
+    }
+
+    fn declares(&self, method: &Method) -> bool {
+        self.actions.iter().any(|(declared, _)| declared == method)
+    }
+
+    pub fn resolve(&self, req: &HttpRequest) -> DeclaredRoute {
+        self.resolve_parts(req.method(), req.path(), req.query_string(), |name| {
+            req.match_info().get(name).map(String::from)
+        })
+    }
+
+    fn resolve_parts(
+        &self,
+        method: &Method,
+        path: &str,
+        query: &str,
+        path_param: impl Fn(&str) -> Option<String>,
+    ) -> DeclaredRoute {
+        let action = self
+            .actions
+            .iter()
+            .find(|(declared, _)| declared == method)
+            .map(|(_, action)| action.to_string());
+
+        let resource_id = self.resource_id.map(|resource_id| match resource_id {
+            ResourceId::Path(name) => path_param(name),
+            ResourceId::Query(name) => web::Query::<HashMap<String, String>>::from_query(query)
+                .ok()
+                .and_then(|query| query.get(name).cloned()),
+            ResourceId::Nothing => None,
+        });
+
+        DeclaredRoute {
+            path: ParsedPath::from(path),
+            object: self.object.map(String::from),
+            action,
+            resource_id,
+            model: self.model.map(String::from),
+        }
+    }
+}
+
+/// Metadata of the routes by their full path pattern
+///
+/// The same table is used to wrap the routes and to find the metadata of
+/// a path outside of the request, so both of them see the same metadata
+#[derive(Default, Debug, Clone)]
+pub struct DeclaredRoutes {
+    routes: Vec<(ResourceDef, RouteMetadata)>,
+}
+
+impl DeclaredRoutes {
+    /// Declares the metadata of the methods that it has an action for
+    pub fn route(mut self, pattern: &str, metadata: RouteMetadata) -> Self {
+        self.routes.push((ResourceDef::new(pattern), metadata));
+        self
+    }
+
+    /// Resolves the route of the request path (with the query string)
+    /// as the AuthZ of the route does
+    ///
+    /// Paths without declared metadata are only parsed
+    pub fn resolve(&self, method: &Method, path_and_query: &str) -> DeclaredRoute {
+        let (path, query) = path_and_query
+            .split_once('?')
+            .unwrap_or((path_and_query, ""));
+
+        for (resource, metadata) in &self.routes {
+            let mut matched = Path::new(path.to_string());
+
+            if metadata.declares(method) && resource.capture_match_info(&mut matched) {
+                return metadata.resolve_parts(method, path, query, |name| {
+                    matched.get(name).map(String::from)
+                });
+            }
+        }
+
+        RouteMetadata::default().resolve_parts(method, path, query, |_| None)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -151,6 +369,133 @@ mod tests {
         assert_eq!(parsed_path.id, None);
     }
 
+    #[test]
+    fn test_subject_pseudo_subjects() {
+        assert_eq!(Subject::from(None), Subject::Anonymous);
+        assert_eq!(Subject::from(Some(1)).account_id(), Some(1));
+
+        assert_eq!(
+            Subject::Anonymous.pseudo_subjects(),
+            &[PseudoSubject::Everyone]
+        );
+        assert_eq!(
+            Subject::Account(1).pseudo_subjects(),
+            &[PseudoSubject::Everyone, PseudoSubject::Authenticated]
+        );
+    }
+
+    #[test]
+    fn test_route_metadata_resolve() {
+        use actix_web::test::TestRequest;
+
+        let metadata = RouteMetadata::default()
+            .object("translation")
+            .action(Method::POST, "edit")
+            .action(Method::DELETE, "edit")
+            .resource_id(ResourceId::Path("translation_uuid"));
+
+        let req = TestRequest::post()
+            .uri("/translation/text/1")
+            .param("translation_uuid", "1")
+            .to_http_request();
+
+        assert_eq!(
+            metadata.resolve(&req),
+            DeclaredRoute {
+                path: ParsedPath::from("/translation/text/1"),
+                object: Some("translation".to_string()),
+                action: Some("edit".to_string()),
+                resource_id: Some(Some("1".to_string())),
+                model: None,
+            }
+        );
+
+        // Undeclared actions are left to the path
+        let req = TestRequest::get()
+            .uri("/translation/text/1")
+            .to_http_request();
+
+        assert_eq!(metadata.resolve(&req).action, None);
+        assert_eq!(metadata.resolve(&req).resource_id, Some(None));
+
+        let metadata = RouteMetadata::default().resource_id(ResourceId::Query("ayah_uuid"));
+
+        let req = TestRequest::get()
+            .uri("/translation/text/1?ayah_uuid=2")
+            .to_http_request();
+
+        assert_eq!(
+            metadata.resolve(&req).resource_id,
+            Some(Some("2".to_string()))
+        );
+
+        let metadata = RouteMetadata::default().model("organization_name");
+
+        assert_eq!(
+            metadata.resolve(&req).model,
+            Some("organization_name".to_string())
+        );
+        assert_eq!(
+            RouteMetadata::default().resolve(&req),
+            DeclaredRoute {
+                path: ParsedPath::from("/translation/text/1"),
+                ..Default::default()
+            }
+        );
+    }
+
+    #[test]
+    fn test_declared_routes() {
+        let routes = DeclaredRoutes::default()
+            .route(
+                "/organization/name/{uuid}",
+                RouteMetadata::default()
+                    .object("organization")
+                    .action(Method::DELETE, "edit")
+                    .resource_id(ResourceId::Path("uuid"))
+                    .model("organization_name"),
+            )
+            .route(
+                "/organization/name/{uuid}",
+                RouteMetadata::default()
+                    .object("organization")
+                    .action(Method::GET, "view")
+                    .resource_id(ResourceId::Path("uuid")),
+            )
+            .route(
+                "/translation/text/{translation_uuid}",
+                RouteMetadata::default()
+                    .object("translation")
+                    .action(Method::POST, "edit")
+                    .resource_id(ResourceId::Query("ayah_uuid")),
+            );
+
+        let route = routes.resolve(&Method::DELETE, "/organization/name/1");
+
+        assert_eq!(route.action, Some("edit".to_string()));
+        assert_eq!(route.resource_id, Some(Some("1".to_string())));
+        assert_eq!(route.model, Some("organization_name".to_string()));
+
+        let route = routes.resolve(&Method::GET, "/organization/name/1");
+
+        assert_eq!(route.action, Some("view".to_string()));
+        assert_eq!(route.model, None);
+
+        let route = routes.resolve(&Method::POST, "/translation/text/1?ayah_uuid=2");
+
+        assert_eq!(route.path, ParsedPath::from("/translation/text/1"));
+        assert_eq!(route.resource_id, Some(Some("2".to_string())));
+
+        // Not declared for the method
+        assert_eq!(
+            routes.resolve(&Method::POST, "/organization/name/1"),
+            DeclaredRoute {
+                path: ParsedPath::from("/organization/name/1"),
+                ..Default::default()
+            }
+        );
+    }
+
     #[test]
     fn test_empty_url() {
         let parsed_path = ParsedPath::from("/");
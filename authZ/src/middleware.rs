@@ -1,5 +1,6 @@
 use actix_utils::future::{ready, Ready};
 use actix_web::{
+    body::EitherBody,
     dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform},
     http::StatusCode,
     Error, HttpMessage, HttpResponse, ResponseError,
@@ -7,11 +8,12 @@ use actix_web::{
 use futures_util::future::LocalBoxFuture;
 use std::{fmt::Display, rc::Rc};
 
-use crate::{CheckPermission, ParsedPath};
+use crate::{CheckPermission, DeclaredRoutes, Subject};
 
 #[derive(Clone, Default)]
 pub struct AuthZ<P> {
     permission: P,
+    routes: Rc<DeclaredRoutes>,
 }
 
 impl<P> AuthZ<P>
@@ -20,7 +22,17 @@ where
 {
     /// Construct `TokenAuth` middleware.
     pub fn new(permission: P) -> Self {
-        Self { permission }
+        Self {
+            permission,
+            routes: Rc::new(DeclaredRoutes::default()),
+        }
+    }
+
+    /// Declares the metadata of the routes, it's looked up by the path
+    /// and method of the request, anything not declared is read from the path
+    pub fn routes(mut self, routes: DeclaredRoutes) -> Self {
+        self.routes = Rc::new(routes);
+        self
     }
 }
 
@@ -34,7 +46,7 @@ where
     B: 'static,
     F: CheckPermission + Clone + 'static,
 {
-    type Response = ServiceResponse<B>;
+    type Response = ServiceResponse<EitherBody<B>>;
     type Error = Error;
     type Transform = AuthZMiddleware<S, F>;
     type InitError = ();
@@ -44,6 +56,7 @@ where
         ready(Ok(AuthZMiddleware {
             service: Rc::new(service),
             permission: self.permission.clone(),
+            routes: self.routes.clone(),
         }))
     }
 }
@@ -82,6 +95,7 @@ impl ResponseError for AccessDeniedError {
 pub struct AuthZMiddleware<S, P> {
     service: Rc<S>,
     permission: P,
+    routes: Rc<DeclaredRoutes>,
 }
 
 impl<S, B, F> Service<ServiceRequest> for AuthZMiddleware<S, F>
@@ -90,7 +104,7 @@ where
     S::Future: 'static,
     F: CheckPermission + Clone + 'static,
 {
-    type Response = ServiceResponse<B>;
+    type Response = ServiceResponse<EitherBody<B>>;
     type Error = Error;
     type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
 
@@ -99,11 +113,14 @@ where
     fn call(&self, req: ServiceRequest) -> Self::Future {
         let service = Rc::clone(&self.service);
         let permission = self.permission.clone();
-        let subject = req.extensions().get::<u32>().copied();
-
-        let url_as_str = req.uri().path();
+        let subject = Subject::from(req.extensions().get::<u32>().copied());
 
-        let path = ParsedPath::from(url_as_str);
+        // The declared metadata is preferred over the parsed path
+        let path_and_query = req
+            .uri()
+            .path_and_query()
+            .map_or(req.path(), |path_and_query| path_and_query.as_str());
+        let route = self.routes.resolve(req.method(), path_and_query);
 
         Box::pin(async move {
             match permission
@@ -112,7 +129,7 @@ where
                     req.request().headers().clone(),
                     req.request().uri().clone(),
                     subject,
-                    path,
+                    route,
                     req.method().to_string(),
                 )
                 .await
@@ -120,11 +137,86 @@ where
                 Ok(()) => {
                     let res = service.call(req).await?;
 
-                    Ok(res)
+                    Ok(res.map_into_left_body())
                 }
 
-                Err(error) => Err(Error::from(error)),
+                // Responded with the request, so the outer middlewares
+                // still know who was denied
+                Err(error) => Ok(req.error_response(Error::from(error)).map_into_right_body()),
             }
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{AccessDeniedError, AuthZ};
+    use crate::{CheckPermission, DeclaredRoute, DeclaredRoutes, RouteMetadata, Subject};
+    use actix_web::{
+        http::{header::HeaderMap, Method, Uri},
+        test, web, App, HttpResponse, ResponseError,
+    };
+    use async_trait::async_trait;
+    use std::net::SocketAddr;
+
+    /// Allows only the `remove` action
+    #[derive(Clone)]
+    struct AllowRemove;
+
+    #[async_trait]
+    impl CheckPermission for AllowRemove {
+        async fn check(
+            &self,
+            _req_addr: SocketAddr,
+            _headers: HeaderMap,
+            _uri: Uri,
+            _subject: Subject,
+            route: DeclaredRoute,
+            _method: String,
+        ) -> Result<(), Box<dyn ResponseError>> {
+            if route.action.as_deref() == Some("remove") {
+                Ok(())
+            } else {
+                Err(Box::new(AccessDeniedError::with_message("denied")))
+            }
+        }
+    }
+
+    #[actix_web::test]
+    async fn test_metadata_of_request_method() {
+        // Declared separately, the metadata is looked up by the request method
+        let routes = DeclaredRoutes::default()
+            .route(
+                "/item/{uuid}",
+                RouteMetadata::default().action(Method::POST, "edit"),
+            )
+            .route(
+                "/item/{uuid}",
+                RouteMetadata::default().action(Method::DELETE, "remove"),
+            );
+
+        let app = test::init_service(
+            App::new().service(
+                web::resource("/item/{uuid}")
+                    .wrap(AuthZ::new(AllowRemove).routes(routes))
+                    .route(web::post().to(HttpResponse::Ok))
+                    .route(web::delete().to(HttpResponse::Ok)),
+            ),
+        )
+        .await;
+
+        let call = |method: Method| {
+            test::TestRequest::default()
+                .method(method)
+                .uri("/item/1")
+                .peer_addr("127.0.0.1:8080".parse().unwrap())
+                .to_request()
+        };
+
+        let res = test::call_service(&app, call(Method::DELETE)).await;
+        assert!(res.status().is_success());
+
+        let res = test::call_service(&app, call(Method::POST)).await;
+        assert_eq!(res.status(), 403);
+    }
+}
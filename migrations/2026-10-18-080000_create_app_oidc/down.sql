@@ -0,0 +1,2 @@
+DROP TABLE app_oidc_identities;
+DROP TABLE app_oidc_sessions;
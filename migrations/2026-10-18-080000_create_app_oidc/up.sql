@@ -0,0 +1,22 @@
+CREATE TABLE app_oidc_sessions (
+    id serial NOT NULL,
+    state VARCHAR(64) NOT NULL,
+    nonce VARCHAR(64) NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CONSTRAINT app_oidc_sessions_id PRIMARY KEY (id),
+    UNIQUE(state)
+);
+
+CREATE TABLE app_oidc_identities (
+    id serial NOT NULL,
+    account_id serial NOT NULL,
+    issuer TEXT NOT NULL,
+    subject TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CONSTRAINT app_oidc_identities_id PRIMARY KEY (id),
+    UNIQUE(issuer, subject),
+    CONSTRAINT fk_oidc_identity_account_rel FOREIGN KEY(account_id) REFERENCES app_accounts(id)
+        on delete cascade
+);
@@ -0,0 +1 @@
+DROP TABLE app_email_outbox;
@@ -0,0 +1,19 @@
+CREATE TABLE app_email_outbox (
+    id serial NOT NULL,
+    uuid uuid DEFAULT uuid_generate_v4 () NOT NULL,
+    recipient TEXT NOT NULL,
+    subject TEXT NOT NULL,
+    text_body TEXT NOT NULL,
+    html_body TEXT NOT NULL,
+    status VARCHAR(16) NOT NULL DEFAULT 'pending',
+    attempts INT NOT NULL DEFAULT 0,
+    last_error TEXT,
+    next_attempt_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    sent_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CONSTRAINT app_email_outbox_id PRIMARY KEY (id),
+    CONSTRAINT app_email_outbox_status CHECK (status IN ('pending', 'sent', 'failed'))
+);
+
+CREATE INDEX app_email_outbox_pending ON app_email_outbox (next_attempt_at) WHERE status = 'pending';
@@ -0,0 +1,12 @@
+DELETE FROM app_permissions WHERE account_id IS NULL;
+
+ALTER TABLE app_permissions
+    DROP CONSTRAINT permission_single_subject,
+    DROP COLUMN group_id,
+    DROP COLUMN role_id,
+    ALTER COLUMN account_id SET NOT NULL;
+
+DROP TABLE app_role_assignments;
+DROP TABLE app_group_members;
+DROP TABLE app_groups;
+DROP TABLE app_roles;
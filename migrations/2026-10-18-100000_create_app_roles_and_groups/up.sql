@@ -0,0 +1,75 @@
+CREATE TABLE app_roles (
+    id serial NOT NULL,
+    uuid uuid DEFAULT uuid_generate_v4 () NOT NULL,
+    creator_user_id serial NOT NULL,
+    name VARCHAR(255) NOT NULL,
+    description TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CONSTRAINT app_roles_id PRIMARY KEY (id),
+    UNIQUE(name),
+    CONSTRAINT role_fk_user_id_rel FOREIGN KEY(creator_user_id) REFERENCES app_users(id)
+);
+
+CREATE TABLE app_groups (
+    id serial NOT NULL,
+    uuid uuid DEFAULT uuid_generate_v4 () NOT NULL,
+    creator_user_id serial NOT NULL,
+    name VARCHAR(255) NOT NULL,
+    description TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CONSTRAINT app_groups_id PRIMARY KEY (id),
+    UNIQUE(name),
+    CONSTRAINT group_fk_user_id_rel FOREIGN KEY(creator_user_id) REFERENCES app_users(id)
+);
+
+CREATE TABLE app_group_members (
+    id serial NOT NULL,
+    creator_user_id serial NOT NULL,
+    group_id serial NOT NULL,
+    account_id serial NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CONSTRAINT app_group_members_id PRIMARY KEY (id),
+    UNIQUE(group_id, account_id),
+    CONSTRAINT group_member_fk_user_id_rel FOREIGN KEY(creator_user_id) REFERENCES app_users(id),
+    CONSTRAINT fk_group_member_group_rel FOREIGN KEY(group_id) REFERENCES app_groups(id)
+        on delete cascade,
+    CONSTRAINT fk_group_member_account_rel FOREIGN KEY(account_id) REFERENCES app_accounts(id)
+        on delete cascade
+);
+
+-- A role is assigned either to an account or to a group
+CREATE TABLE app_role_assignments (
+    id serial NOT NULL,
+    creator_user_id serial NOT NULL,
+    role_id serial NOT NULL,
+    account_id INT,
+    group_id INT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CONSTRAINT app_role_assignments_id PRIMARY KEY (id),
+    UNIQUE(role_id, account_id),
+    UNIQUE(role_id, group_id),
+    CONSTRAINT role_assignment_single_subject CHECK (num_nonnulls(account_id, group_id) = 1),
+    CONSTRAINT role_assignment_fk_user_id_rel FOREIGN KEY(creator_user_id) REFERENCES app_users(id),
+    CONSTRAINT fk_role_assignment_role_rel FOREIGN KEY(role_id) REFERENCES app_roles(id)
+        on delete cascade,
+    CONSTRAINT fk_role_assignment_account_rel FOREIGN KEY(account_id) REFERENCES app_accounts(id)
+        on delete cascade,
+    CONSTRAINT fk_role_assignment_group_rel FOREIGN KEY(group_id) REFERENCES app_groups(id)
+        on delete cascade
+);
+
+-- A permission is granted to exactly one of account, role or group
+ALTER TABLE app_permissions
+    ALTER COLUMN account_id DROP DEFAULT,
+    ALTER COLUMN account_id DROP NOT NULL,
+    ADD COLUMN role_id INT,
+    ADD COLUMN group_id INT,
+    ADD CONSTRAINT permission_fk_role_id FOREIGN KEY(role_id) REFERENCES app_roles(id)
+        on delete cascade,
+    ADD CONSTRAINT permission_fk_group_id FOREIGN KEY(group_id) REFERENCES app_groups(id)
+        on delete cascade,
+    ADD CONSTRAINT permission_single_subject CHECK (num_nonnulls(account_id, role_id, group_id) = 1);
@@ -0,0 +1,20 @@
+CREATE TABLE app_organization_invitations (
+    id serial NOT NULL,
+    uuid uuid DEFAULT uuid_generate_v4 () NOT NULL,
+    org_account_id serial NOT NULL,
+    creator_user_id serial NOT NULL,
+    email TEXT NOT NULL,
+    expires_at TIMESTAMPTZ NOT NULL,
+    accepted_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CONSTRAINT app_organization_invitations_id PRIMARY KEY (id),
+    CONSTRAINT invitation_fk_user_id_rel FOREIGN KEY(creator_user_id) REFERENCES app_users(id),
+    CONSTRAINT fk_invitation_org_account_rel FOREIGN KEY(org_account_id) REFERENCES app_accounts(id)
+        on delete cascade
+);
+
+-- Only one pending invitation for each email in an organization
+CREATE UNIQUE INDEX app_organization_invitations_pending
+    ON app_organization_invitations (org_account_id, email)
+    WHERE accepted_at IS NULL;
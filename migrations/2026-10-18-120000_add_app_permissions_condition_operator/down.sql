@@ -0,0 +1 @@
+ALTER TABLE app_permissions DROP COLUMN condition_operator;
@@ -0,0 +1,6 @@
+-- Existing permissions were granted when any of their conditions was satisfied
+ALTER TABLE app_permissions
+    ADD COLUMN condition_operator VARCHAR(3) NOT NULL DEFAULT 'or',
+    ADD CONSTRAINT app_permissions_condition_operator CHECK (condition_operator IN ('and', 'or'));
+
+ALTER TABLE app_permissions ALTER COLUMN condition_operator SET DEFAULT 'and';
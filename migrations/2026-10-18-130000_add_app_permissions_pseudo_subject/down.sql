@@ -0,0 +1,7 @@
+DELETE FROM app_permissions WHERE pseudo_subject IS NOT NULL;
+
+ALTER TABLE app_permissions
+    DROP CONSTRAINT permission_single_subject,
+    ADD CONSTRAINT permission_single_subject CHECK (num_nonnulls(account_id, role_id, group_id) = 1),
+    DROP CONSTRAINT app_permissions_pseudo_subject,
+    DROP COLUMN pseudo_subject;
@@ -0,0 +1,6 @@
+-- A permission can be granted to everyone or every authenticated account
+ALTER TABLE app_permissions
+    ADD COLUMN pseudo_subject VARCHAR(13),
+    ADD CONSTRAINT app_permissions_pseudo_subject CHECK (pseudo_subject IN ('everyone', 'authenticated')),
+    DROP CONSTRAINT permission_single_subject,
+    ADD CONSTRAINT permission_single_subject CHECK (num_nonnulls(account_id, role_id, group_id, pseudo_subject) = 1);
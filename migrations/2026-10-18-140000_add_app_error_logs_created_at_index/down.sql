@@ -0,0 +1 @@
+DROP INDEX app_error_logs_created_at_idx;
@@ -0,0 +1,2 @@
+-- Used by the retention purge and the error stats
+CREATE INDEX app_error_logs_created_at_idx ON app_error_logs (created_at);
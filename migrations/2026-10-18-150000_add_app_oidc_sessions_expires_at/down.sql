@@ -0,0 +1,2 @@
+DROP INDEX app_oidc_sessions_expires_at_idx;
+ALTER TABLE app_oidc_sessions DROP COLUMN expires_at;
@@ -0,0 +1,4 @@
+ALTER TABLE app_oidc_sessions ADD COLUMN expires_at TIMESTAMPTZ NOT NULL DEFAULT NOW() + INTERVAL '10 minutes';
+
+-- Used by the purge of the abandoned sessions
+CREATE INDEX app_oidc_sessions_expires_at_idx ON app_oidc_sessions (expires_at);
@@ -0,0 +1,9 @@
+DROP INDEX app_email_outbox_finished;
+
+UPDATE app_email_outbox SET status = 'failed' WHERE status = 'expired';
+
+ALTER TABLE app_email_outbox DROP CONSTRAINT app_email_outbox_status;
+ALTER TABLE app_email_outbox ADD CONSTRAINT app_email_outbox_status
+    CHECK (status IN ('pending', 'sent', 'failed'));
+
+ALTER TABLE app_email_outbox DROP COLUMN expires_at;
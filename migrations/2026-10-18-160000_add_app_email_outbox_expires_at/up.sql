@@ -0,0 +1,9 @@
+-- Emails are not sent after this, like the verification codes that are expired
+ALTER TABLE app_email_outbox ADD COLUMN expires_at TIMESTAMPTZ;
+
+ALTER TABLE app_email_outbox DROP CONSTRAINT app_email_outbox_status;
+ALTER TABLE app_email_outbox ADD CONSTRAINT app_email_outbox_status
+    CHECK (status IN ('pending', 'sent', 'failed', 'expired'));
+
+-- Used by the purge of the finished emails
+CREATE INDEX app_email_outbox_finished ON app_email_outbox (updated_at) WHERE status <> 'pending';
@@ -1,20 +1,29 @@
 use std::net::SocketAddr;
 use std::sync::Arc;
 
+use crate::condition::{AttribValue, ConditionOperator, ConditionValue, ConditionValueType};
 use crate::error::{RouterError, RouterErrorDetail};
-use crate::models::{Organization, User};
-use crate::select_model::SelectModel;
+use crate::metrics::metrics;
+use crate::models::{
+    Group, Organization, Permission, QuranAyah, QuranMushaf, QuranSurah, QuranWord, Role,
+    Translation, User,
+};
+use crate::permission_cache::{self, PermissionCache};
+use crate::select_model::{OwnedModel, SelectModel};
 use crate::DbPool;
 use actix_web::http::header::HeaderMap;
 use actix_web::http::Uri;
 use actix_web::{web, ResponseError};
 use async_trait::async_trait;
-use auth_z::{CheckPermission, GetModel, ModelPermission, ParsedPath};
+use auth_z::{CheckPermission, DeclaredRoute, GetModel, ModelPermission, ParsedPath, Subject};
+use chrono::Utc;
 use diesel::prelude::*;
+use serde::Serialize;
+use uuid::Uuid;
 
 #[derive(Debug)]
 /// Request Action
-enum Action {
+pub(crate) enum Action {
     /// Create (POST) request to a controller
     Create,
 
@@ -28,8 +37,33 @@ enum Action {
     View,
 }
 
+/// Returns the id of the resource that request is about
+///
+/// `/controller/{id}` and `/controller/action/{id}` are about the resource itself,
+/// `/controller/{uuid}/sub_resource` is about the sub resource of `{uuid}`
+fn resource_id(path: &ParsedPath) -> Option<&str> {
+    match (path.action.as_deref(), path.id.as_deref()) {
+        (Some(action), Some(id)) if is_sub_resource(action, id) => Some(action),
+        (_, id) => id,
+    }
+}
+
+fn is_sub_resource(action: &str, id: &str) -> bool {
+    Uuid::parse_str(action).is_ok() && Uuid::parse_str(id).is_err()
+}
+
 impl Action {
-    fn from_auth_z(path: &ParsedPath, method: &str) -> Self {
+    pub(crate) fn from_auth_z(path: &ParsedPath, method: &str) -> Self {
+        // Changing the sub resources edits the resource
+        if let (Some(action), Some(id)) = (path.action.as_deref(), path.id.as_deref()) {
+            if is_sub_resource(action, id) {
+                return match method {
+                    "POST" | "DELETE" => Self::Edit,
+                    _ => Self::View,
+                };
+            }
+        }
+
         // Checks the id of path and request method
         match (path.id.clone(), method) {
             (Some(_), "GET") => Self::View,
@@ -53,16 +87,261 @@ impl From<Action> for &str {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Clone)]
 /// Actual Context of AuthZ
 pub struct AuthZController {
     db_pool: DbPool,
+    cache: PermissionCache<PermissionsWithConditions>,
 }
 
 impl AuthZController {
     pub fn new(db_pool: DbPool) -> Self {
-        Self { db_pool }
+        Self {
+            db_pool,
+            cache: PermissionCache::default(),
+        }
+    }
+
+    /// Clears the cached permissions when they are changed,
+    /// by this replica or others
+    pub fn listen_permission_changes(&self, database_url: String) {
+        permission_cache::listen(self.cache.clone(), database_url);
     }
+
+    /// Evaluates the permissions of the account for the request
+    ///
+    /// Every matching permission and condition is kept in the
+    /// returned trace, `check` only looks at the final decision
+    pub async fn evaluate(
+        &self,
+        subject: Subject,
+        route: &DeclaredRoute,
+        method: &str,
+    ) -> Result<Evaluation, RouterError> {
+        use crate::schema::app_permission_conditions::dsl::{
+            app_permission_conditions, name, value,
+        };
+        use crate::schema::app_permissions::dsl::{
+            account_id as permission_account_id, action as permission_action, app_permissions,
+            condition_operator, group_id as permission_group_id, id as permission_id,
+            object as permission_object, pseudo_subject, role_id as permission_role_id,
+            uuid as permission_uuid,
+        };
+
+        // The route declared parts are preferred over the parsed path
+        let path = &route.path;
+        let calculated_action = route.action.clone().unwrap_or_else(|| {
+            // Found the requested Action
+            <&str>::from(Action::from_auth_z(path, method)).to_string()
+        });
+
+        let mut evaluation = Evaluation {
+            object: route.object.clone().or(path.controller.clone()),
+            action: calculated_action.clone(),
+            resource: match &route.resource_id {
+                Some(declared) => declared.clone(),
+                None => resource_id(path).map(String::from),
+            },
+            permissions: vec![],
+            granted: false,
+        };
+
+        let Some(object) = evaluation.object.clone() else {
+            return Ok(evaluation);
+        };
+
+        let account = subject.account_id().map(|id| id as i32);
+        let cache_key = (account, object.clone(), calculated_action.clone());
+
+        // Permissions are cached until one of them changes
+        let (permissions, conditions, organizations): PermissionsWithConditions =
+            match self.cache.get(&cache_key) {
+                Some(cached) => cached,
+                None => {
+                    // these will be moved to the web::block closure
+                    let mut conn = self.db_pool.get().unwrap();
+                    let (object, calculated_action) = (object.clone(), calculated_action.clone());
+
+                    let loaded: PermissionsWithConditions = web::block(move || {
+                        // Anonymous requests only have the pseudo subjects
+                        let subjects = match account {
+                            Some(account) => AccountSubjects::load(&mut conn, account)?,
+                            None => AccountSubjects::default(),
+                        };
+
+                        let pseudo_subjects: Vec<&str> = subject
+                            .pseudo_subjects()
+                            .iter()
+                            .map(|pseudo| pseudo.as_str())
+                            .collect();
+
+                        // Check the permissions and get the conditions
+                        // effective permissions are the direct, organization, role, group
+                        // and pseudo subject grants
+                        let permissions_filter = app_permissions
+                            .filter(
+                                pseudo_subject
+                                    .eq_any(pseudo_subjects)
+                                    .or(permission_account_id.eq_any(subjects.accounts()))
+                                    .or(permission_group_id.eq_any(subjects.groups))
+                                    .or(permission_role_id.eq_any(subjects.roles)),
+                            )
+                            .filter(permission_object.eq(object))
+                            .filter(permission_action.eq(calculated_action));
+
+                        let permissions = permissions_filter
+                            .clone()
+                            .select((permission_id, permission_uuid, condition_operator))
+                            .load(&mut conn)?;
+
+                        let conditions = permissions_filter
+                            .inner_join(app_permission_conditions)
+                            .select((permission_id, name, value))
+                            .load(&mut conn)?;
+
+                        Ok::<_, RouterError>((permissions, conditions, subjects.organizations))
+                    })
+                    .await
+                    .unwrap()?;
+
+                    self.cache.insert(cache_key, loaded.clone());
+
+                    loaded
+                }
+            };
+
+        // The model is loaded only when there is a condition,
+        // requests without resource (like create) have no model
+        let model_name = route.model.as_deref().unwrap_or(&object);
+        let model = match (&evaluation.resource, conditions.is_empty()) {
+            (Some(resource), false) => self.get_model(model_name, resource).await,
+            _ => None,
+        };
+
+        let inner_subject = subject.account_id().map(|id| id.to_string());
+
+        // Conditions of each permission are combined with its operator,
+        // one granted permission is enough
+        for (permission, uuid, operator) in permissions {
+            let operator = ConditionOperator::from(operator.as_str());
+
+            let mut permission_trace = PermissionTrace {
+                uuid,
+                condition_operator: operator,
+                conditions: vec![],
+                granted: false,
+            };
+
+            for (_, cond_name, cond_value) in conditions
+                .iter()
+                .filter(|(cond_permission, _, _)| *cond_permission == permission)
+            {
+                let mut condition_trace = ConditionTrace {
+                    name: cond_name.clone(),
+                    value: cond_value.clone(),
+                    attribute: None,
+                    satisfied: false,
+                    error: None,
+                };
+
+                let model_attr = match ModelAttrib::try_from(cond_name.as_str()) {
+                    Ok(v) => v,
+
+                    // Permission with unknown condition is never granted
+                    Err(err) => {
+                        condition_trace.error = Some(err.error_name());
+                        permission_trace.conditions.push(condition_trace);
+
+                        continue;
+                    }
+                };
+
+                let attr = match (&model_attr, &model) {
+                    (ModelAttrib::Time, _) => Some(AttribValue::from(Utc::now().naive_utc())),
+                    (_, Some(model)) => model.get_attr(model_attr.clone()).await,
+                    (_, None) => None,
+                };
+
+                condition_trace.attribute = attr.as_ref().map(|attr| attr.to_string());
+
+                // Employee condition needs the organizations of the subject
+                let condition = match model_attr {
+                    ModelAttrib::Employee => {
+                        ModelAttribResult::Employee(Employee::new(organizations.clone()))
+                    }
+                    _ => ModelAttribResult::from(model_attr),
+                };
+
+                condition_trace.satisfied =
+                    condition.validate(attr, inner_subject.as_deref(), cond_value);
+
+                permission_trace.conditions.push(condition_trace);
+            }
+
+            let results: Vec<bool> = permission_trace
+                .conditions
+                .iter()
+                .map(|condition| condition.error.is_none() && condition.satisfied)
+                .collect();
+
+            // No need to Checking the conditions
+            // permission without condition is granted
+            permission_trace.granted = results.is_empty()
+                || (permission_trace
+                    .conditions
+                    .iter()
+                    .all(|condition| condition.error.is_none())
+                    && operator.combine(&results));
+
+            evaluation.granted |= permission_trace.granted;
+            evaluation.permissions.push(permission_trace);
+        }
+
+        Ok(evaluation)
+    }
+}
+
+/// (permission id, permission uuid, condition operator), (permission id, condition name, condition value)
+/// and the organizations of the account
+type PermissionsWithConditions = (
+    Vec<(i32, Uuid, String)>,
+    Vec<(i32, String, String)>,
+    Vec<i32>,
+);
+
+/// Result of the permission evaluation with every step of it
+#[derive(Debug, Clone, Serialize)]
+pub struct Evaluation {
+    pub object: Option<String>,
+    pub action: String,
+
+    /// Id of the resource that conditions are checked against
+    pub resource: Option<String>,
+
+    pub permissions: Vec<PermissionTrace>,
+    pub granted: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PermissionTrace {
+    pub uuid: Uuid,
+    pub condition_operator: ConditionOperator,
+    pub conditions: Vec<ConditionTrace>,
+    pub granted: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ConditionTrace {
+    pub name: String,
+    pub value: String,
+
+    /// Model attribute that the condition is checked against
+    pub attribute: Option<String>,
+
+    pub satisfied: bool,
+
+    /// Name of the error if the condition is not valid
+    pub error: Option<String>,
 }
 
 #[async_trait]
@@ -72,18 +351,10 @@ impl CheckPermission for AuthZController {
         req_addr: SocketAddr,
         headers: HeaderMap,
         uri: Uri,
-        account_id: Option<u32>,
-        path: ParsedPath,
+        subject: Subject,
+        route: DeclaredRoute,
         method: String,
     ) -> Result<(), Box<dyn ResponseError>> {
-        use crate::schema::app_permission_conditions::dsl::{
-            app_permission_conditions, name, value,
-        };
-        use crate::schema::app_permissions::dsl::{
-            account_id as permission_account_id, action as permission_action, app_permissions,
-            id as permission_id, object as permission_object,
-        };
-
         let mut error_detail_builder = RouterErrorDetail::builder();
 
         error_detail_builder
@@ -91,149 +362,162 @@ impl CheckPermission for AuthZController {
             .request_url_parsed(uri.path())
             .req_address(req_addr);
 
+        error_detail_builder.request_id_from_headers(&headers);
+
         if let Some(user_agent) = headers.get("User-agent") {
             error_detail_builder.user_agent(user_agent.to_str().unwrap().to_string());
         }
 
         let error_detail = error_detail_builder.build();
 
+        // Anonymous requests are asked to authenticate
         let permission_denied_error =
-            Box::new(RouterError::from_predefined("AUTHZ_PERMISSION_DENIED"));
-
-        // these will be moved to the web::block closure
-        let path_copy = path.clone();
-
-        let mut conn = self.db_pool.get().unwrap();
-        let select_result: Result<(Vec<i32>, Vec<(String, String)>), RouterError> =
-            web::block(move || {
-                // Found the requested Action
-                let calculated_action = Action::from_auth_z(&path_copy, method.as_str());
-
-                // Check the permissions and get the conditions
-                let permissions_filter = app_permissions
-                    .filter(permission_account_id.eq(account_id.unwrap() as i32))
-                    .filter(permission_object.eq(path_copy.controller.unwrap().clone()))
-                    .filter(permission_action.eq::<&str>(calculated_action.into()));
-
-                let permissions = permissions_filter
-                    .clone()
-                    .select(permission_id)
-                    .load(&mut conn)?;
-
-                let conditions = permissions_filter
-                    .inner_join(app_permission_conditions)
-                    .select((name, value))
-                    .load(&mut conn)?;
-
-                Ok((permissions, conditions))
-            })
-            .await
-            .unwrap();
-
-        let Ok(select_result) = select_result else {
+            Box::new(RouterError::from_predefined(if subject.is_anonymous() {
+                "AUTHN_TOKEN_NOT_FOUND"
+            } else {
+                "AUTHZ_PERMISSION_DENIED"
+            }));
+
+        let Ok(evaluation) = self.evaluate(subject, &route, &method).await else {
+            metrics().authz_denials.inc();
             permission_denied_error.log_to_db(Arc::new(self.db_pool.clone()), error_detail);
             return Err(permission_denied_error);
         };
 
-        if select_result.0.is_empty() {
-            permission_denied_error.log_to_db(Arc::new(self.db_pool.clone()), error_detail);
-            return Err(permission_denied_error);
+        // Not valid conditions must be fixed by the admins
+        for condition in evaluation
+            .permissions
+            .iter()
+            .flat_map(|permission| permission.conditions.iter())
+        {
+            if let Some(error_name) = &condition.error {
+                RouterError::from_predefined(error_name)
+                    .log_to_db(Arc::new(self.db_pool.clone()), error_detail.clone());
+            }
         }
 
-        // No need to Checking the conditions
-        // there is no condition
-        if select_result.1.is_empty() {
+        if evaluation.granted {
             return Ok(());
         }
 
-        // *Now Check the conditions*
-
-        // First get the required Resource as Model
-        let model = self
-            .get_model(
-                &path.controller.unwrap().clone(),
-                path.id.unwrap().clone().parse().unwrap(),
-            )
-            .await;
+        metrics().authz_denials.inc();
+        permission_denied_error.log_to_db(Arc::new(self.db_pool.clone()), error_detail);
+        return Err(permission_denied_error);
+    }
+}
 
-        // We Got the model now we check every condition
-        for (cond_name, cond_value) in select_result.1 {
-            let model_attr: Option<ModelAttrib> = match ModelAttrib::try_from(cond_name.as_str()) {
-                Ok(v) => Some(v),
+/// Everything that permissions of an account can be granted to
+#[derive(Default)]
+pub struct AccountSubjects {
+    /// None for the anonymous requests
+    pub account: Option<i32>,
 
-                Err(err) => {
-                    err.log_to_db(Arc::new(self.db_pool.clone()), error_detail.clone());
+    /// Organizations that the account is employee of
+    pub organizations: Vec<i32>,
 
-                    None
-                }
-            };
+    /// Groups of the account or its organizations
+    pub groups: Vec<i32>,
 
-            let Some(model_attr) = model_attr else {
-                permission_denied_error.log_to_db(Arc::new(self.db_pool.clone()), error_detail);
-                return Err(permission_denied_error);
-            };
+    /// Roles that are assigned to the account, its organizations or groups
+    pub roles: Vec<i32>,
+}
 
-            let attr = model.get_attr(model_attr.clone()).await;
+impl AccountSubjects {
+    pub fn load(conn: &mut PgConnection, account: i32) -> QueryResult<Self> {
+        use crate::schema::app_employees::dsl::{
+            app_employees, employee_account_id, org_account_id,
+        };
+        use crate::schema::app_group_members::dsl::{
+            account_id as member_account_id, app_group_members, group_id as member_group_id,
+        };
+        use crate::schema::app_role_assignments::dsl::{
+            account_id as assignment_account_id, app_role_assignments,
+            group_id as assignment_group_id, role_id as assignment_role_id,
+        };
 
-            let inner_subject = account_id.map(|id| id.to_string());
+        let organizations: Vec<i32> = app_employees
+            .filter(employee_account_id.eq(account))
+            .filter(org_account_id.ne(account))
+            .select(org_account_id)
+            .distinct()
+            .load(conn)?;
+
+        let mut accounts = organizations.clone();
+        accounts.push(account);
+
+        let groups: Vec<i32> = app_group_members
+            .filter(member_account_id.eq_any(accounts.clone()))
+            .select(member_group_id)
+            .distinct()
+            .load(conn)?;
+
+        let roles: Vec<i32> = app_role_assignments
+            .filter(
+                assignment_account_id
+                    .eq_any(accounts)
+                    .or(assignment_group_id.eq_any(groups.clone())),
+            )
+            .select(assignment_role_id)
+            .distinct()
+            .load(conn)?;
+
+        Ok(Self {
+            account: Some(account),
+            organizations,
+            groups,
+            roles,
+        })
+    }
 
-            let result = ModelAttribResult::from(model_attr).validate(
-                attr,
-                inner_subject.as_deref(),
-                &cond_value,
-            );
+    /// The account itself and its organizations
+    pub fn accounts(&self) -> Vec<i32> {
+        let mut accounts = self.organizations.clone();
+        accounts.extend(self.account);
 
-            if result {
-                return Ok(());
-            }
-        }
-
-        permission_denied_error.log_to_db(Arc::new(self.db_pool.clone()), error_detail);
-        return Err(permission_denied_error);
+        accounts
     }
 }
 
 #[async_trait]
-impl GetModel<ModelAttrib, i32> for AuthZController {
+impl GetModel<ModelAttrib, AttribValue> for AuthZController {
     async fn get_model(
         &self,
         resource_name: &str,
-        resource_id: u32,
-    ) -> Box<dyn ModelPermission<ModelAttrib, i32>> {
-        //let mut conn = self.db_pool.get().unwrap();
-        let resource_id = resource_id as i32;
+        resource_id: &str,
+    ) -> Option<Box<dyn ModelPermission<ModelAttrib, AttribValue>>> {
+        let resource_uuid = Uuid::parse_str(resource_id).ok()?;
+
+        let pool = self.db_pool.clone();
 
         // Resource must have been impl the Model permission trait
-        let model: Box<dyn ModelPermission<ModelAttrib, i32>> = match resource_name {
-            "user" => Box::new(User::from_id(self.db_pool.clone(), resource_id).await),
+        let model: Box<dyn ModelPermission<ModelAttrib, AttribValue>> = match resource_name {
+            "user" => Box::new(User::from_uuid(pool, resource_uuid).await?),
+            "organization" => Box::new(Organization::from_uuid(pool, resource_uuid).await?),
 
-            "organization" => {
-                Box::new(Organization::from_id(self.db_pool.clone(), resource_id).await)
+            // Names are checked as their organization
+            "organization_name" => {
+                Box::new(Organization::from_name_uuid(pool, resource_uuid).await?)
             }
 
-            _ => todo!(),
-        };
-
-        model
-    }
-}
+            // Translation texts are addressed by their translation
+            "translation" => Box::new(Translation::from_uuid(pool, resource_uuid).await?),
 
-#[derive(Debug, Clone, PartialEq, Eq)]
-pub enum ConditionValueType {
-    Boolean,
-}
-
-impl TryFrom<&str> for ConditionValueType {
-    type Error = RouterError;
+            "mushaf" => Box::new(OwnedModel::<QuranMushaf>::from_uuid(pool, resource_uuid).await?),
+            "surah" => Box::new(OwnedModel::<QuranSurah>::from_uuid(pool, resource_uuid).await?),
+            "ayah" => Box::new(OwnedModel::<QuranAyah>::from_uuid(pool, resource_uuid).await?),
+            "word" => Box::new(OwnedModel::<QuranWord>::from_uuid(pool, resource_uuid).await?),
+            "permission" => {
+                Box::new(OwnedModel::<Permission>::from_uuid(pool, resource_uuid).await?)
+            }
+            "role" => Box::new(OwnedModel::<Role>::from_uuid(pool, resource_uuid).await?),
+            "group" => Box::new(OwnedModel::<Group>::from_uuid(pool, resource_uuid).await?),
 
-    fn try_from(value: &str) -> Result<Self, Self::Error> {
-        match value {
-            "true" | "false" => Ok(Self::Boolean),
+            // Phrases are addressed by language and others have no
+            // model, only the conditions without attribute apply to them
+            _ => return None,
+        };
 
-            _ => Err(RouterError::from_predefined(
-                "AUTHZ_CONDITION_VALUE_NOT_DEFINED",
-            )),
-        }
+        Some(model)
     }
 }
 
@@ -241,15 +525,15 @@ pub trait Condition<'a> {
     /// Validates the condition based on subject and value
     fn validate(
         &self,
-        attribute: Option<i32>,
+        attribute: Option<AttribValue>,
         subject: Option<&'a str>,
         condition_value: &'a str,
     ) -> bool
     where
         Self: Sized;
 
-    /// Returns the value type of the condition
-    fn get_value_type(&self) -> ConditionValueType
+    /// Returns the value types that the condition accepts
+    fn get_value_types(&self) -> &'static [ConditionValueType]
     where
         Self: Sized;
 }
@@ -261,7 +545,7 @@ impl<'a> Condition<'a> for Owner {
     // Validates the Owner Condition
     fn validate(
         &self,
-        attr: Option<i32>,
+        attr: Option<AttribValue>,
         subject: Option<&'a str>,
         condition_value: &'a str,
     ) -> bool {
@@ -269,17 +553,19 @@ impl<'a> Condition<'a> for Owner {
             return false;
         };
 
+        let attr = attr.map(|attr| attr.to_string());
+
         if condition_value == "true" {
-            attr.is_some() && subject == attr.unwrap().to_string()
+            attr.is_some() && subject == attr.unwrap()
         } else if condition_value == "false" {
-            attr.is_none() || subject != attr.unwrap().to_string()
+            attr.is_none() || subject != attr.unwrap()
         } else {
             true
         }
     }
 
-    fn get_value_type(&self) -> ConditionValueType {
-        ConditionValueType::Boolean
+    fn get_value_types(&self) -> &'static [ConditionValueType] {
+        &[ConditionValueType::Boolean]
     }
 }
 
@@ -290,15 +576,82 @@ impl<'a> Condition<'a> for Login {
     // Validates the Owner Condition
     fn validate(
         &self,
-        _attr: Option<i32>,
+        _attr: Option<AttribValue>,
         subject: Option<&'a str>,
         _condition_value: &'a str,
     ) -> bool {
         subject.is_some()
     }
 
-    fn get_value_type(&self) -> ConditionValueType {
-        ConditionValueType::Boolean
+    fn get_value_types(&self) -> &'static [ConditionValueType] {
+        &[ConditionValueType::Boolean]
+    }
+}
+
+/// Checks if the subject is employee of the organization
+/// that owns the resource
+#[derive(Debug, Clone, Default)]
+pub struct Employee {
+    /// Organization accounts of the subject
+    organizations: Vec<i32>,
+}
+
+impl Employee {
+    pub fn new(organizations: Vec<i32>) -> Self {
+        Self { organizations }
+    }
+}
+
+impl<'a> Condition<'a> for Employee {
+    fn validate(
+        &self,
+        attr: Option<AttribValue>,
+        subject: Option<&'a str>,
+        condition_value: &'a str,
+    ) -> bool {
+        if subject.is_none() {
+            return false;
+        }
+
+        let is_employee = self
+            .organizations
+            .iter()
+            .any(|org| attr == Some(AttribValue::from(*org)));
+
+        match condition_value {
+            "true" => is_employee,
+            "false" => !is_employee,
+            _ => true,
+        }
+    }
+
+    fn get_value_types(&self) -> &'static [ConditionValueType] {
+        &[ConditionValueType::Boolean]
+    }
+}
+
+/// Compares the attribute with the condition value,
+/// `== "fa"`, `in [fa, ar]`, `08:00..17:00`
+#[derive(Debug, Clone)]
+pub struct Compare {
+    value_types: &'static [ConditionValueType],
+}
+
+impl<'a> Condition<'a> for Compare {
+    fn validate(
+        &self,
+        attr: Option<AttribValue>,
+        _subject: Option<&'a str>,
+        condition_value: &'a str,
+    ) -> bool {
+        match ConditionValue::try_from(condition_value) {
+            Ok(value) => value.matches(attr.as_ref()),
+            Err(_) => false,
+        }
+    }
+
+    fn get_value_types(&self) -> &'static [ConditionValueType] {
+        self.value_types
     }
 }
 
@@ -309,25 +662,71 @@ pub enum ModelAttribResult {
 
     /// Login Condition Result
     Login(Login),
+
+    /// Employee Condition Result
+    Employee(Employee),
+
+    /// Time and Field Conditions Result
+    Compare(Compare),
 }
 
 impl<'a> Condition<'a> for ModelAttribResult {
     fn validate(
         &self,
-        attribute: Option<i32>,
+        attribute: Option<AttribValue>,
         subject: Option<&'a str>,
         condition_value: &'a str,
     ) -> bool {
         match self {
             Self::Owner(owner) => owner.validate(attribute, subject, condition_value),
             Self::Login(login) => login.validate(attribute, subject, condition_value),
+            Self::Employee(employee) => employee.validate(attribute, subject, condition_value),
+            Self::Compare(compare) => compare.validate(attribute, subject, condition_value),
         }
     }
 
-    fn get_value_type(&self) -> ConditionValueType {
+    fn get_value_types(&self) -> &'static [ConditionValueType] {
         match self {
-            Self::Owner(owner) => owner.get_value_type(),
-            Self::Login(login) => login.get_value_type(),
+            Self::Owner(owner) => owner.get_value_types(),
+            Self::Login(login) => login.get_value_types(),
+            Self::Employee(employee) => employee.get_value_types(),
+            Self::Compare(compare) => compare.get_value_types(),
+        }
+    }
+}
+
+/// Fields of the models that conditions can check
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ModelField {
+    Language,
+    Approved,
+    ReleaseDate,
+    Source,
+    Name,
+    Number,
+    Object,
+    Action,
+    CreateTime,
+    UpdateTime,
+}
+
+impl TryFrom<&str> for ModelField {
+    type Error = RouterError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "language" => Ok(Self::Language),
+            "approved" => Ok(Self::Approved),
+            "releaseDate" => Ok(Self::ReleaseDate),
+            "source" => Ok(Self::Source),
+            "name" => Ok(Self::Name),
+            "number" => Ok(Self::Number),
+            "object" => Ok(Self::Object),
+            "action" => Ok(Self::Action),
+            "createTime" => Ok(Self::CreateTime),
+            "updateTime" => Ok(Self::UpdateTime),
+
+            _ => Err(RouterError::from_predefined("MODEL_ATTRIBUTE_NOT_DEFINED")),
         }
     }
 }
@@ -336,6 +735,15 @@ impl<'a> Condition<'a> for ModelAttribResult {
 pub enum ModelAttrib {
     Owner,
     Login,
+
+    /// Subject is employee of the organization that owns the model
+    Employee,
+
+    /// Time of the request
+    Time,
+
+    /// Field of the model
+    Field(ModelField),
 }
 
 impl From<ModelAttrib> for ModelAttribResult {
@@ -345,6 +753,18 @@ impl From<ModelAttrib> for ModelAttribResult {
         match value {
             ModelAttrib::Owner => ModelAttribResult::Owner(Owner {}),
             ModelAttrib::Login => ModelAttribResult::Login(Login {}),
+            ModelAttrib::Employee => ModelAttribResult::Employee(Employee::default()),
+            ModelAttrib::Time => ModelAttribResult::Compare(Compare {
+                value_types: &[ConditionValueType::Comparison, ConditionValueType::Window],
+            }),
+            ModelAttrib::Field(_) => ModelAttribResult::Compare(Compare {
+                value_types: &[
+                    ConditionValueType::Boolean,
+                    ConditionValueType::Comparison,
+                    ConditionValueType::Membership,
+                    ConditionValueType::Window,
+                ],
+            }),
         }
     }
 }
@@ -358,45 +778,224 @@ impl TryFrom<&str> for ModelAttrib {
         match value {
             "isOwner" => Ok(Self::Owner),
             "isLoggedIn" => Ok(Self::Login),
+            "isEmployee" | "isOrgMember" => Ok(Self::Employee),
+            "time" => Ok(Self::Time),
 
-            _ => Err(RouterError::from_predefined("MODEL_ATTRIBUTE_NOT_DEFINED")),
+            field => Ok(Self::Field(ModelField::try_from(field)?)),
+        }
+    }
+}
+
+/// Returns the fields of the model that conditions can check
+pub trait ModelFields {
+    fn get_field(&self, field: ModelField) -> Option<AttribValue>;
+}
+
+impl ModelFields for User {
+    fn get_field(&self, field: ModelField) -> Option<AttribValue> {
+        match field {
+            ModelField::Language => self.language.clone().map(AttribValue::from),
+            ModelField::CreateTime => Some(self.created_at.into()),
+            ModelField::UpdateTime => Some(self.updated_at.into()),
+            _ => None,
+        }
+    }
+}
+
+impl ModelFields for Organization {
+    fn get_field(&self, field: ModelField) -> Option<AttribValue> {
+        match field {
+            ModelField::CreateTime => Some(self.created_at.into()),
+            ModelField::UpdateTime => Some(self.updated_at.into()),
+            _ => None,
+        }
+    }
+}
+
+impl ModelFields for Translation {
+    fn get_field(&self, field: ModelField) -> Option<AttribValue> {
+        match field {
+            ModelField::Language => Some(self.language.clone().into()),
+            ModelField::Approved => Some(self.approved.into()),
+            ModelField::ReleaseDate => self
+                .release_date
+                .and_then(|date| date.and_hms_opt(0, 0, 0))
+                .map(AttribValue::from),
+            ModelField::Source => self.source.clone().map(AttribValue::from),
+            ModelField::CreateTime => Some(self.created_at.into()),
+            ModelField::UpdateTime => Some(self.updated_at.into()),
+            _ => None,
+        }
+    }
+}
+
+impl ModelFields for QuranMushaf {
+    fn get_field(&self, field: ModelField) -> Option<AttribValue> {
+        match field {
+            ModelField::Name => self.short_name.clone().map(AttribValue::from),
+            ModelField::Source => self.source.clone().map(AttribValue::from),
+            ModelField::CreateTime => Some(self.created_at.into()),
+            ModelField::UpdateTime => Some(self.updated_at.into()),
+            _ => None,
+        }
+    }
+}
+
+impl ModelFields for QuranSurah {
+    fn get_field(&self, field: ModelField) -> Option<AttribValue> {
+        match field {
+            ModelField::Name => Some(self.name.clone().into()),
+            ModelField::Number => Some(self.number.into()),
+            ModelField::CreateTime => Some(self.created_at.into()),
+            ModelField::UpdateTime => Some(self.updated_at.into()),
+            _ => None,
+        }
+    }
+}
+
+impl ModelFields for QuranAyah {
+    fn get_field(&self, field: ModelField) -> Option<AttribValue> {
+        match field {
+            ModelField::Number => Some(self.ayah_number.into()),
+            ModelField::CreateTime => Some(self.created_at.into()),
+            ModelField::UpdateTime => Some(self.updated_at.into()),
+            _ => None,
+        }
+    }
+}
+
+impl ModelFields for QuranWord {
+    fn get_field(&self, field: ModelField) -> Option<AttribValue> {
+        match field {
+            ModelField::CreateTime => Some(self.created_at.into()),
+            ModelField::UpdateTime => Some(self.updated_at.into()),
+            _ => None,
+        }
+    }
+}
+
+impl ModelFields for Permission {
+    fn get_field(&self, field: ModelField) -> Option<AttribValue> {
+        match field {
+            ModelField::Object => Some(self.object.clone().into()),
+            ModelField::Action => Some(self.action.clone().into()),
+            ModelField::CreateTime => Some(self.created_at.into()),
+            ModelField::UpdateTime => Some(self.updated_at.into()),
+            _ => None,
+        }
+    }
+}
+
+impl ModelFields for Role {
+    fn get_field(&self, field: ModelField) -> Option<AttribValue> {
+        match field {
+            ModelField::Name => Some(self.name.clone().into()),
+            ModelField::CreateTime => Some(self.created_at.into()),
+            ModelField::UpdateTime => Some(self.updated_at.into()),
+            _ => None,
+        }
+    }
+}
+
+impl ModelFields for Group {
+    fn get_field(&self, field: ModelField) -> Option<AttribValue> {
+        match field {
+            ModelField::Name => Some(self.name.clone().into()),
+            ModelField::CreateTime => Some(self.created_at.into()),
+            ModelField::UpdateTime => Some(self.updated_at.into()),
+            _ => None,
+        }
+    }
+}
+
+#[async_trait]
+impl ModelPermission<ModelAttrib, AttribValue> for User {
+    async fn get_attr(&self, name: ModelAttrib) -> Option<AttribValue> {
+        match name {
+            ModelAttrib::Owner => Some(self.account_id.into()),
+            ModelAttrib::Login => None,
+            ModelAttrib::Employee => None,
+            ModelAttrib::Time => None,
+            ModelAttrib::Field(field) => self.get_field(field),
         }
     }
 }
 
 #[async_trait]
-impl ModelPermission<ModelAttrib, i32> for User {
-    async fn get_attr(&self, name: ModelAttrib) -> Option<i32> {
+impl ModelPermission<ModelAttrib, AttribValue> for Organization {
+    async fn get_attr(&self, name: ModelAttrib) -> Option<AttribValue> {
         match name {
-            ModelAttrib::Owner => Some(self.account_id),
+            ModelAttrib::Owner => Some(self.owner_account_id.into()),
             ModelAttrib::Login => None,
+            ModelAttrib::Employee => Some(self.account_id.into()),
+            ModelAttrib::Time => None,
+            ModelAttrib::Field(field) => self.get_field(field),
         }
     }
 }
 
 #[async_trait]
-impl ModelPermission<ModelAttrib, i32> for Organization {
-    async fn get_attr(&self, name: ModelAttrib) -> Option<i32> {
+impl<T> ModelPermission<ModelAttrib, AttribValue> for OwnedModel<T>
+where
+    T: ModelFields + Send + Sync,
+{
+    async fn get_attr(&self, name: ModelAttrib) -> Option<AttribValue> {
         match name {
-            ModelAttrib::Owner => Some(self.owner_account_id),
+            ModelAttrib::Owner => Some(self.owner_account_id.into()),
             ModelAttrib::Login => None,
+            ModelAttrib::Employee => Some(self.owner_account_id.into()),
+            ModelAttrib::Time => None,
+            ModelAttrib::Field(field) => self.model.get_field(field),
+        }
+    }
+}
+
+#[async_trait]
+impl ModelPermission<ModelAttrib, AttribValue> for Translation {
+    async fn get_attr(&self, name: ModelAttrib) -> Option<AttribValue> {
+        match name {
+            ModelAttrib::Owner => Some(self.translator_account_id.into()),
+            ModelAttrib::Login => None,
+
+            // Translator is the organization when it's published by one
+            ModelAttrib::Employee => Some(self.translator_account_id.into()),
+
+            ModelAttrib::Time => None,
+            ModelAttrib::Field(field) => self.get_field(field),
         }
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{Condition, Login, ModelAttrib, Owner};
+    use super::{
+        resource_id, AccountSubjects, Action, AuthZController, Condition, Employee, Login,
+        ModelAttrib, ModelAttribResult, ModelField, Owner,
+    };
+    use crate::condition::{AttribValue, ConditionOperator, ConditionValueType};
+    use crate::models::{
+        Account, NewAccount, NewOrganization, NewOrganizationName, NewPermission,
+        NewPermissionCondition, Role, User,
+    };
+    use crate::route_metadata::declared_routes;
+    use crate::routers::account::create_user_with_email;
+    use crate::select_model::OwnedModel;
+    use crate::test_db;
+    use actix_web::http::{header::HeaderMap, Method, Uri};
+    use auth_z::{CheckPermission, ModelPermission, ParsedPath, Subject};
+    use chrono::Utc;
+    use diesel::prelude::*;
+    use uuid::Uuid;
 
     #[test]
     fn test_login_condition() {
         let login = Login {};
 
         // This should return true
-        assert_eq!(login.validate(None, Some("user"), ""), true);
+        assert!(login.validate(None, Some("user"), ""));
 
         // This should return false
-        assert_eq!(login.validate(None, None, ""), false);
+        assert!(!login.validate(None, None, ""));
     }
 
     #[test]
@@ -404,16 +1003,18 @@ mod tests {
         let owner = Owner {};
 
         // This should return true
-        assert_eq!(owner.validate(None, Some("user"), ""), true);
+        assert!(owner.validate(None, Some("user"), ""));
 
         // This should return false
-        assert_eq!(owner.validate(None, None, ""), false);
+        assert!(!owner.validate(None, None, ""));
 
-        assert_eq!(owner.validate(Some(1), Some("1"), "true"), true);
+        assert!(owner.validate(Some(1.into()), Some("1"), "true"));
     }
 
     #[test]
     fn test_model_attrib() {
+        crate::error::load_predefined_errors();
+
         assert_eq!(
             ModelAttrib::try_from("isOwner").unwrap(),
             ModelAttrib::Owner
@@ -422,5 +1023,249 @@ mod tests {
             ModelAttrib::try_from("isLoggedIn").unwrap(),
             ModelAttrib::Login
         );
+        assert_eq!(
+            ModelAttrib::try_from("isEmployee").unwrap(),
+            ModelAttrib::Employee
+        );
+        assert_eq!(
+            ModelAttrib::try_from("isOrgMember").unwrap(),
+            ModelAttrib::Employee
+        );
+        assert_eq!(ModelAttrib::try_from("time").unwrap(), ModelAttrib::Time);
+        assert_eq!(
+            ModelAttrib::try_from("language").unwrap(),
+            ModelAttrib::Field(ModelField::Language)
+        );
+        assert!(ModelAttrib::try_from("unknown").is_err());
+    }
+
+    #[test]
+    fn test_field_condition() {
+        let language = ModelAttribResult::from(ModelAttrib::Field(ModelField::Language));
+        let persian = Some(AttribValue::from("fa".to_string()));
+
+        assert!(language.validate(persian.clone(), None, "== \"fa\""));
+        assert!(language.validate(persian.clone(), None, "in [fa, ar]"));
+        assert!(!language.validate(persian, None, "== en"));
+        assert!(!language.validate(None, None, "== fa"));
+
+        // Time only accepts comparisons and windows
+        let time = ModelAttribResult::from(ModelAttrib::Time);
+        assert!(!time
+            .get_value_types()
+            .contains(&ConditionValueType::Membership));
+        assert!(language
+            .get_value_types()
+            .contains(&ConditionValueType::Membership));
+    }
+
+    #[test]
+    fn test_employee_condition() {
+        let employee = Employee::new(vec![10, 20]);
+
+        assert!(employee.validate(Some(10.into()), Some("1"), "true"));
+        assert!(!employee.validate(Some(30.into()), Some("1"), "true"));
+        assert!(employee.validate(Some(30.into()), Some("1"), "false"));
+
+        // Model without organization
+        assert!(!employee.validate(None, Some("1"), "true"));
+
+        // Not logged in
+        assert!(!employee.validate(Some(10.into()), None, "true"));
+
+        // Default has no organization, it's only used for the value type
+        let default = ModelAttribResult::from(ModelAttrib::Employee);
+        assert!(!default.validate(Some(10.into()), Some("1"), "true"));
+    }
+
+    #[test]
+    fn test_account_subjects_accounts() {
+        // Anonymous requests have no account
+        assert!(AccountSubjects::default().accounts().is_empty());
+
+        let subjects = AccountSubjects {
+            account: Some(1),
+            organizations: vec![2, 3],
+            ..Default::default()
+        };
+
+        assert_eq!(subjects.accounts(), vec![2, 3, 1]);
+    }
+
+    #[test]
+    fn test_resource_id() {
+        let uuid = "c4d4a9b2-4c1a-4b0e-9f7a-2f3c6a9b8e11";
+
+        let path = ParsedPath::from(format!("/organization/{}", uuid).as_str());
+        assert_eq!(resource_id(&path), Some(uuid));
+
+        let path = ParsedPath::from(format!("/organization/name/{}", uuid).as_str());
+        assert_eq!(resource_id(&path), Some(uuid));
+
+        // Sub resources belong to the resource
+        let path = ParsedPath::from(format!("/organization/{}/employees", uuid).as_str());
+        assert_eq!(resource_id(&path), Some(uuid));
+
+        let path = ParsedPath::from("/organization");
+        assert_eq!(resource_id(&path), None);
+    }
+
+    #[test]
+    fn test_sub_resource_action() {
+        let uuid = "c4d4a9b2-4c1a-4b0e-9f7a-2f3c6a9b8e11";
+        let path = ParsedPath::from(format!("/organization/{}/employees/{}", uuid, uuid).as_str());
+
+        assert!(matches!(Action::from_auth_z(&path, "GET"), Action::View));
+        assert!(matches!(Action::from_auth_z(&path, "POST"), Action::Edit));
+        assert!(matches!(Action::from_auth_z(&path, "DELETE"), Action::Edit));
+
+        let path = ParsedPath::from(format!("/organization/{}", uuid).as_str());
+        assert!(matches!(
+            Action::from_auth_z(&path, "DELETE"),
+            Action::Delete
+        ));
+    }
+
+    /// Creates an organization of the owner with a name,
+    /// returns the uuid of the name
+    fn create_named_organization(conn: &mut PgConnection, owner: &User) -> Uuid {
+        use crate::schema::app_accounts::dsl::app_accounts;
+        use crate::schema::app_organization_names::dsl::{app_organization_names, uuid};
+        use crate::schema::app_organizations::dsl::app_organizations;
+
+        let account: Account = NewAccount {
+            username: &format!("o{}", &Uuid::new_v4().simple().to_string()[..12]),
+            account_type: &"organization".to_string(),
+        }
+        .insert_into(app_accounts)
+        .get_result(conn)
+        .unwrap();
+
+        NewOrganization {
+            creator_user_id: owner.id,
+            account_id: account.id,
+            owner_account_id: owner.account_id,
+            profile_image: None,
+            established_date: Utc::now().date_naive(),
+            national_id: "12345678901".to_string(),
+        }
+        .insert_into(app_organizations)
+        .execute(conn)
+        .unwrap();
+
+        NewOrganizationName {
+            creator_user_id: owner.id,
+            account_id: account.id,
+            name: "Natiq".to_string(),
+            language: "default".to_string(),
+        }
+        .insert_into(app_organization_names)
+        .returning(uuid)
+        .get_result(conn)
+        .unwrap()
+    }
+
+    /// Grants editing the organizations that the account owns
+    fn grant_owner_edit(conn: &mut PgConnection, user: &User) {
+        use crate::schema::app_permission_conditions::dsl::app_permission_conditions;
+        use crate::schema::app_permissions::dsl::{app_permissions, id};
+
+        let permission: i32 = NewPermission {
+            creator_user_id: user.id,
+            account_id: Some(user.account_id),
+            role_id: None,
+            group_id: None,
+            object: &"organization".to_string(),
+            action: &"edit".to_string(),
+            condition_operator: ConditionOperator::default().as_str(),
+            pseudo_subject: None,
+        }
+        .insert_into(app_permissions)
+        .returning(id)
+        .get_result(conn)
+        .unwrap();
+
+        NewPermissionCondition {
+            creator_user_id: user.id,
+            permission_id: permission,
+            name: "isOwner".to_string(),
+            value: "true".to_string(),
+        }
+        .insert_into(app_permission_conditions)
+        .execute(conn)
+        .unwrap();
+    }
+
+    #[actix_web::test]
+    #[ignore = "needs TEST_DATABASE_URL"]
+    async fn test_organization_name_owner() {
+        let pool = test_db::pool();
+
+        crate::error::load_predefined_errors();
+
+        let mut conn = pool.get().unwrap();
+
+        let owner =
+            create_user_with_email(&mut conn, &format!("{}@natiq.local", Uuid::new_v4())).unwrap();
+        let other =
+            create_user_with_email(&mut conn, &format!("{}@natiq.local", Uuid::new_v4())).unwrap();
+
+        let name_uuid = create_named_organization(&mut conn, &owner).to_string();
+
+        grant_owner_edit(&mut conn, &owner);
+        grant_owner_edit(&mut conn, &other);
+
+        let route = declared_routes().resolve(
+            &Method::DELETE,
+            &format!("/organization/name/{}", name_uuid),
+        );
+
+        let controller = AuthZController::new(pool.clone());
+
+        let check = |account: i32| {
+            controller.check(
+                "127.0.0.1:8080".parse().unwrap(),
+                HeaderMap::new(),
+                Uri::from_static("/organization/name"),
+                Subject::Account(account as u32),
+                route.clone(),
+                "DELETE".to_string(),
+            )
+        };
+
+        assert!(check(owner.account_id).await.is_ok());
+        assert!(check(other.account_id).await.is_err());
+    }
+
+    #[actix_web::test]
+    async fn test_owned_model_attr() {
+        let now = Utc::now().naive_utc();
+
+        let model = OwnedModel {
+            model: Role {
+                id: 1,
+                uuid: uuid::Uuid::new_v4(),
+                creator_user_id: 1,
+                name: "translators".to_string(),
+                description: None,
+                created_at: now,
+                updated_at: now,
+            },
+            owner_account_id: 7,
+        };
+
+        assert_eq!(model.get_attr(ModelAttrib::Owner).await, Some(7.into()));
+        assert_eq!(model.get_attr(ModelAttrib::Employee).await, Some(7.into()));
+        assert_eq!(model.get_attr(ModelAttrib::Login).await, None);
+        assert_eq!(
+            model.get_attr(ModelAttrib::Field(ModelField::Name)).await,
+            Some("translators".to_string().into())
+        );
+        assert_eq!(
+            model
+                .get_attr(ModelAttrib::Field(ModelField::Language))
+                .await,
+            None
+        );
     }
 }
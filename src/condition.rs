@@ -0,0 +1,477 @@
+use std::cmp::Ordering;
+use std::fmt;
+
+use chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime};
+use serde::{Deserialize, Serialize};
+
+use crate::error::RouterError;
+
+/// How the conditions of a permission are combined
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ConditionOperator {
+    /// Every condition must be satisfied
+    #[default]
+    And,
+
+    /// One satisfied condition is enough
+    Or,
+}
+
+impl ConditionOperator {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::And => "and",
+            Self::Or => "or",
+        }
+    }
+
+    /// Combines the condition results
+    pub fn combine(&self, results: &[bool]) -> bool {
+        match self {
+            Self::And => results.iter().all(|result| *result),
+            Self::Or => results.iter().any(|result| *result),
+        }
+    }
+}
+
+impl From<&str> for ConditionOperator {
+    fn from(value: &str) -> Self {
+        match value {
+            "or" => Self::Or,
+            _ => Self::And,
+        }
+    }
+}
+
+/// Value of a model attribute that conditions are checked against
+#[derive(Debug, Clone, PartialEq)]
+pub enum AttribValue {
+    Number(i64),
+    Text(String),
+    Boolean(bool),
+    Time(NaiveDateTime),
+}
+
+impl From<i32> for AttribValue {
+    fn from(value: i32) -> Self {
+        Self::Number(value as i64)
+    }
+}
+
+impl From<String> for AttribValue {
+    fn from(value: String) -> Self {
+        Self::Text(value)
+    }
+}
+
+impl From<bool> for AttribValue {
+    fn from(value: bool) -> Self {
+        Self::Boolean(value)
+    }
+}
+
+impl From<NaiveDateTime> for AttribValue {
+    fn from(value: NaiveDateTime) -> Self {
+        Self::Time(value)
+    }
+}
+
+impl fmt::Display for AttribValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Number(value) => write!(f, "{}", value),
+            Self::Text(value) => write!(f, "{}", value),
+            Self::Boolean(value) => write!(f, "{}", value),
+            Self::Time(value) => write!(f, "{}", value),
+        }
+    }
+}
+
+/// A single value that is written in the condition
+#[derive(Debug, Clone, PartialEq)]
+pub enum Literal {
+    Number(i64),
+    Text(String),
+    Boolean(bool),
+
+    /// `2026-01-01` or `2026-01-01T08:00:00`
+    Date(NaiveDateTime),
+
+    /// `08:00`, compared with the time part of the attribute
+    TimeOfDay(NaiveTime),
+}
+
+impl From<&str> for Literal {
+    fn from(value: &str) -> Self {
+        let value = value.trim();
+
+        // Quoted values are always text, `"10"` is not a number
+        if value.len() >= 2 && value.starts_with('"') && value.ends_with('"') {
+            return Self::Text(value[1..value.len() - 1].to_string());
+        }
+
+        if let Ok(number) = value.parse::<i64>() {
+            return Self::Number(number);
+        }
+
+        if let Ok(boolean) = value.parse::<bool>() {
+            return Self::Boolean(boolean);
+        }
+
+        if let Ok(time) = DateTime::parse_from_rfc3339(value) {
+            return Self::Date(time.naive_utc());
+        }
+
+        if let Ok(time) = NaiveDateTime::parse_from_str(value, "%Y-%m-%dT%H:%M:%S") {
+            return Self::Date(time);
+        }
+
+        if let Ok(date) = NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+            return Self::Date(date.and_hms_opt(0, 0, 0).unwrap());
+        }
+
+        for format in ["%H:%M", "%H:%M:%S"] {
+            if let Ok(time) = NaiveTime::parse_from_str(value, format) {
+                return Self::TimeOfDay(time);
+            }
+        }
+
+        Self::Text(value.to_string())
+    }
+}
+
+impl Literal {
+    /// Compares the attribute with the literal,
+    /// None if they are not comparable
+    fn compare(&self, attr: &AttribValue) -> Option<Ordering> {
+        match (attr, self) {
+            (AttribValue::Number(a), Self::Number(b)) => Some(a.cmp(b)),
+            (AttribValue::Text(a), Self::Text(b)) => Some(a.as_str().cmp(b.as_str())),
+            (AttribValue::Boolean(a), Self::Boolean(b)) => Some(a.cmp(b)),
+            (AttribValue::Time(a), Self::Date(b)) => Some(a.cmp(b)),
+            (AttribValue::Time(a), Self::TimeOfDay(b)) => Some(a.time().cmp(b)),
+
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompareOperator {
+    Equal,
+    NotEqual,
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+}
+
+impl CompareOperator {
+    fn accepts(&self, ordering: Ordering) -> bool {
+        match self {
+            Self::Equal => ordering.is_eq(),
+            Self::NotEqual => ordering.is_ne(),
+            Self::Less => ordering.is_lt(),
+            Self::LessOrEqual => ordering.is_le(),
+            Self::Greater => ordering.is_gt(),
+            Self::GreaterOrEqual => ordering.is_ge(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConditionValueType {
+    /// `true` or `false`
+    Boolean,
+
+    /// `== "fa"`, `!= 1`, `< 2026-01-01`, `>= 10`
+    Comparison,
+
+    /// `in [fa, ar]`, `not in [fa, ar]`
+    Membership,
+
+    /// `08:00..17:00`, `2026-01-01..2026-02-01`
+    Window,
+}
+
+impl TryFrom<&str> for ConditionValueType {
+    type Error = RouterError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        Ok(ConditionValue::try_from(value)?.value_type())
+    }
+}
+
+/// Parsed value of a permission condition
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConditionValue {
+    Boolean(bool),
+    Compare(CompareOperator, Literal),
+    In(Vec<Literal>),
+    NotIn(Vec<Literal>),
+
+    /// Start is included and end is not,
+    /// time of day windows can pass midnight (`22:00..06:00`)
+    Window(Literal, Literal),
+}
+
+impl TryFrom<&str> for ConditionValue {
+    type Error = RouterError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        let value = value.trim();
+
+        let not_defined = || RouterError::from_predefined("AUTHZ_CONDITION_VALUE_NOT_DEFINED");
+
+        match value {
+            "true" => return Ok(Self::Boolean(true)),
+            "false" => return Ok(Self::Boolean(false)),
+            _ => {}
+        }
+
+        // Longer operators must be checked first
+        let operators = [
+            ("==", CompareOperator::Equal),
+            ("!=", CompareOperator::NotEqual),
+            ("<=", CompareOperator::LessOrEqual),
+            (">=", CompareOperator::GreaterOrEqual),
+            ("<", CompareOperator::Less),
+            (">", CompareOperator::Greater),
+        ];
+
+        for (prefix, operator) in operators {
+            if let Some(rest) = value.strip_prefix(prefix) {
+                if rest.trim().is_empty() {
+                    return Err(not_defined());
+                }
+
+                return Ok(Self::Compare(operator, Literal::from(rest)));
+            }
+        }
+
+        if let Some(rest) = value.strip_prefix("not in") {
+            return Ok(Self::NotIn(parse_list(rest).ok_or_else(not_defined)?));
+        }
+
+        if let Some(rest) = value.strip_prefix("in") {
+            return Ok(Self::In(parse_list(rest).ok_or_else(not_defined)?));
+        }
+
+        if let Some((start, end)) = value.split_once("..") {
+            let (start, end) = (Literal::from(start), Literal::from(end));
+
+            // Both sides must be the same kind of value
+            if std::mem::discriminant(&start) != std::mem::discriminant(&end) {
+                return Err(not_defined());
+            }
+
+            return Ok(Self::Window(start, end));
+        }
+
+        Err(not_defined())
+    }
+}
+
+/// Parses `[a, "b", 1]`
+fn parse_list(value: &str) -> Option<Vec<Literal>> {
+    let items = value
+        .trim()
+        .strip_prefix('[')?
+        .strip_suffix(']')?
+        .split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(Literal::from)
+        .collect::<Vec<Literal>>();
+
+    if items.is_empty() {
+        return None;
+    }
+
+    Some(items)
+}
+
+impl ConditionValue {
+    pub fn value_type(&self) -> ConditionValueType {
+        match self {
+            Self::Boolean(_) => ConditionValueType::Boolean,
+            Self::Compare(..) => ConditionValueType::Comparison,
+            Self::In(_) | Self::NotIn(_) => ConditionValueType::Membership,
+            Self::Window(..) => ConditionValueType::Window,
+        }
+    }
+
+    /// Checks the attribute against the value
+    ///
+    /// Missing attributes and values that can't be
+    /// compared never match
+    pub fn matches(&self, attr: Option<&AttribValue>) -> bool {
+        let Some(attr) = attr else {
+            return false;
+        };
+
+        match self {
+            Self::Boolean(value) => attr == &AttribValue::Boolean(*value),
+
+            Self::Compare(operator, literal) => literal
+                .compare(attr)
+                .is_some_and(|ordering| operator.accepts(ordering)),
+
+            Self::In(literals) => literals
+                .iter()
+                .any(|literal| literal.compare(attr).is_some_and(Ordering::is_eq)),
+
+            Self::NotIn(literals) => literals.iter().all(|literal| {
+                literal
+                    .compare(attr)
+                    .is_some_and(|ordering| ordering.is_ne())
+            }),
+
+            Self::Window(start, end) => {
+                let (Some(after_start), Some(before_end)) = (
+                    start.compare(attr).map(Ordering::is_ge),
+                    end.compare(attr).map(Ordering::is_lt),
+                ) else {
+                    return false;
+                };
+
+                match (start, end) {
+                    (Literal::TimeOfDay(s), Literal::TimeOfDay(e)) if s > e => {
+                        after_start || before_end
+                    }
+
+                    _ => after_start && before_end,
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        AttribValue, CompareOperator, ConditionOperator, ConditionValue, ConditionValueType,
+        Literal,
+    };
+    use chrono::NaiveDate;
+
+    fn time(hour: u32, minute: u32) -> AttribValue {
+        AttribValue::Time(
+            NaiveDate::from_ymd_opt(2026, 10, 18)
+                .unwrap()
+                .and_hms_opt(hour, minute, 0)
+                .unwrap(),
+        )
+    }
+
+    #[test]
+    fn test_literal() {
+        assert_eq!(Literal::from("\"fa\""), Literal::Text("fa".to_string()));
+        assert_eq!(Literal::from("fa"), Literal::Text("fa".to_string()));
+        assert_eq!(Literal::from("\"10\""), Literal::Text("10".to_string()));
+        assert_eq!(Literal::from(" 10 "), Literal::Number(10));
+        assert_eq!(Literal::from("true"), Literal::Boolean(true));
+        assert!(matches!(Literal::from("2026-01-01"), Literal::Date(_)));
+        assert!(matches!(
+            Literal::from("2026-01-01T08:00:00Z"),
+            Literal::Date(_)
+        ));
+        assert!(matches!(Literal::from("08:30"), Literal::TimeOfDay(_)));
+    }
+
+    #[test]
+    fn test_parse_condition_value() {
+        crate::error::load_predefined_errors();
+
+        assert_eq!(
+            ConditionValue::try_from("true").unwrap(),
+            ConditionValue::Boolean(true)
+        );
+        assert_eq!(
+            ConditionValue::try_from("== \"fa\"").unwrap(),
+            ConditionValue::Compare(CompareOperator::Equal, Literal::Text("fa".to_string()))
+        );
+        assert_eq!(
+            ConditionValue::try_from(">= 10").unwrap(),
+            ConditionValue::Compare(CompareOperator::GreaterOrEqual, Literal::Number(10))
+        );
+        assert_eq!(
+            ConditionValue::try_from("not in [fa, ar]")
+                .unwrap()
+                .value_type(),
+            ConditionValueType::Membership
+        );
+        assert_eq!(
+            ConditionValueType::try_from("08:00..17:00").unwrap(),
+            ConditionValueType::Window
+        );
+
+        assert!(ConditionValue::try_from("fa").is_err());
+        assert!(ConditionValue::try_from("==").is_err());
+        assert!(ConditionValue::try_from("in []").is_err());
+        assert!(ConditionValue::try_from("08:00..2026-01-01").is_err());
+    }
+
+    #[test]
+    fn test_comparison() {
+        let persian = AttribValue::from("fa".to_string());
+
+        assert!(ConditionValue::try_from("== \"fa\"")
+            .unwrap()
+            .matches(Some(&persian)));
+        assert!(!ConditionValue::try_from("!= fa")
+            .unwrap()
+            .matches(Some(&persian)));
+        assert!(ConditionValue::try_from("< 10")
+            .unwrap()
+            .matches(Some(&AttribValue::from(9))));
+
+        // Not comparable values and missing attributes never match
+        assert!(!ConditionValue::try_from("!= 10")
+            .unwrap()
+            .matches(Some(&persian)));
+        assert!(!ConditionValue::try_from("== fa").unwrap().matches(None));
+    }
+
+    #[test]
+    fn test_membership() {
+        let arabic = AttribValue::from("ar".to_string());
+        let english = AttribValue::from("en".to_string());
+
+        let value = ConditionValue::try_from("in [fa, \"ar\"]").unwrap();
+        assert!(value.matches(Some(&arabic)));
+        assert!(!value.matches(Some(&english)));
+
+        let value = ConditionValue::try_from("not in [fa, ar]").unwrap();
+        assert!(!value.matches(Some(&arabic)));
+        assert!(value.matches(Some(&english)));
+    }
+
+    #[test]
+    fn test_window() {
+        let office_hours = ConditionValue::try_from("08:00..17:00").unwrap();
+        assert!(office_hours.matches(Some(&time(8, 0))));
+        assert!(!office_hours.matches(Some(&time(17, 0))));
+
+        // Passes midnight
+        let night = ConditionValue::try_from("22:00..06:00").unwrap();
+        assert!(night.matches(Some(&time(23, 30))));
+        assert!(night.matches(Some(&time(5, 0))));
+        assert!(!night.matches(Some(&time(12, 0))));
+
+        let october = ConditionValue::try_from("2026-10-01..2026-11-01").unwrap();
+        assert!(october.matches(Some(&time(12, 0))));
+    }
+
+    #[test]
+    fn test_condition_operator() {
+        assert!(ConditionOperator::And.combine(&[true, true]));
+        assert!(!ConditionOperator::And.combine(&[true, false]));
+        assert!(ConditionOperator::Or.combine(&[false, true]));
+        assert!(!ConditionOperator::Or.combine(&[false, false]));
+
+        assert_eq!(ConditionOperator::from("or"), ConditionOperator::Or);
+        assert_eq!(ConditionOperator::default().as_str(), "and");
+    }
+}
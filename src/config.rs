@@ -0,0 +1,721 @@
+use crate::email::SmtpTls;
+use crate::email_outbox::DEFAULT_RETENTION_DAYS as DEFAULT_OUTBOX_RETENTION_DAYS;
+use crate::error_log::DEFAULT_RETENTION_DAYS;
+use crate::logging::LogConfig;
+use actix_cors::Cors;
+use actix_web::http::Uri;
+use lettre::message::Mailbox;
+use serde::Deserialize;
+use std::{
+    env,
+    fmt::{self, Display},
+    fs,
+    net::SocketAddr,
+    path::PathBuf,
+    str::FromStr,
+    time::Duration,
+};
+
+/// Path of the config file, only the env vars are used if it's not set
+pub const CONFIG_FILE_ENV: &str = "CONFIG_FILE";
+
+/// Any origin is allowed when the CORS origins has it
+const ANY_ORIGIN: &str = "*";
+
+/// Sender of the emails when we don't send them to a real smtp server
+const DEFAULT_EMAIL_FROM: &str = "Natiq <noreply@natiq.local>";
+
+/// Every problem of the config, they are reported together
+#[derive(Debug)]
+pub struct ConfigError(Vec<String>);
+
+impl Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Invalid configuration:")?;
+
+        for error in &self.0 {
+            writeln!(f, "  - {}", error)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+#[derive(Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ServerConfig {
+    pub bind: SocketAddr,
+
+    /// Number of the http workers, actix selects it from the cpus if not set
+    pub workers: Option<usize>,
+
+    /// Allowed origins, like `https://natiq.net`, or `*` for any
+    pub cors_origins: Vec<String>,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            bind: SocketAddr::from(([0, 0, 0, 0], 8080)),
+            workers: None,
+            cors_origins: vec![ANY_ORIGIN.to_string()],
+        }
+    }
+}
+
+impl ServerConfig {
+    pub fn cors(&self) -> Cors {
+        if self.cors_origins.iter().any(|origin| origin == ANY_ORIGIN) {
+            return Cors::permissive();
+        }
+
+        self.cors_origins
+            .iter()
+            .fold(Cors::default(), |cors, origin| cors.allowed_origin(origin))
+            .allow_any_method()
+            .allow_any_header()
+            .expose_any_header()
+            .max_age(3600)
+    }
+}
+
+#[derive(Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DatabaseConfig {
+    pub url: String,
+    pub pool_size: u32,
+
+    /// Max wait for a connection of the pool
+    pub connection_timeout_seconds: u64,
+
+    /// Idle connections are closed after this
+    pub idle_timeout_seconds: u64,
+}
+
+/// Same as the r2d2 defaults
+impl Default for DatabaseConfig {
+    fn default() -> Self {
+        Self {
+            url: String::new(),
+            pool_size: 10,
+            connection_timeout_seconds: 30,
+            idle_timeout_seconds: 600,
+        }
+    }
+}
+
+impl DatabaseConfig {
+    pub fn connection_timeout(&self) -> Duration {
+        Duration::from_secs(self.connection_timeout_seconds)
+    }
+
+    pub fn idle_timeout(&self) -> Duration {
+        Duration::from_secs(self.idle_timeout_seconds)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TransportKind {
+    #[default]
+    Smtp,
+
+    /// Writes emails to the `file_dir`
+    File,
+}
+
+impl FromStr for TransportKind {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "smtp" => Ok(Self::Smtp),
+            "file" => Ok(Self::File),
+            _ => Err(format!(
+                "Unknown email transport `{}`, use smtp or file",
+                value
+            )),
+        }
+    }
+}
+
+#[derive(Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct EmailConfig {
+    pub transport: TransportKind,
+
+    /// Sender of the emails, must be set for the smtp transport
+    pub from: Option<String>,
+
+    pub file_dir: PathBuf,
+
+    /// Sent, failed and expired emails are deleted after this many days
+    pub outbox_retention_days: i64,
+}
+
+impl Default for EmailConfig {
+    fn default() -> Self {
+        Self {
+            transport: TransportKind::default(),
+            from: None,
+            file_dir: PathBuf::from("emails"),
+            outbox_retention_days: DEFAULT_OUTBOX_RETENTION_DAYS,
+        }
+    }
+}
+
+impl EmailConfig {
+    pub fn from(&self) -> String {
+        self.from
+            .clone()
+            .unwrap_or_else(|| DEFAULT_EMAIL_FROM.to_string())
+    }
+}
+
+#[derive(Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SmtpConfig {
+    pub host: Option<String>,
+    pub port: Option<u16>,
+    pub username: Option<String>,
+    pub password: Option<String>,
+
+    /// `starttls`, `tls` or `none`, selected from the port if not set
+    pub tls: Option<String>,
+}
+
+impl SmtpConfig {
+    pub fn tls(&self) -> Result<SmtpTls, String> {
+        match (&self.tls, self.port) {
+            (Some(tls), _) => SmtpTls::try_from(tls.as_str()),
+            (None, Some(port)) => Ok(SmtpTls::from_port(port)),
+            (None, None) => Err("smtp.port is not set".to_string()),
+        }
+    }
+}
+
+#[derive(Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AccountConfig {
+    /// Tokens are valid for this long, they never expire if not set
+    pub token_lifetime_seconds: Option<i64>,
+
+    /// Verification codes are valid for this long
+    pub verify_code_expire_seconds: i64,
+
+    /// A new code is not sent to the same email before this
+    pub verify_code_resend_seconds: i64,
+}
+
+impl Default for AccountConfig {
+    fn default() -> Self {
+        Self {
+            token_lifetime_seconds: None,
+            verify_code_expire_seconds: 70,
+            verify_code_resend_seconds: 10,
+        }
+    }
+}
+
+/// OpenID Connect login is enabled when the issuer is set
+#[derive(Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct OidcConfig {
+    pub issuer: Option<String>,
+    pub client_id: Option<String>,
+    pub client_secret: Option<String>,
+    pub redirect_uri: Option<String>,
+}
+
+#[derive(Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ErrorLogConfig {
+    pub retention_days: i64,
+}
+
+impl Default for ErrorLogConfig {
+    fn default() -> Self {
+        Self {
+            retention_days: DEFAULT_RETENTION_DAYS,
+        }
+    }
+}
+
+/// Config of the app, read from the CONFIG_FILE (toml) and
+/// overridden by the env vars
+#[derive(Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AppConfig {
+    pub server: ServerConfig,
+    pub database: DatabaseConfig,
+    pub email: EmailConfig,
+    pub smtp: SmtpConfig,
+    pub log: LogConfig,
+    pub account: AccountConfig,
+    pub oidc: OidcConfig,
+    pub error_log: ErrorLogConfig,
+}
+
+/// Applies the env vars to the config fields
+struct EnvOverrides<F> {
+    var: F,
+    errors: Vec<String>,
+}
+
+impl<F> EnvOverrides<F>
+where
+    F: Fn(&str) -> Option<String>,
+{
+    /// Empty vars are the same as not set ones
+    fn get(&self, name: &str) -> Option<String> {
+        (self.var)(name).filter(|value| !value.is_empty())
+    }
+
+    fn parse<T>(&mut self, name: &str) -> Option<T>
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        match self.get(name)?.parse() {
+            Ok(value) => Some(value),
+            Err(err) => {
+                self.errors.push(format!("{} is not valid: {}", name, err));
+
+                None
+            }
+        }
+    }
+
+    fn set<T>(&mut self, name: &str, field: &mut T)
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        if let Some(value) = self.parse(name) {
+            *field = value;
+        }
+    }
+
+    fn set_option<T>(&mut self, name: &str, field: &mut Option<T>)
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        if let Some(value) = self.parse(name) {
+            *field = Some(value);
+        }
+    }
+}
+
+impl AppConfig {
+    /// Loads the config and validates it
+    pub fn load() -> Result<Self, ConfigError> {
+        let mut config = match env::var(CONFIG_FILE_ENV) {
+            Ok(path) if !path.is_empty() => {
+                let content = fs::read_to_string(&path).map_err(|err| {
+                    ConfigError(vec![format!(
+                        "Can't read the config file {}: {}",
+                        path, err
+                    )])
+                })?;
+
+                Self::from_toml(&content).map_err(|err| {
+                    ConfigError(vec![format!("Config file {} is not valid: {}", path, err)])
+                })?
+            }
+
+            _ => Self::default(),
+        };
+
+        let errors: Vec<String> = [
+            config.apply_env(|name| env::var(name).ok()),
+            config.validate(),
+        ]
+        .into_iter()
+        .filter_map(Result::err)
+        .flat_map(|err| err.0)
+        .collect();
+
+        match errors.is_empty() {
+            true => Ok(config),
+            false => Err(ConfigError(errors)),
+        }
+    }
+
+    pub fn from_toml(content: &str) -> Result<Self, toml::de::Error> {
+        toml::from_str(content)
+    }
+
+    /// Env vars override the file, their names
+    /// are listed in the README
+    fn apply_env<F>(&mut self, var: F) -> Result<(), ConfigError>
+    where
+        F: Fn(&str) -> Option<String>,
+    {
+        let mut env = EnvOverrides {
+            var,
+            errors: vec![],
+        };
+
+        env.set("BIND_ADDRESS", &mut self.server.bind);
+        env.set_option("WORKERS", &mut self.server.workers);
+
+        if let Some(origins) = env.get("CORS_ORIGINS") {
+            self.server.cors_origins = origins
+                .split(',')
+                .map(str::trim)
+                .filter(|origin| !origin.is_empty())
+                .map(String::from)
+                .collect();
+        }
+
+        env.set("DATABASE_URL", &mut self.database.url);
+        env.set("DATABASE_POOL_SIZE", &mut self.database.pool_size);
+        env.set(
+            "DATABASE_CONNECTION_TIMEOUT_SECONDS",
+            &mut self.database.connection_timeout_seconds,
+        );
+        env.set(
+            "DATABASE_IDLE_TIMEOUT_SECONDS",
+            &mut self.database.idle_timeout_seconds,
+        );
+
+        env.set("EMAIL_TRANSPORT", &mut self.email.transport);
+        env.set("EMAIL_FILE_DIR", &mut self.email.file_dir);
+        env.set(
+            "EMAIL_OUTBOX_RETENTION_DAYS",
+            &mut self.email.outbox_retention_days,
+        );
+        env.set_option("SMTP_FROM", &mut self.email.from);
+        env.set_option("SMTP_HOST", &mut self.smtp.host);
+        env.set_option("SMTP_PORT", &mut self.smtp.port);
+        env.set_option("SMTP_USERNAME", &mut self.smtp.username);
+        env.set_option("SMTP_PASSWORD", &mut self.smtp.password);
+        env.set_option("SMTP_TLS", &mut self.smtp.tls);
+
+        env.set("LOG_FORMAT", &mut self.log.format);
+        env.set("LOG_LEVEL", &mut self.log.levels);
+        env.set_option("LOG_FILE_PATH", &mut self.log.file_path);
+        env.set("LOG_FILE_MAX_SIZE", &mut self.log.file_max_size);
+        env.set("LOG_FILE_COUNT", &mut self.log.file_count);
+        env.set_option("LOG_CONFIG_FILE", &mut self.log.config_file);
+
+        env.set_option(
+            "TOKEN_LIFETIME_SECONDS",
+            &mut self.account.token_lifetime_seconds,
+        );
+        env.set(
+            "VERIFY_CODE_EXPIRE_SECONDS",
+            &mut self.account.verify_code_expire_seconds,
+        );
+        env.set(
+            "VERIFY_CODE_RESEND_SECONDS",
+            &mut self.account.verify_code_resend_seconds,
+        );
+
+        env.set_option("OIDC_ISSUER", &mut self.oidc.issuer);
+        env.set_option("OIDC_CLIENT_ID", &mut self.oidc.client_id);
+        env.set_option("OIDC_CLIENT_SECRET", &mut self.oidc.client_secret);
+        env.set_option("OIDC_REDIRECT_URI", &mut self.oidc.redirect_uri);
+
+        env.set(
+            "ERROR_LOG_RETENTION_DAYS",
+            &mut self.error_log.retention_days,
+        );
+
+        match env.errors.is_empty() {
+            true => Ok(()),
+            false => Err(ConfigError(env.errors)),
+        }
+    }
+
+    fn validate(&self) -> Result<(), ConfigError> {
+        let mut errors = vec![];
+
+        let mut required = |is_set: bool, name: &str, env: &str| {
+            if !is_set {
+                errors.push(format!("{} ({}) must be set", name, env));
+            }
+        };
+
+        required(
+            !self.database.url.is_empty(),
+            "database.url",
+            "DATABASE_URL",
+        );
+
+        if self.email.transport == TransportKind::Smtp {
+            required(is_set(&self.smtp.host), "smtp.host", "SMTP_HOST");
+            required(self.smtp.port.is_some(), "smtp.port", "SMTP_PORT");
+            required(is_set(&self.email.from), "email.from", "SMTP_FROM");
+        }
+
+        // OpenID Connect is only configured with the issuer
+        if is_set(&self.oidc.issuer) {
+            required(
+                is_set(&self.oidc.client_id),
+                "oidc.client_id",
+                "OIDC_CLIENT_ID",
+            );
+            required(
+                is_set(&self.oidc.client_secret),
+                "oidc.client_secret",
+                "OIDC_CLIENT_SECRET",
+            );
+            required(
+                is_set(&self.oidc.redirect_uri),
+                "oidc.redirect_uri",
+                "OIDC_REDIRECT_URI",
+            );
+        }
+
+        if self.server.workers == Some(0) {
+            errors.push("server.workers (WORKERS) must be at least 1".to_string());
+        }
+
+        if self.server.cors_origins.is_empty() {
+            errors.push("server.cors_origins (CORS_ORIGINS) must have an origin or *".to_string());
+        }
+
+        for origin in &self.server.cors_origins {
+            if origin != ANY_ORIGIN && !valid_origin(origin) {
+                errors.push(format!(
+                    "server.cors_origins (CORS_ORIGINS): `{}` is not an origin, like https://natiq.net",
+                    origin
+                ));
+            }
+        }
+
+        if self.database.pool_size == 0 {
+            errors.push("database.pool_size (DATABASE_POOL_SIZE) must be at least 1".to_string());
+        }
+
+        if self.database.connection_timeout_seconds == 0 {
+            errors.push(
+                "database.connection_timeout_seconds (DATABASE_CONNECTION_TIMEOUT_SECONDS) must be at least 1"
+                    .to_string(),
+            );
+        }
+
+        if let Some(from) = &self.email.from {
+            if let Err(err) = from.parse::<Mailbox>() {
+                errors.push(format!("email.from (SMTP_FROM) is not valid: {}", err));
+            }
+        }
+
+        if self.email.transport == TransportKind::Smtp && self.smtp.port.is_some() {
+            if let Err(err) = self.smtp.tls() {
+                errors.push(format!("smtp.tls (SMTP_TLS) is not valid: {}", err));
+            }
+        }
+
+        // Credentials are optional, but the username is useless without the password
+        if is_set(&self.smtp.username) != is_set(&self.smtp.password) {
+            errors.push(
+                "smtp.username (SMTP_USERNAME) and smtp.password (SMTP_PASSWORD) must be set together"
+                    .to_string(),
+            );
+        }
+
+        if self.log.file_count == 0 {
+            errors.push("log.file_count (LOG_FILE_COUNT) must be at least 1".to_string());
+        }
+
+        if self
+            .account
+            .token_lifetime_seconds
+            .is_some_and(|seconds| seconds <= 0)
+        {
+            errors.push(
+                "account.token_lifetime_seconds (TOKEN_LIFETIME_SECONDS) must be positive"
+                    .to_string(),
+            );
+        }
+
+        if self.account.verify_code_expire_seconds <= 0 {
+            errors.push(
+                "account.verify_code_expire_seconds (VERIFY_CODE_EXPIRE_SECONDS) must be positive"
+                    .to_string(),
+            );
+        }
+
+        if self.account.verify_code_resend_seconds < 0 {
+            errors.push(
+                "account.verify_code_resend_seconds (VERIFY_CODE_RESEND_SECONDS) can't be negative"
+                    .to_string(),
+            );
+        }
+
+        if self.email.outbox_retention_days <= 0 {
+            errors.push(
+                "email.outbox_retention_days (EMAIL_OUTBOX_RETENTION_DAYS) must be positive"
+                    .to_string(),
+            );
+        }
+
+        if self.error_log.retention_days <= 0 {
+            errors.push(
+                "error_log.retention_days (ERROR_LOG_RETENTION_DAYS) must be positive".to_string(),
+            );
+        }
+
+        match errors.is_empty() {
+            true => Ok(()),
+            false => Err(ConfigError(errors)),
+        }
+    }
+}
+
+fn is_set(value: &Option<String>) -> bool {
+    value.as_deref().is_some_and(|value| !value.is_empty())
+}
+
+/// `scheme://host[:port]`, the same as the Origin header of the browsers
+fn valid_origin(origin: &str) -> bool {
+    let Ok(uri) = origin.parse::<Uri>() else {
+        return false;
+    };
+
+    matches!(uri.scheme_str(), Some("http") | Some("https"))
+        && uri.host().is_some()
+        && uri.path() == "/"
+        && !origin.ends_with('/')
+        && uri.query().is_none()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{valid_origin, AppConfig, TransportKind};
+    use log::LevelFilter;
+    use std::collections::HashMap;
+
+    fn env(vars: &[(&str, &str)]) -> impl Fn(&str) -> Option<String> {
+        let vars: HashMap<String, String> = vars
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect();
+
+        move |name| vars.get(name).cloned()
+    }
+
+    #[test]
+    fn test_from_toml() {
+        let config = AppConfig::from_toml(
+            r#"
+            [server]
+            bind = "127.0.0.1:9000"
+            workers = 2
+            cors_origins = ["https://natiq.net"]
+
+            [database]
+            url = "postgres://localhost/base"
+
+            [log]
+            level = "warn,access=off"
+
+            [account]
+            verify_code_expire_seconds = 120
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(config.server.bind.port(), 9000);
+        assert_eq!(config.server.workers, Some(2));
+        assert_eq!(config.database.pool_size, 10);
+        assert_eq!(config.log.levels.root, LevelFilter::Warn);
+        assert_eq!(config.account.verify_code_expire_seconds, 120);
+        assert_eq!(config.account.verify_code_resend_seconds, 10);
+
+        // Typos are not ignored
+        assert!(AppConfig::from_toml("[server]\nbnd = \"0.0.0.0:80\"").is_err());
+    }
+
+    #[test]
+    fn test_env_overrides() {
+        let mut config = AppConfig::from_toml("[database]\npool_size = 4").unwrap();
+
+        config
+            .apply_env(env(&[
+                ("DATABASE_POOL_SIZE", "20"),
+                ("EMAIL_TRANSPORT", "file"),
+                ("CORS_ORIGINS", "https://natiq.net, http://localhost:3000"),
+                ("SMTP_PORT", ""),
+            ]))
+            .unwrap();
+
+        assert_eq!(config.database.pool_size, 20);
+        assert_eq!(config.email.transport, TransportKind::File);
+        assert_eq!(
+            config.server.cors_origins,
+            ["https://natiq.net", "http://localhost:3000"]
+        );
+        assert_eq!(config.smtp.port, None);
+
+        let err = config
+            .apply_env(env(&[("SMTP_PORT", "smtp"), ("WORKERS", "-1")]))
+            .err()
+            .unwrap();
+
+        assert_eq!(err.0.len(), 2);
+        assert!(err.0[0].starts_with("WORKERS is not valid"));
+        assert!(err.0[1].starts_with("SMTP_PORT is not valid"));
+    }
+
+    #[test]
+    fn test_validate() {
+        let mut config = AppConfig::default();
+
+        let err = config.validate().err().unwrap();
+
+        assert!(err
+            .0
+            .contains(&"database.url (DATABASE_URL) must be set".to_string()));
+        assert!(err
+            .0
+            .contains(&"smtp.host (SMTP_HOST) must be set".to_string()));
+
+        config
+            .apply_env(env(&[
+                ("DATABASE_URL", "postgres://localhost/base"),
+                ("EMAIL_TRANSPORT", "file"),
+            ]))
+            .unwrap();
+
+        assert!(config.validate().is_ok());
+
+        // Smtp without credentials
+        config.email.transport = TransportKind::Smtp;
+        config.email.from = Some("Natiq <noreply@natiq.net>".to_string());
+        config.smtp.host = Some("localhost".to_string());
+        config.smtp.port = Some(25);
+        config.smtp.tls = Some("none".to_string());
+
+        assert!(config.validate().is_ok());
+
+        config.smtp.username = Some("natiq".to_string());
+
+        assert_eq!(
+            config.validate().err().unwrap().0,
+            ["smtp.username (SMTP_USERNAME) and smtp.password (SMTP_PASSWORD) must be set together"]
+        );
+
+        config.smtp.password = Some("pass".to_string());
+        config.server.cors_origins = vec!["https://natiq.net/".to_string()];
+        config.account.verify_code_expire_seconds = 0;
+
+        let err = config.validate().err().unwrap();
+
+        assert_eq!(err.0.len(), 2);
+    }
+
+    #[test]
+    fn test_valid_origin() {
+        assert!(valid_origin("https://natiq.net"));
+        assert!(valid_origin("http://localhost:3000"));
+        assert!(!valid_origin("https://natiq.net/"));
+        assert!(!valid_origin("https://natiq.net/app"));
+        assert!(!valid_origin("natiq.net"));
+    }
+}
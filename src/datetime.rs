@@ -21,15 +21,18 @@ pub fn validate_date_time(date: &NaiveDate) -> Result<(), ValidationError> {
     let input_year = date.year();
 
     if input_year > current_year {
-        return Err(ValidationError::new(
-            "The date year is more than current year.",
-        ));
+        let mut error = ValidationError::new("date_in_future");
+        error.message = Some("The date year is more than current year.".into());
+
+        return Err(error);
     }
 
     if (current_year - input_year) as u32 > OLDEST_YEAR_LIMIT {
-        return Err(ValidationError::new(
-            "The date year is older than 200 years.",
-        ));
+        let mut error = ValidationError::new("date_too_old");
+        error.message = Some("The date year is older than 200 years.".into());
+        error.add_param("limit".into(), &OLDEST_YEAR_LIMIT);
+
+        return Err(error);
     }
 
     Ok(())
@@ -1,49 +1,349 @@
+use crate::email_template::RenderedEmail;
+use async_trait::async_trait;
 use lettre::{
-    transport::smtp::authentication::Credentials, AsyncSmtpTransport, AsyncTransport, Message,
-    Tokio1Executor,
+    message::{Mailbox, MultiPart},
+    transport::smtp::authentication::Credentials,
+    AsyncFileTransport, AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor,
 };
-use std::error::Error;
+use std::{error::Error, path::PathBuf, sync::Arc};
 
-#[derive(Clone)]
-pub struct EmailManager {
-    default_from: String,
+#[cfg(test)]
+use std::sync::Mutex;
+
+pub type EmailError = Box<dyn Error + Send + Sync>;
+
+/// Delivers the built messages
+#[async_trait]
+pub trait EmailTransport: Send + Sync {
+    async fn send(&self, message: Message) -> Result<(), EmailError>;
+
+    /// Checks the configuration of the transport, nothing is sent
+    async fn check(&self) -> Result<(), EmailError> {
+        Ok(())
+    }
+}
+
+/// How the smtp connection is encrypted
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SmtpTls {
+    /// Plain connection upgraded with STARTTLS (usually port 587)
+    StartTls,
+
+    /// TLS from the first byte (usually port 465)
+    Implicit,
+
+    /// No encryption, only for local mail servers
+    None,
+}
+
+impl SmtpTls {
+    /// Port 465 only works with implicit TLS,
+    /// every other port uses STARTTLS
+    pub fn from_port(port: u16) -> Self {
+        match port {
+            465 => Self::Implicit,
+            _ => Self::StartTls,
+        }
+    }
+}
+
+impl TryFrom<&str> for SmtpTls {
+    type Error = String;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "starttls" => Ok(Self::StartTls),
+            "tls" => Ok(Self::Implicit),
+            "none" => Ok(Self::None),
+
+            _ => Err(format!("Unknown smtp tls mode: {}", value)),
+        }
+    }
+}
+
+pub struct SmtpTransport {
+    host: String,
+    port: u16,
+    tls: SmtpTls,
     mailer: AsyncSmtpTransport<Tokio1Executor>,
 }
 
-impl EmailManager {
-    /// Grab creds and create smtpTransport and return EmailManage
+impl SmtpTransport {
+    /// Credentials are optional, local mail servers may not need them
     pub fn new(
         host: &str,
         port: u16,
-        creds: Credentials,
-        default_from: String,
-    ) -> Result<Self, Box<dyn Error>> {
-        // Create a new smtpTransport object
-        let mailer = AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(host)?
-            .port(port)
-            .credentials(creds)
-            .build();
+        tls: SmtpTls,
+        creds: Option<Credentials>,
+    ) -> Result<Self, EmailError> {
+        let mut builder = match tls {
+            SmtpTls::StartTls => AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(host)?,
+            SmtpTls::Implicit => AsyncSmtpTransport::<Tokio1Executor>::relay(host)?,
+            SmtpTls::None => AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(host),
+        }
+        .port(port);
+
+        if let Some(creds) = creds {
+            builder = builder.credentials(creds);
+        }
 
         Ok(Self {
-            default_from,
-            mailer,
+            host: host.to_string(),
+            port,
+            tls,
+            mailer: builder.build(),
+        })
+    }
+}
+
+#[async_trait]
+impl EmailTransport for SmtpTransport {
+    async fn send(&self, message: Message) -> Result<(), EmailError> {
+        self.mailer.send(message).await?;
+
+        Ok(())
+    }
+
+    /// Only the config is checked, the server is not connected
+    ///
+    /// An unreachable server must not make the instance unready,
+    /// the outbox retries the emails until it's back
+    async fn check(&self) -> Result<(), EmailError> {
+        if self.host.is_empty() {
+            return Err("Smtp host is not set".into());
+        }
+
+        match (self.port, self.tls) {
+            (0, _) => Err("Smtp port is not set".into()),
+            (465, SmtpTls::StartTls | SmtpTls::None) => {
+                Err("Smtp port 465 needs implicit TLS".into())
+            }
+            (587, SmtpTls::Implicit) => Err("Smtp port 587 needs STARTTLS".into()),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Writes every message as a .eml file to the directory
+pub struct FileTransport {
+    dir: PathBuf,
+    transport: AsyncFileTransport<Tokio1Executor>,
+}
+
+impl FileTransport {
+    pub fn new(dir: PathBuf) -> Result<Self, EmailError> {
+        std::fs::create_dir_all(&dir)?;
+
+        Ok(Self {
+            transport: AsyncFileTransport::new(dir.clone()),
+            dir,
         })
     }
+}
+
+#[async_trait]
+impl EmailTransport for FileTransport {
+    async fn send(&self, message: Message) -> Result<(), EmailError> {
+        self.transport.send(message).await?;
+
+        Ok(())
+    }
+
+    async fn check(&self) -> Result<(), EmailError> {
+        if !self.dir.is_dir() {
+            return Err(format!("Email directory {} doesn't exist", self.dir.display()).into());
+        }
+
+        Ok(())
+    }
+}
+
+/// Keeps the messages in memory, for tests
+#[cfg(test)]
+#[derive(Clone, Default)]
+pub struct MemoryTransport {
+    messages: Arc<Mutex<Vec<Message>>>,
+}
+
+#[cfg(test)]
+impl MemoryTransport {
+    /// Returns the sent messages
+    pub fn messages(&self) -> Vec<Message> {
+        self.messages.lock().unwrap().clone()
+    }
+}
+
+#[cfg(test)]
+#[async_trait]
+impl EmailTransport for MemoryTransport {
+    async fn send(&self, message: Message) -> Result<(), EmailError> {
+        self.messages.lock().unwrap().push(message);
+
+        Ok(())
+    }
+}
+
+#[derive(Clone)]
+pub struct EmailManager {
+    default_from: String,
+    transport: Arc<dyn EmailTransport>,
+}
+
+impl EmailManager {
+    pub fn new<T>(transport: T, default_from: String) -> Self
+    where
+        T: EmailTransport + 'static,
+    {
+        Self {
+            default_from,
+            transport: Arc::new(transport),
+        }
+    }
+
+    /// Checks the sender address and the transport configuration
+    pub async fn check(&self) -> Result<(), EmailError> {
+        self.default_from.parse::<Mailbox>()?;
 
-    pub async fn send_email(
-        &self,
-        to: &str,
-        subject: &str,
-        body: String,
-    ) -> Result<(), Box<dyn Error>> {
+        self.transport.check().await
+    }
+
+    /// Sends the rendered email with both plain text and html parts
+    pub async fn send_email(&self, to: &str, email: &RenderedEmail) -> Result<(), EmailError> {
         let message = Message::builder()
             .from(self.default_from.parse()?)
             .to(to.parse()?)
-            .subject(subject)
-            .body(body)?;
+            .subject(email.subject.clone())
+            .multipart(MultiPart::alternative_plain_html(
+                email.text.clone(),
+                email.html.clone(),
+            ))?;
 
-        self.mailer.send(message).await?;
+        self.transport.send(message).await
+    }
+}
 
-        Ok(())
+#[cfg(test)]
+mod tests {
+    use super::{EmailManager, FileTransport, MemoryTransport, SmtpTls, SmtpTransport};
+    use crate::email_template::EmailTemplate;
+
+    const FROM: &str = "Natiq <noreply@natiq.local>";
+
+    #[test]
+    fn test_smtp_tls() {
+        assert_eq!(SmtpTls::from_port(465), SmtpTls::Implicit);
+        assert_eq!(SmtpTls::from_port(587), SmtpTls::StartTls);
+        assert_eq!(SmtpTls::try_from("tls").unwrap(), SmtpTls::Implicit);
+        assert!(SmtpTls::try_from("ssl").is_err());
+    }
+
+    #[actix_web::test]
+    async fn test_memory_transport() {
+        let transport = MemoryTransport::default();
+        let manager = EmailManager::new(transport.clone(), FROM.to_string());
+
+        let email = EmailTemplate::Verification {
+            code: "123456",
+            expire_seconds: 70,
+        }
+        .render(None)
+        .unwrap();
+        manager.send_email("user@natiq.net", &email).await.unwrap();
+
+        let messages = transport.messages();
+        assert_eq!(messages.len(), 1);
+
+        let formatted = String::from_utf8(messages[0].formatted()).unwrap();
+        assert!(formatted.contains("To: user@natiq.net"));
+        assert!(formatted.contains("multipart/alternative"));
+        assert!(formatted.contains("text/plain"));
+        assert!(formatted.contains("text/html"));
+    }
+
+    #[actix_web::test]
+    async fn test_file_transport() {
+        let dir = std::env::temp_dir().join(format!(
+            "nq-api-emails-{}",
+            crate::oidc::generate_random_string()
+        ));
+
+        let manager = EmailManager::new(FileTransport::new(dir.clone()).unwrap(), FROM.to_string());
+
+        let email = EmailTemplate::Verification {
+            code: "123456",
+            expire_seconds: 70,
+        }
+        .render(None)
+        .unwrap();
+        manager.send_email("user@natiq.net", &email).await.unwrap();
+
+        let files: Vec<_> = std::fs::read_dir(&dir).unwrap().collect();
+        assert_eq!(files.len(), 1);
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[actix_web::test]
+    async fn test_invalid_address() {
+        let manager = EmailManager::new(MemoryTransport::default(), FROM.to_string());
+        let email = EmailTemplate::Verification {
+            code: "123456",
+            expire_seconds: 70,
+        }
+        .render(None)
+        .unwrap();
+
+        assert!(manager.send_email("not an email", &email).await.is_err());
+    }
+
+    #[actix_web::test]
+    async fn test_check() {
+        assert!(
+            EmailManager::new(MemoryTransport::default(), FROM.to_string())
+                .check()
+                .await
+                .is_ok()
+        );
+        assert!(
+            EmailManager::new(MemoryTransport::default(), "natiq".to_string())
+                .check()
+                .await
+                .is_err()
+        );
+
+        let dir = std::env::temp_dir().join(format!(
+            "nq-api-emails-{}",
+            crate::oidc::generate_random_string()
+        ));
+
+        let manager = EmailManager::new(FileTransport::new(dir.clone()).unwrap(), FROM.to_string());
+        assert!(manager.check().await.is_ok());
+
+        std::fs::remove_dir_all(dir).unwrap();
+        assert!(manager.check().await.is_err());
+    }
+
+    #[actix_web::test]
+    async fn test_smtp_check() {
+        let check = |host: &str, port: u16, tls: SmtpTls| {
+            let transport = SmtpTransport::new(host, port, tls, None).unwrap();
+
+            async move { EmailManager::new(transport, FROM.to_string()).check().await }
+        };
+
+        // Nothing listens on the port after the listener is dropped,
+        // the server is not connected
+        let port = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        assert!(check("127.0.0.1", port, SmtpTls::None).await.is_ok());
+
+        assert!(check("localhost", 465, SmtpTls::Implicit).await.is_ok());
+        assert!(check("localhost", 465, SmtpTls::StartTls).await.is_err());
+        assert!(check("localhost", 587, SmtpTls::Implicit).await.is_err());
+        assert!(check("localhost", 0, SmtpTls::StartTls).await.is_err());
+        assert!(check("", 587, SmtpTls::None).await.is_err());
     }
 }
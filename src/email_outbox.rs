@@ -0,0 +1,452 @@
+use crate::email::{EmailError, EmailManager};
+use crate::email_template::RenderedEmail;
+use crate::metrics::metrics;
+use crate::models::{NewOutboxEmail, OutboxEmail};
+use crate::pg_listener::{Listener, RECONNECT_INTERVAL};
+use crate::DbPool;
+use actix_web::web;
+use chrono::{Duration, NaiveDateTime, Utc};
+use diesel::prelude::*;
+use diesel::sql_query;
+use diesel::sql_types::{BigInt, Timestamptz};
+use log::{error, info};
+use std::{sync::Arc, thread};
+use tokio::sync::Notify;
+
+/// Postgres channel that the queued emails are notified on
+pub const OUTBOX_CHANNEL: &str = "app_email_outbox_queued";
+
+/// The worker is woken up by the queued emails, this poll
+/// finds the retries and the emails that were not notified
+const POLL_INTERVAL: std::time::Duration = std::time::Duration::from_secs(10);
+
+/// Max emails that are sent in one poll
+const BATCH_SIZE: i64 = 20;
+
+/// Claimed emails are not picked again (by other workers)
+/// until this passes
+const CLAIM_SECONDS: i64 = 60;
+
+/// After this many failed attempts the email is marked as failed
+pub const MAX_ATTEMPTS: i32 = 8;
+
+/// First retry is after 30 seconds, then it doubles
+/// until it reaches an hour
+const BASE_BACKOFF_SECONDS: i64 = 30;
+const MAX_BACKOFF_SECONDS: i64 = 3600;
+
+/// Finished emails are kept this many days, if it's not configured
+pub const DEFAULT_RETENTION_DAYS: i64 = 7;
+
+/// The finished emails are purged with this interval
+const PURGE_INTERVAL: std::time::Duration = std::time::Duration::from_secs(3600);
+
+/// Max emails that are deleted in one statement
+const PURGE_BATCH_SIZE: i64 = 5_000;
+
+pub const STATUS_PENDING: &str = "pending";
+pub const STATUS_SENT: &str = "sent";
+pub const STATUS_FAILED: &str = "failed";
+pub const STATUS_EXPIRED: &str = "expired";
+
+/// Adds the email to the outbox and wakes up the workers
+///
+/// Use this in the same transaction with the data that email is about,
+/// the email is not sent after `expires_at`
+pub fn enqueue(
+    conn: &mut PgConnection,
+    to: &str,
+    email: &RenderedEmail,
+    expires_at: Option<NaiveDateTime>,
+) -> QueryResult<()> {
+    use crate::schema::app_email_outbox::dsl::app_email_outbox;
+
+    NewOutboxEmail {
+        recipient: to,
+        subject: &email.subject,
+        text_body: &email.text,
+        html_body: &email.html,
+        expires_at,
+    }
+    .insert_into(app_email_outbox)
+    .execute(conn)?;
+
+    // Inside a transaction, the notification is sent on commit
+    sql_query(format!("NOTIFY {}", OUTBOX_CHANNEL)).execute(conn)?;
+
+    Ok(())
+}
+
+/// Delay before the next attempt
+fn backoff(attempts: i32) -> Duration {
+    let exponent = (attempts - 1).clamp(0, 16) as u32;
+
+    Duration::seconds((BASE_BACKOFF_SECONDS * 2_i64.pow(exponent)).min(MAX_BACKOFF_SECONDS))
+}
+
+/// Returns the status and the next attempt time after a failed attempt
+///
+/// The email is expired if the next attempt is too late
+fn after_failure(
+    attempts: i32,
+    now: NaiveDateTime,
+    expires_at: Option<NaiveDateTime>,
+) -> (&'static str, NaiveDateTime) {
+    if attempts >= MAX_ATTEMPTS {
+        return (STATUS_FAILED, now);
+    }
+
+    let next_attempt = now + backoff(attempts);
+
+    if expires_at.is_some_and(|expires_at| next_attempt >= expires_at) {
+        (STATUS_EXPIRED, now)
+    } else {
+        (STATUS_PENDING, next_attempt)
+    }
+}
+
+/// Marks the pending emails that are expired, they are never sent
+fn expire_pending(conn: &mut PgConnection, now: NaiveDateTime) -> QueryResult<usize> {
+    use crate::schema::app_email_outbox::dsl::{
+        app_email_outbox, expires_at, html_body, status, subject, text_body, updated_at,
+    };
+
+    diesel::update(
+        app_email_outbox
+            .filter(status.eq(STATUS_PENDING))
+            .filter(expires_at.le(now)),
+    )
+    .set((
+        status.eq(STATUS_EXPIRED),
+        subject.eq(""),
+        text_body.eq(""),
+        html_body.eq(""),
+        updated_at.eq(now),
+    ))
+    .execute(conn)
+}
+
+/// Returns the due emails and claims them, so other workers skip them
+fn claim_due(conn: &mut PgConnection) -> QueryResult<Vec<OutboxEmail>> {
+    use crate::schema::app_email_outbox::dsl::{app_email_outbox, id, next_attempt_at, status};
+
+    conn.transaction(|conn| {
+        let now = Utc::now().naive_utc();
+
+        expire_pending(conn, now)?;
+
+        let due: Vec<OutboxEmail> = app_email_outbox
+            .filter(status.eq(STATUS_PENDING))
+            .filter(next_attempt_at.le(now))
+            .order(next_attempt_at.asc())
+            .limit(BATCH_SIZE)
+            .for_update()
+            .skip_locked()
+            .load(conn)?;
+
+        let ids: Vec<i32> = due.iter().map(|email| email.id).collect();
+
+        diesel::update(app_email_outbox.filter(id.eq_any(ids)))
+            .set(next_attempt_at.eq(now + Duration::seconds(CLAIM_SECONDS)))
+            .execute(conn)?;
+
+        Ok(due)
+    })
+}
+
+/// Saves the delivery status of the email
+///
+/// The content of the finished emails is cleared, it may have secrets
+fn record_attempt(
+    conn: &mut PgConnection,
+    email: &OutboxEmail,
+    result: Result<(), String>,
+) -> QueryResult<()> {
+    use crate::schema::app_email_outbox::dsl::{
+        attempts, html_body, last_error, next_attempt_at, sent_at, status, subject, text_body,
+        updated_at,
+    };
+
+    let now = Utc::now().naive_utc();
+    let new_attempts = email.attempts + 1;
+
+    match result {
+        Ok(()) => diesel::update(email)
+            .set((
+                status.eq(STATUS_SENT),
+                attempts.eq(new_attempts),
+                last_error.eq(None::<String>),
+                sent_at.eq(Some(now)),
+                subject.eq(""),
+                text_body.eq(""),
+                html_body.eq(""),
+                updated_at.eq(now),
+            ))
+            .execute(conn)?,
+
+        Err(err) => match after_failure(new_attempts, now, email.expires_at) {
+            (STATUS_PENDING, next_attempt) => diesel::update(email)
+                .set((
+                    attempts.eq(new_attempts),
+                    last_error.eq(Some(err)),
+                    next_attempt_at.eq(next_attempt),
+                    updated_at.eq(now),
+                ))
+                .execute(conn)?,
+
+            (new_status, next_attempt) => diesel::update(email)
+                .set((
+                    status.eq(new_status),
+                    attempts.eq(new_attempts),
+                    last_error.eq(Some(err)),
+                    next_attempt_at.eq(next_attempt),
+                    subject.eq(""),
+                    text_body.eq(""),
+                    html_body.eq(""),
+                    updated_at.eq(now),
+                ))
+                .execute(conn)?,
+        },
+    };
+
+    Ok(())
+}
+
+/// Sends the due emails once
+async fn process_due(pool: &DbPool, emailer: &EmailManager) -> Result<(), EmailError> {
+    let pool_clone = pool.clone();
+    let due = web::block(move || -> Result<_, EmailError> {
+        let mut conn = pool_clone.get()?;
+
+        Ok(claim_due(&mut conn)?)
+    })
+    .await??;
+
+    for email in due {
+        let rendered = RenderedEmail {
+            subject: email.subject.clone(),
+            text: email.text_body.clone(),
+            html: email.html_body.clone(),
+        };
+
+        let result = emailer
+            .send_email(&email.recipient, &rendered)
+            .await
+            .map_err(|err| err.to_string());
+
+        match result {
+            Ok(()) => metrics().emails_sent.inc(),
+
+            Err(ref err) => {
+                metrics().email_send_failures.inc();
+
+                error!(
+                    "Email {} to {} failed: {}",
+                    email.uuid, email.recipient, err
+                );
+            }
+        }
+
+        let pool_clone = pool.clone();
+        web::block(move || -> Result<_, EmailError> {
+            let mut conn = pool_clone.get()?;
+
+            Ok(record_attempt(&mut conn, &email, result)?)
+        })
+        .await??;
+    }
+
+    Ok(())
+}
+
+/// Starts the thread that wakes up the worker when emails are queued
+///
+/// Notifications may be lost while the listener is disconnected,
+/// so the worker is woken up on every (re)connection too
+fn listen(queued: Arc<Notify>, database_url: String) {
+    thread::spawn(move || loop {
+        match Listener::connect(&database_url, OUTBOX_CHANNEL) {
+            Ok(listener) => {
+                info!("Listening to {}", OUTBOX_CHANNEL);
+                queued.notify_one();
+
+                loop {
+                    match listener.wait() {
+                        Ok(0) => {}
+                        Ok(_) => queued.notify_one(),
+                        Err(err) => {
+                            error!("Email outbox listener: {}", err);
+                            break;
+                        }
+                    }
+                }
+            }
+
+            Err(err) => error!("Email outbox listener can't connect: {}", err),
+        }
+
+        thread::sleep(RECONNECT_INTERVAL);
+    });
+}
+
+/// Background worker that delivers the outbox emails,
+/// failed emails are retried with backoff
+pub async fn run_worker(pool: DbPool, emailer: EmailManager, database_url: String) {
+    let queued = Arc::new(Notify::new());
+
+    listen(queued.clone(), database_url);
+
+    loop {
+        if let Err(err) = process_due(&pool, &emailer).await {
+            error!("Email outbox worker: {}", err);
+        }
+
+        // Returns on the first notification or after the poll interval
+        let _ = actix_web::rt::time::timeout(POLL_INTERVAL, queued.notified()).await;
+    }
+}
+
+/// Deletes the sent, failed and expired emails that are older than the retention
+pub fn purge_finished(conn: &mut PgConnection, retention_days: i64) -> QueryResult<usize> {
+    let finished_before = Utc::now() - Duration::days(retention_days);
+
+    let mut purged = 0;
+
+    loop {
+        let deleted = sql_query(
+            "DELETE FROM app_email_outbox WHERE id IN
+                (SELECT id FROM app_email_outbox
+                    WHERE status <> 'pending' AND updated_at < $1 LIMIT $2)",
+        )
+        .bind::<Timestamptz, _>(finished_before)
+        .bind::<BigInt, _>(PURGE_BATCH_SIZE)
+        .execute(conn)?;
+
+        purged += deleted;
+
+        if deleted < PURGE_BATCH_SIZE as usize {
+            return Ok(purged);
+        }
+    }
+}
+
+/// Background worker that purges the finished emails
+pub async fn run_purge_worker(pool: DbPool, retention_days: i64) {
+    let mut interval = actix_web::rt::time::interval(PURGE_INTERVAL);
+
+    loop {
+        interval.tick().await;
+
+        let pool = pool.clone();
+
+        let result = web::block(move || {
+            let mut conn = pool.get().map_err(|err| err.to_string())?;
+
+            purge_finished(&mut conn, retention_days).map_err(|err| err.to_string())
+        })
+        .await;
+
+        match result {
+            Ok(Ok(0)) => {}
+            Ok(Ok(purged)) => info!("Purged {} finished outbox emails", purged),
+            Ok(Err(err)) => error!("Email outbox purge: {}", err),
+            Err(err) => error!("Email outbox purge: {}", err),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        after_failure, backoff, claim_due, enqueue, purge_finished, MAX_ATTEMPTS, STATUS_EXPIRED,
+        STATUS_FAILED, STATUS_PENDING,
+    };
+    use crate::{email_template::RenderedEmail, models::OutboxEmail, test_db};
+    use chrono::{Duration, Utc};
+    use diesel::prelude::*;
+
+    #[test]
+    fn test_backoff() {
+        assert_eq!(backoff(1), Duration::seconds(30));
+        assert_eq!(backoff(2), Duration::seconds(60));
+        assert_eq!(backoff(3), Duration::seconds(120));
+        assert_eq!(backoff(20), Duration::seconds(3600));
+    }
+
+    #[test]
+    fn test_after_failure() {
+        let now = Utc::now().naive_utc();
+
+        assert_eq!(
+            after_failure(1, now, None),
+            (STATUS_PENDING, now + Duration::seconds(30))
+        );
+        assert_eq!(after_failure(MAX_ATTEMPTS, now, None), (STATUS_FAILED, now));
+
+        // The code is expired before the retry
+        assert_eq!(
+            after_failure(1, now, Some(now + Duration::seconds(20))),
+            (STATUS_EXPIRED, now)
+        );
+        assert_eq!(
+            after_failure(1, now, Some(now + Duration::seconds(70))),
+            (STATUS_PENDING, now + Duration::seconds(30))
+        );
+    }
+
+    #[test]
+    #[ignore = "needs TEST_DATABASE_URL"]
+    fn test_expired_email_not_sent() {
+        use crate::schema::app_email_outbox::dsl::{app_email_outbox, recipient, updated_at};
+
+        let pool = test_db::pool();
+        let mut conn = pool.get().unwrap();
+
+        // Rolled back at the end, the claim and the purge
+        // don't change the rows of the other tests
+        conn.test_transaction::<_, diesel::result::Error, _>(|conn| {
+            let to = format!("{}@natiq.local", crate::oidc::generate_random_string());
+
+            let email = RenderedEmail {
+                subject: "Code: 123456".to_string(),
+                text: "123456".to_string(),
+                html: "<p>123456</p>".to_string(),
+            };
+
+            let expired_at = Utc::now().naive_utc() - Duration::seconds(1);
+            enqueue(conn, &to, &email, Some(expired_at))?;
+
+            let due = claim_due(conn)?;
+            assert!(due.iter().all(|email| email.recipient != to));
+
+            let expired: OutboxEmail = app_email_outbox
+                .filter(recipient.eq(&to))
+                .get_result(conn)?;
+
+            assert_eq!(expired.status, STATUS_EXPIRED);
+            assert_eq!(expired.attempts, 0);
+            assert!(expired.text_body.is_empty());
+            assert!(!serde_json::to_string(&expired).unwrap().contains("123456"));
+
+            let exists = |conn: &mut PgConnection| {
+                diesel::select(diesel::dsl::exists(
+                    app_email_outbox.filter(recipient.eq(&to)),
+                ))
+                .get_result::<bool>(conn)
+            };
+
+            // Nothing is old enough with the retention of a day
+            purge_finished(conn, 1)?;
+            assert!(exists(conn)?);
+
+            diesel::update(app_email_outbox.filter(recipient.eq(&to)))
+                .set(updated_at.eq(Utc::now().naive_utc() - Duration::days(2)))
+                .execute(conn)?;
+
+            purge_finished(conn, 1)?;
+            assert!(!exists(conn)?);
+
+            Ok(())
+        });
+    }
+}
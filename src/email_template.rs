@@ -0,0 +1,369 @@
+use serde::Deserialize;
+use std::{collections::HashMap, fmt::Display, sync::OnceLock};
+
+const EMAIL_TEMPLATES_JSON: &str = include_str!("../email_templates.json");
+const EMAIL_LAYOUT_HTML: &str = include_str!("../templates/email/layout.html");
+
+/// Used when the user has no language or
+/// we don't have templates for it
+pub const DEFAULT_EMAIL_LANGUAGE: &str = "en";
+
+/// Languages that are written right to left
+const RTL_LANGUAGES: [&str; 4] = ["ar", "fa", "he", "ur"];
+
+static EMAIL_TEMPLATES: OnceLock<EmailTemplates> = OnceLock::new();
+
+#[derive(Debug, Deserialize)]
+struct TemplateText {
+    subject: String,
+    body: String,
+}
+
+/// language -> template name -> text
+type EmailTemplates = HashMap<String, HashMap<String, TemplateText>>;
+
+fn templates() -> &'static EmailTemplates {
+    EMAIL_TEMPLATES.get_or_init(|| {
+        serde_json::from_str(EMAIL_TEMPLATES_JSON).expect("Failed to parse email_templates json!")
+    })
+}
+
+/// Transactional emails that we send
+// Not every template has a router that sends it yet
+#[allow(dead_code)]
+#[derive(Debug, Clone)]
+pub enum EmailTemplate<'a> {
+    Verification {
+        code: &'a str,
+
+        /// The code is valid for this many seconds
+        expire_seconds: i64,
+    },
+    PasswordReset {
+        code: &'a str,
+
+        /// The code is valid for this many seconds
+        expire_seconds: i64,
+    },
+    Invitation {
+        organization: &'a str,
+        inviter: &'a str,
+    },
+    Approval {
+        object: &'a str,
+        name: &'a str,
+    },
+}
+
+/// The template is not defined, not even in the default language
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TemplateNotFound(pub &'static str);
+
+impl Display for TemplateNotFound {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Email template {} is not defined", self.0)
+    }
+}
+
+/// Final email, ready to be sent as
+/// multipart/alternative message
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RenderedEmail {
+    pub subject: String,
+    pub text: String,
+    pub html: String,
+}
+
+impl<'a> EmailTemplate<'a> {
+    /// Name of the template in email_templates.json
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Verification { .. } => "verification",
+            Self::PasswordReset { .. } => "password_reset",
+            Self::Invitation { .. } => "invitation",
+            Self::Approval { .. } => "approval",
+        }
+    }
+
+    fn params(&self) -> Vec<(&'static str, String)> {
+        match self {
+            Self::Verification {
+                code,
+                expire_seconds,
+            }
+            | Self::PasswordReset {
+                code,
+                expire_seconds,
+            } => vec![
+                ("code", code.to_string()),
+                ("seconds", expire_seconds.to_string()),
+                ("minutes", (expire_seconds / 60).to_string()),
+            ],
+            Self::Invitation {
+                organization,
+                inviter,
+            } => vec![
+                ("organization", organization.to_string()),
+                ("inviter", inviter.to_string()),
+            ],
+            Self::Approval { object, name } => {
+                vec![("object", object.to_string()), ("name", name.to_string())]
+            }
+        }
+    }
+
+    /// Renders the template with the user language,
+    /// falls back to the default language
+    pub fn render(&self, language: Option<&str>) -> Result<RenderedEmail, TemplateNotFound> {
+        let mut language = template_language(language);
+
+        let text = match templates()
+            .get(language)
+            .and_then(|language_templates| language_templates.get(self.name()))
+        {
+            Some(text) => text,
+
+            None => {
+                language = DEFAULT_EMAIL_LANGUAGE;
+
+                templates()
+                    .get(language)
+                    .and_then(|language_templates| language_templates.get(self.name()))
+                    .ok_or(TemplateNotFound(self.name()))?
+            }
+        };
+
+        let params = self.params();
+        let subject = fill(&text.subject, &params);
+        let body = fill(&text.body, &params);
+
+        let content = body
+            .split("\n\n")
+            .map(|paragraph| format!("<p>{}</p>", escape_html(paragraph).replace('\n', "<br>")))
+            .collect::<Vec<String>>()
+            .join("\n");
+
+        let dir = if RTL_LANGUAGES.contains(&language) {
+            "rtl"
+        } else {
+            "ltr"
+        };
+
+        let html = substitute(
+            EMAIL_LAYOUT_HTML,
+            ("{{", "}}"),
+            &[
+                ("lang", language.to_string()),
+                ("dir", dir.to_string()),
+                ("subject", escape_html(&subject)),
+                ("content", content),
+            ],
+        );
+
+        Ok(RenderedEmail {
+            subject,
+            text: body,
+            html,
+        })
+    }
+}
+
+/// Returns the template language for the user language,
+/// `fa-IR` and `fa_IR` are the same as `fa`
+fn template_language(language: Option<&str>) -> &str {
+    let Some(language) = language else {
+        return DEFAULT_EMAIL_LANGUAGE;
+    };
+
+    let language = language
+        .split(['-', '_'])
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_lowercase();
+
+    match templates().get_key_value(&language) {
+        Some((key, _)) => key,
+        None => DEFAULT_EMAIL_LANGUAGE,
+    }
+}
+
+/// Replaces the {name} placeholders with the param values
+fn fill<V: AsRef<str>>(template: &str, params: &[(&str, V)]) -> String {
+    substitute(template, ("{", "}"), params)
+}
+
+/// Replaces the placeholders between the delimiters with the param values
+///
+/// This is done in one pass so values can't inject other placeholders
+fn substitute<V: AsRef<str>>(
+    template: &str,
+    (open, close): (&str, &str),
+    params: &[(&str, V)],
+) -> String {
+    let mut result = String::with_capacity(template.len());
+    let mut rest = template;
+
+    while let Some(start) = rest.find(open) {
+        result.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        let value = rest[open.len()..].find(close).and_then(|end| {
+            params
+                .iter()
+                .find(|(name, _)| *name == &rest[open.len()..open.len() + end])
+                .map(|(_, value)| (open.len() + end + close.len(), value))
+        });
+
+        match value {
+            Some((end, value)) => {
+                result.push_str(value.as_ref());
+                rest = &rest[end..];
+            }
+
+            None => {
+                result.push_str(open);
+                rest = &rest[open.len()..];
+            }
+        }
+    }
+
+    result.push_str(rest);
+
+    result
+}
+
+fn escape_html(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{fill, substitute, template_language, templates, EmailTemplate};
+
+    #[test]
+    fn test_every_language_has_every_template() {
+        let names = ["verification", "password_reset", "invitation", "approval"];
+
+        for (language, language_templates) in templates() {
+            for name in names {
+                assert!(
+                    language_templates.contains_key(name),
+                    "{} template is missing for {}",
+                    name,
+                    language
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_template_language() {
+        assert_eq!(template_language(Some("fa")), "fa");
+        assert_eq!(template_language(Some("fa-IR")), "fa");
+        assert_eq!(template_language(Some("AR_sa")), "ar");
+        assert_eq!(template_language(Some("xx")), "en");
+        assert_eq!(template_language(None), "en");
+    }
+
+    #[test]
+    fn test_fill() {
+        assert_eq!(fill("Code: {code}", &[("code", "123456")]), "Code: 123456");
+        assert_eq!(fill("{unknown} {", &[("code", "1")]), "{unknown} {");
+
+        // Values must not be filled again
+        assert_eq!(fill("{a} {b}", &[("a", "{b}"), ("b", "x")]), "{b} x");
+    }
+
+    #[test]
+    fn test_substitute_layout() {
+        let params = [("subject", "{{content}}"), ("content", "<p>body</p>")];
+
+        assert_eq!(
+            substitute("{{subject}}|{{content}}|{ }", ("{{", "}}"), &params),
+            "{{content}}|<p>body</p>|{ }"
+        );
+    }
+
+    #[test]
+    fn test_render() {
+        let verification = EmailTemplate::Verification {
+            code: "123456",
+            expire_seconds: 90,
+        };
+
+        let email = verification.render(Some("en")).unwrap();
+
+        assert!(email.subject.contains("123456"));
+        assert!(email.text.contains("123456"));
+        assert!(email.text.contains("90 seconds"));
+        assert!(email.html.contains("<p>123456</p>"));
+        assert!(email.html.contains("dir=\"ltr\""));
+
+        let email = verification.render(Some("fa-IR")).unwrap();
+
+        assert!(email.html.contains("lang=\"fa\" dir=\"rtl\""));
+        assert!(email.text.contains("90 ثانیه"));
+    }
+
+    #[test]
+    fn test_render_password_reset() {
+        let email = EmailTemplate::PasswordReset {
+            code: "654321",
+            expire_seconds: 300,
+        }
+        .render(Some("ar"))
+        .unwrap();
+
+        assert!(email.text.contains("654321"));
+        assert!(email.text.contains("300 ثانية"));
+        assert!(email.html.contains("lang=\"ar\" dir=\"rtl\""));
+    }
+
+    #[test]
+    fn test_render_approval() {
+        let email = EmailTemplate::Approval {
+            object: "translation",
+            name: "Sahih International",
+        }
+        .render(Some("en"))
+        .unwrap();
+
+        assert_eq!(email.subject, "Your translation is approved");
+        assert!(email.text.contains("\"Sahih International\""));
+        assert!(email.html.contains("&quot;Sahih International&quot;"));
+    }
+
+    #[test]
+    fn test_render_subject_not_injected() {
+        let email = EmailTemplate::Invitation {
+            organization: "{{content}}",
+            inviter: "admin",
+        }
+        .render(None)
+        .unwrap();
+
+        assert!(email
+            .html
+            .contains("<title>You are invited to join {{content}}</title>"));
+    }
+
+    #[test]
+    fn test_render_escapes_html() {
+        let email = EmailTemplate::Invitation {
+            organization: "<b>Org</b>",
+            inviter: "admin",
+        }
+        .render(None)
+        .unwrap();
+
+        assert!(email.text.contains("<b>Org</b>"));
+        assert!(!email.html.contains("<b>Org</b>"));
+        assert!(email.html.contains("&lt;b&gt;Org&lt;/b&gt;"));
+    }
+}
@@ -1,19 +1,19 @@
 use actix_web::{
-    error::{PathError, ResponseError},
-    http::{header::ContentType, StatusCode},
-    HttpMessage, HttpRequest, HttpResponse,
+    error::{JsonPayloadError, PathError, QueryPayloadError, ResponseError},
+    http::{
+        header::{ContentType, HeaderMap},
+        StatusCode,
+    },
+    web, HttpMessage, HttpRequest, HttpResponse,
 };
 use auth_z::ParsedPath;
-use diesel::{
-    prelude::*,
-    result::{DatabaseErrorKind, Error as DieselError},
-};
+use diesel::result::{DatabaseErrorKind, Error as DieselError};
 use ipnetwork::IpNetwork;
 use log::error;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
 use std::{
-    collections::HashMap,
+    collections::{BTreeMap, HashMap},
     error::Error,
     fmt::Display,
     net::{Ipv4Addr, SocketAddr},
@@ -21,12 +21,26 @@ use std::{
 };
 use uuid::Error as UuidError;
 
-use crate::{models::NewErrorLog, DbPool, FIXED_ERROR_RESPONSES};
+use crate::{
+    email_template::TemplateNotFound,
+    error_language::DEFAULT_ERROR_LANGUAGE,
+    error_log::{self, redact_body, redact_text, redact_token, redact_url},
+    models::NewErrorLog,
+    request_id::REQUEST_ID_HEADER,
+    validate::{deserialize_field_errors, localized_field_errors, FieldErrors},
+    DbPool, FIXED_ERROR_RESPONSES,
+};
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct PreDefinedResponseError {
     status_code: u16,
+
+    /// English message
     message: String,
+
+    /// language -> translated message
+    #[serde(default)]
+    messages: BTreeMap<String, String>,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -34,11 +48,22 @@ pub struct PreDefinedResponseErrors {
     pub errors: HashMap<String, PreDefinedResponseError>,
 }
 
+/// Loads the predefined errors, tests don't run the main
+#[cfg(test)]
+pub fn load_predefined_errors() {
+    FIXED_ERROR_RESPONSES.get_or_init(|| PreDefinedResponseErrors {
+        errors: serde_json::from_str(crate::FIXED_ERROR_JSON).unwrap(),
+    });
+}
+
 #[derive(Clone, Debug)]
 pub struct RouterError {
     error_name: String,
     error: PreDefinedResponseError,
     detail: Option<String>,
+
+    /// Validation errors of the fields, responded instead of the detail
+    fields: Option<Box<FieldErrors>>,
 }
 
 #[derive(Clone, Debug)]
@@ -58,6 +83,7 @@ pub struct RouterErrorDetail {
     pub request_url: Option<String>,
     pub request_controller: Option<String>,
     pub request_action: Option<String>,
+    /// X-Request-Id of the request
     pub request_id: Option<String>,
     pub request_body: Option<Vec<u8>>,
     pub request_body_content_type: Option<String>,
@@ -109,8 +135,11 @@ impl RouterErrorDetailBuilder {
             error_detail_builder.user_agent(user_agent.to_str().unwrap().to_string());
         }
 
+        error_detail_builder.request_id_from_headers(req.headers());
+
+        // Only the hash of the token is logged
         if let Some(token) = req.headers().get("Authorization") {
-            error_detail_builder.user_token(token.to_str().unwrap().to_string());
+            error_detail_builder.user_token(redact_token(token.to_str().unwrap()));
         }
 
         error_detail_builder
@@ -146,13 +175,12 @@ impl RouterErrorDetailBuilder {
         self
     }
 
-    /// This will parse the url and set request_controller, request_action, request_id params.
+    /// This will parse the url and set request_controller, request_action params.
     pub fn request_url_parsed(&mut self, url_path: &str) -> &mut Self {
         let parsed = ParsedPath::from(url_path);
 
         self.detail.request_controller = parsed.controller;
         self.detail.request_action = parsed.action;
-        self.detail.request_id = parsed.id;
 
         self
     }
@@ -175,6 +203,15 @@ impl RouterErrorDetailBuilder {
         self
     }
 
+    /// Sets the X-Request-Id of the request, if it has any
+    pub fn request_id_from_headers(&mut self, headers: &HeaderMap) -> &mut Self {
+        if let Some(id) = headers.get(REQUEST_ID_HEADER) {
+            self.detail.request_id = id.to_str().ok().map(String::from);
+        }
+
+        self
+    }
+
     pub fn request_body(&mut self, body: Vec<u8>) -> &mut Self {
         self.detail.request_body = Some(body);
 
@@ -203,6 +240,7 @@ impl RouterError {
             error: err_resp.errors.get(error_response_name).unwrap().clone(),
             error_name: error_response_name.to_string(),
             detail: None,
+            fields: None,
         }
     }
 
@@ -213,33 +251,55 @@ impl RouterError {
             error: err_resp.errors.get(error_response_name).unwrap().clone(),
             error_name: error_response_name.to_string(),
             detail: Some(detail.to_string()),
+            fields: None,
         }
     }
 
-    pub fn log_to_db(&self, pool: Arc<DbPool>, detail: RouterErrorDetail) -> Self {
-        use crate::schema::app_error_logs::dsl::app_error_logs;
+    /// Predefined error with the errors of the fields
+    ///
+    /// The detail that is logged has the codes and params of the errors
+    pub fn from_predefined_with_fields(error_response_name: &str, fields: FieldErrors) -> Self {
+        let detail = serde_json::to_string(&fields).unwrap_or_default();
+
+        Self {
+            fields: Some(Box::new(fields)),
+            ..Self::from_predefined_with_detail(error_response_name, &detail)
+        }
+    }
+
+    /// VALIDATION_ERROR with the errors of the fields
+    pub fn from_validation(fields: FieldErrors) -> Self {
+        Self::from_predefined_with_fields("VALIDATION_ERROR", fields)
+    }
 
-        let mut conn = pool.get().unwrap();
+    /// Name of the predefined error
+    pub fn error_name(&self) -> String {
+        self.error_name.clone()
+    }
 
-        NewErrorLog {
-            error_name: &self.error_name,
+    /// Queues the error to be inserted to app_error_logs
+    ///
+    /// Secrets and emails of the body, url and detail are redacted,
+    /// the pool is only used when the error logger is not started
+    pub fn log_to_db(&self, pool: Arc<DbPool>, detail: RouterErrorDetail) -> Self {
+        let log = NewErrorLog {
+            error_name: self.error_name.clone(),
             status_code: self.error.status_code as i32,
-            message: &self.error.message,
-            detail: self.detail.as_ref(),
+            message: self.error.message.clone(),
+            detail: self.detail.as_deref().map(redact_text),
             account_id: detail.account_id,
-            request_user_agent: detail.user_agent.as_ref(),
+            request_user_agent: detail.user_agent,
             request_ipv4: IpNetwork::from(detail.req_address.ip()),
             request_token: detail.user_token,
-            request_body: detail.request_body,
-            request_url: detail.request_url,
+            request_body: detail.request_body.as_deref().map(redact_body),
+            request_url: detail.request_url.as_deref().map(redact_url),
             request_controller: detail.request_controller,
             request_action: detail.request_action,
             request_id: detail.request_id,
             request_body_content_type: detail.request_body_content_type,
-        }
-        .insert_into(app_error_logs)
-        .execute(&mut conn)
-        .unwrap();
+        };
+
+        error_log::log(log, &pool);
 
         self.clone()
     }
@@ -262,26 +322,52 @@ impl Error for RouterError {
     }
 }
 
-impl ResponseError for RouterError {
-    fn error_response(&self) -> HttpResponse {
-        let json = match self.detail.clone() {
-            Some(detail) => json!({
-                "error_name": self.error_name,
-                "message": self.error.message,
-                "detail": detail
-            }),
-
-            None => json!({
-                "error_name": self.error_name,
-                "message": self.error.message,
-            }),
+impl RouterError {
+    /// Message in the language, English if it's not translated
+    pub fn message(&self, language: &str) -> &str {
+        self.error
+            .messages
+            .get(language)
+            .unwrap_or(&self.error.message)
+    }
+
+    /// Error response with the message in the language
+    ///
+    /// The request id is in the body, so users can send it to the support
+    pub fn localized_response(&self, language: &str, request_id: Option<&str>) -> HttpResponse {
+        let message = self.message(language);
+
+        let detail = match (&self.fields, &self.detail) {
+            (Some(fields), _) => Some(localized_field_errors(fields, language)),
+            (None, Some(detail)) => Some(json!(detail)),
+            (None, None) => None,
         };
+
+        let mut json = json!({
+            "error_name": self.error_name,
+            "message": message,
+        });
+
+        if let Some(detail) = detail {
+            json["detail"] = detail;
+        }
+
+        if let Some(request_id) = request_id {
+            json["request_id"] = json!(request_id);
+        }
+
         HttpResponse::build(self.status_code())
             .insert_header(ContentType::json())
             // WARINING TODO: Do not allow all Cors
             .insert_header(("Access-Control-Allow-Origin", "*"))
             .body(json.to_string())
     }
+}
+
+impl ResponseError for RouterError {
+    fn error_response(&self) -> HttpResponse {
+        self.localized_response(DEFAULT_ERROR_LANGUAGE, None)
+    }
 
     fn status_code(&self) -> StatusCode {
         StatusCode::from_u16(self.error.status_code).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR)
@@ -317,18 +403,190 @@ impl From<DatabaseErrorKind> for RouterError {
     }
 }
 
+impl From<TemplateNotFound> for RouterError {
+    fn from(value: TemplateNotFound) -> Self {
+        error!("InternalError: {}", value);
+
+        Self::from_predefined("INTERNAL_ERROR")
+    }
+}
+
 impl From<UuidError> for RouterError {
     fn from(_value: UuidError) -> Self {
         Self::from_predefined("UUID_ERROR")
     }
 }
 
-pub fn path_error_handler(err: PathError, _req: &HttpRequest) -> actix_web::Error {
+/// Logs the extractor errors, they don't reach the handlers
+///
+/// Not logged when the app has no pool
+fn extractor_error(error: RouterError, req: &HttpRequest) -> actix_web::Error {
+    let Some(pool) = req.app_data::<web::Data<DbPool>>() else {
+        return error.into();
+    };
+
+    let mut detail = RouterErrorDetailBuilder::from_http_request(req);
+
+    if let Some(account) = req.extensions().get::<u32>() {
+        detail.account_id(*account as i32);
+    }
+
+    error
+        .log_to_db(pool.clone().into_inner(), detail.build())
+        .into()
+}
+
+pub fn path_error_handler(err: PathError, req: &HttpRequest) -> actix_web::Error {
     let e = match err {
         PathError::Deserialize(e) => e.to_string(),
 
         _ => String::new(),
     };
 
-    RouterError::from_predefined_with_detail("BAD_PATH", &e).into()
+    extractor_error(
+        RouterError::from_predefined_with_detail("BAD_PATH", &e),
+        req,
+    )
+}
+
+pub fn json_error_handler(err: JsonPayloadError, req: &HttpRequest) -> actix_web::Error {
+    let error = match err {
+        // The body is a valid JSON, but not the one the route expects
+        JsonPayloadError::Deserialize(e) if e.is_data() => {
+            RouterError::from_validation(deserialize_field_errors(&e))
+        }
+
+        JsonPayloadError::ContentType => {
+            RouterError::from_predefined("JSON_CONTENT_TYPE_NOT_VALID")
+        }
+
+        JsonPayloadError::Overflow { .. } | JsonPayloadError::OverflowKnownLength { .. } => {
+            RouterError::from_predefined_with_detail("REQUEST_BODY_TOO_LARGE", &err.to_string())
+        }
+
+        err => RouterError::from_predefined_with_detail("BAD_JSON", &err.to_string()),
+    };
+
+    extractor_error(error, req)
+}
+
+pub fn query_error_handler(err: QueryPayloadError, req: &HttpRequest) -> actix_web::Error {
+    let error = match err {
+        QueryPayloadError::Deserialize(e) => {
+            RouterError::from_predefined_with_fields("BAD_QUERY", deserialize_field_errors(&e))
+        }
+
+        err => RouterError::from_predefined_with_detail("BAD_QUERY", &err.to_string()),
+    };
+
+    extractor_error(error, req)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{json_error_handler, load_predefined_errors, query_error_handler};
+    use actix_web::{
+        test::{call_service, init_service, read_body_json, TestRequest},
+        web, App,
+    };
+    use serde::Deserialize;
+
+    #[derive(Deserialize)]
+    struct Body {
+        #[allow(dead_code)]
+        email: String,
+    }
+
+    #[actix_web::test]
+    async fn test_json_error_handler() {
+        load_predefined_errors();
+
+        let app = init_service(
+            App::new()
+                .app_data(web::JsonConfig::default().error_handler(json_error_handler))
+                .route("/", web::post().to(|_: web::Json<Body>| async { "" })),
+        )
+        .await;
+
+        let req = TestRequest::post()
+            .uri("/")
+            .set_json(serde_json::json!({ "name": "nq" }))
+            .to_request();
+
+        let res = call_service(&app, req).await;
+        assert_eq!(res.status(), 400);
+
+        let body: serde_json::Value = read_body_json(res).await;
+
+        assert_eq!(body["error_name"], "VALIDATION_ERROR");
+        assert_eq!(body["detail"]["email"][0]["code"], "required");
+        assert_eq!(
+            body["detail"]["email"][0]["message"],
+            "This field is required"
+        );
+    }
+
+    #[derive(Deserialize)]
+    struct Query {
+        #[allow(dead_code)]
+        mushaf: String,
+    }
+
+    #[actix_web::test]
+    async fn test_json_error_handler_bad_json() {
+        load_predefined_errors();
+
+        let app = init_service(
+            App::new()
+                .app_data(web::JsonConfig::default().error_handler(json_error_handler))
+                .route("/", web::post().to(|_: web::Json<Body>| async { "" })),
+        )
+        .await;
+
+        let req = TestRequest::post()
+            .uri("/")
+            .insert_header(("Content-Type", "application/json"))
+            .set_payload("{\"email\": ")
+            .to_request();
+
+        let res = call_service(&app, req).await;
+        assert_eq!(res.status(), 400);
+
+        let body: serde_json::Value = read_body_json(res).await;
+        assert_eq!(body["error_name"], "BAD_JSON");
+
+        let req = TestRequest::post()
+            .uri("/")
+            .insert_header(("Content-Type", "text/plain"))
+            .set_payload("{}")
+            .to_request();
+
+        let res = call_service(&app, req).await;
+        assert_eq!(res.status(), 415);
+
+        let body: serde_json::Value = read_body_json(res).await;
+        assert_eq!(body["error_name"], "JSON_CONTENT_TYPE_NOT_VALID");
+    }
+
+    #[actix_web::test]
+    async fn test_query_error_handler() {
+        load_predefined_errors();
+
+        let app = init_service(
+            App::new()
+                .app_data(web::QueryConfig::default().error_handler(query_error_handler))
+                .route("/", web::get().to(|_: web::Query<Query>| async { "" })),
+        )
+        .await;
+
+        let req = TestRequest::get().uri("/?surah=1").to_request();
+
+        let res = call_service(&app, req).await;
+        assert_eq!(res.status(), 400);
+
+        let body: serde_json::Value = read_body_json(res).await;
+
+        assert_eq!(body["error_name"], "BAD_QUERY");
+        assert_eq!(body["detail"]["mushaf"][0]["code"], "required");
+    }
 }
@@ -0,0 +1,268 @@
+use crate::{error::RouterError, request_id::REQUEST_ID_HEADER, DbPool};
+use actix_web::{
+    body::BoxBody,
+    dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform},
+    error::InternalError,
+    http::header::ACCEPT_LANGUAGE,
+    web, Error, HttpMessage, HttpRequest,
+};
+use diesel::prelude::*;
+use std::{
+    future::{ready, Future, Ready},
+    pin::Pin,
+    rc::Rc,
+};
+
+/// Messages of error_codes.json are in English,
+/// the translations are in the `messages`
+pub const DEFAULT_ERROR_LANGUAGE: &str = "en";
+
+/// Languages that the error messages are translated to
+pub const ERROR_LANGUAGES: [&str; 3] = ["en", "fa", "ar"];
+
+/// Returns the supported languages of the Accept-Language header,
+/// ordered by their quality
+///
+/// `fa-IR,fa;q=0.9,en;q=0.8` -> `[fa, fa, en]`
+fn accepted_languages(header: &str) -> Vec<&'static str> {
+    let mut languages: Vec<(&'static str, f32)> = header
+        .split(',')
+        .filter_map(|part| {
+            let mut params = part.trim().split(';');
+
+            // Only the primary subtag is used, `fa-IR` is `fa`
+            let tag = params.next()?.split('-').next()?.trim();
+
+            let quality = params
+                .find_map(|param| param.trim().strip_prefix("q="))
+                .map_or(Some(1.0), |q| q.trim().parse::<f32>().ok())?;
+
+            let language = ERROR_LANGUAGES
+                .into_iter()
+                .find(|language| language.eq_ignore_ascii_case(tag))?;
+
+            (quality > 0.0).then_some((language, quality))
+        })
+        .collect();
+
+    // Stable sort keeps the header order of the same qualities
+    languages.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+    languages
+        .into_iter()
+        .map(|(language, _)| language)
+        .collect()
+}
+
+/// Language of the error messages: the Accept-Language header,
+/// then the language of the user, then English
+fn error_language(accept_language: Option<&str>, user_language: Option<&str>) -> &'static str {
+    accept_language
+        .and_then(|header| accepted_languages(header).first().copied())
+        .or_else(|| {
+            user_language.and_then(|language| {
+                ERROR_LANGUAGES
+                    .into_iter()
+                    .find(|supported| supported.eq_ignore_ascii_case(language))
+            })
+        })
+        .unwrap_or(DEFAULT_ERROR_LANGUAGE)
+}
+
+/// Language of the requesting user, if the request has a token
+async fn user_language(pool: DbPool, req: &HttpRequest) -> Option<String> {
+    use crate::schema::app_users::dsl::{account_id, app_users, language};
+
+    let account = req.extensions().get::<u32>().copied()?;
+
+    web::block(move || {
+        let mut conn = pool.get().ok()?;
+
+        app_users
+            .filter(account_id.eq(account as i32))
+            .select(language)
+            .first::<Option<String>>(&mut conn)
+            .ok()
+            .flatten()
+    })
+    .await
+    .ok()
+    .flatten()
+}
+
+/// Translates the messages of the RouterError responses
+#[derive(Clone)]
+pub struct LocalizeErrors {
+    pool: DbPool,
+}
+
+impl LocalizeErrors {
+    pub fn new(pool: DbPool) -> Self {
+        Self { pool }
+    }
+}
+
+impl<S> Transform<S, ServiceRequest> for LocalizeErrors
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<BoxBody>, Error = Error> + 'static,
+    S::Future: 'static,
+{
+    type Response = ServiceResponse<BoxBody>;
+    type Error = Error;
+    type Transform = LocalizeErrorsMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(LocalizeErrorsMiddleware {
+            service: Rc::new(service),
+            pool: self.pool.clone(),
+        }))
+    }
+}
+
+pub struct LocalizeErrorsMiddleware<S> {
+    service: Rc<S>,
+    pool: DbPool,
+}
+
+impl<S> Service<ServiceRequest> for LocalizeErrorsMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<BoxBody>, Error = Error> + 'static,
+    S::Future: 'static,
+{
+    type Response = ServiceResponse<BoxBody>;
+    type Error = Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let service = Rc::clone(&self.service);
+        let pool = self.pool.clone();
+
+        // The request can't be cloned before routing, middleware errors
+        // (like the token errors) come back without it
+        let accept_language = req
+            .headers()
+            .get(ACCEPT_LANGUAGE)
+            .and_then(|value| value.to_str().ok())
+            .map(String::from);
+
+        let request_id = req
+            .headers()
+            .get(REQUEST_ID_HEADER)
+            .and_then(|value| value.to_str().ok())
+            .map(String::from);
+
+        Box::pin(async move {
+            let accept_language = accept_language.as_deref();
+            let request_id = request_id.as_deref();
+
+            let res = match service.call(req).await {
+                Ok(res) => res,
+
+                Err(err) => {
+                    let Some(router_error) = err.as_error::<RouterError>() else {
+                        return Err(err);
+                    };
+
+                    let response = router_error
+                        .localized_response(error_language(accept_language, None), request_id);
+
+                    return Err(InternalError::from_response(router_error.clone(), response).into());
+                }
+            };
+
+            let Some(router_error) = res
+                .response()
+                .error()
+                .and_then(|err| err.as_error::<RouterError>())
+                .cloned()
+            else {
+                return Ok(res);
+            };
+
+            let http_req = res.into_parts().0;
+
+            // The user is looked up only if the header has no supported language
+            let user_language = match accept_language.map(accepted_languages) {
+                Some(languages) if !languages.is_empty() => None,
+                _ => user_language(pool, &http_req).await,
+            };
+
+            let language = error_language(accept_language, user_language.as_deref());
+
+            let response = router_error.localized_response(language, request_id);
+
+            Ok(ServiceResponse::new(http_req, response))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{accepted_languages, error_language, LocalizeErrors};
+    use crate::error::{load_predefined_errors, RouterError};
+    use actix_web::{
+        test::{call_and_read_body_json, init_service, TestRequest},
+        web, App,
+    };
+    use diesel::r2d2::{ConnectionManager, Pool};
+
+    #[test]
+    fn test_accepted_languages() {
+        assert_eq!(
+            accepted_languages("fa-IR,fa;q=0.9,en;q=0.8"),
+            vec!["fa", "fa", "en"]
+        );
+        assert_eq!(accepted_languages("en;q=0.5, ar"), vec!["ar", "en"]);
+        assert_eq!(
+            accepted_languages("de, fr;q=0.9, en;q=0"),
+            Vec::<&str>::new()
+        );
+        assert_eq!(accepted_languages("*"), Vec::<&str>::new());
+    }
+
+    #[test]
+    fn test_error_language() {
+        assert_eq!(error_language(Some("ar"), Some("fa")), "ar");
+        assert_eq!(error_language(Some("de"), Some("fa")), "fa");
+        assert_eq!(error_language(None, Some("FA")), "fa");
+        assert_eq!(error_language(None, Some("de")), "en");
+        assert_eq!(error_language(None, None), "en");
+    }
+
+    #[actix_web::test]
+    async fn test_localize_errors() {
+        load_predefined_errors();
+
+        // The header has the language, database is not touched
+        let pool = Pool::builder().build_unchecked(ConnectionManager::new("postgres://localhost"));
+
+        let app = init_service(App::new().wrap(LocalizeErrors::new(pool)).route(
+            "/",
+            web::get().to(|| async { Err::<String, _>(RouterError::from_predefined("NOT_FOUND")) }),
+        ))
+        .await;
+
+        let req = TestRequest::get()
+            .uri("/")
+            .insert_header(("Accept-Language", "fa-IR,fa;q=0.9"))
+            .to_request();
+
+        let body: serde_json::Value = call_and_read_body_json(&app, req).await;
+
+        assert_eq!(body["error_name"], "NOT_FOUND");
+        assert_eq!(body["message"], "پیدا نشد!");
+
+        let req = TestRequest::get()
+            .uri("/")
+            .insert_header(("Accept-Language", "en"))
+            .to_request();
+
+        let body: serde_json::Value = call_and_read_body_json(&app, req).await;
+
+        assert_eq!(body["message"], "Not found!");
+    }
+}
@@ -0,0 +1,383 @@
+use crate::{models::NewErrorLog, DbPool};
+use actix_web::web;
+use auth_n::HashBuilder;
+use chrono::{Duration, Utc};
+use diesel::prelude::*;
+use diesel::sql_query;
+use diesel::sql_types::{BigInt, Timestamptz};
+use log::{error, info};
+use regex::Regex;
+use serde_json::Value;
+use std::{
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        mpsc::{self, SyncSender, TrySendError},
+        OnceLock,
+    },
+    thread,
+    time::Instant,
+};
+
+/// Error logs are kept this many days if ERROR_LOG_RETENTION_DAYS is not set
+pub const DEFAULT_RETENTION_DAYS: i64 = 30;
+
+/// The purge worker runs with this interval
+const PURGE_INTERVAL: std::time::Duration = std::time::Duration::from_secs(3600);
+
+/// Max rows that are deleted in one statement, keeps the locks short
+const PURGE_BATCH_SIZE: i64 = 5_000;
+
+/// Replaces the secrets in the logs
+const REDACTED: &str = "[REDACTED]";
+
+/// JSON keys and query params that their values are never logged
+const SECRET_KEYS: [&str; 9] = [
+    "token",
+    "code",
+    "password",
+    "secret",
+    "client_secret",
+    "email",
+    "authorization",
+    "state",
+    "nonce",
+];
+
+fn is_secret_key(key: &str) -> bool {
+    SECRET_KEYS
+        .into_iter()
+        .any(|secret| secret.eq_ignore_ascii_case(key))
+}
+
+fn email_regex() -> &'static Regex {
+    static EMAIL: OnceLock<Regex> = OnceLock::new();
+
+    EMAIL.get_or_init(|| Regex::new(r"[\w.+-]+(@|%40)[\w-]+(\.[\w-]+)+").unwrap())
+}
+
+/// Hash of the token, same as the `token_hash` of app_tokens
+///
+/// Logs never have the token itself
+pub fn redact_token(token: &str) -> String {
+    let token_bytes = token.as_bytes().to_vec();
+
+    HashBuilder::default()
+        .set_source(&token_bytes)
+        .generate()
+        .get_result()
+        .unwrap_or_default()
+}
+
+/// Replaces the email addresses of the text
+pub fn redact_text(text: &str) -> String {
+    email_regex().replace_all(text, REDACTED).into_owned()
+}
+
+fn redact_value(value: &mut Value) {
+    match value {
+        Value::Object(object) => {
+            for (key, value) in object.iter_mut() {
+                if is_secret_key(key) {
+                    *value = Value::from(REDACTED);
+                } else {
+                    redact_value(value);
+                }
+            }
+        }
+
+        Value::Array(items) => items.iter_mut().for_each(redact_value),
+
+        Value::String(text) => *text = redact_text(text),
+
+        _ => {}
+    }
+}
+
+/// Redacts the secret keys of JSON bodies and the emails of any body
+pub fn redact_body(body: &[u8]) -> Vec<u8> {
+    match serde_json::from_slice::<Value>(body) {
+        Ok(mut json) => {
+            redact_value(&mut json);
+
+            json.to_string().into_bytes()
+        }
+
+        Err(_) => redact_text(&String::from_utf8_lossy(body)).into_bytes(),
+    }
+}
+
+/// Redacts the secret params and the emails of the url
+pub fn redact_url(url: &str) -> String {
+    let Some((path, query)) = url.split_once('?') else {
+        return redact_text(url);
+    };
+
+    let query: Vec<String> = query
+        .split('&')
+        .map(|param| match param.split_once('=') {
+            Some((key, _)) if is_secret_key(key) => format!("{}={}", key, REDACTED),
+            _ => redact_text(param),
+        })
+        .collect();
+
+    format!("{}?{}", redact_text(path), query.join("&"))
+}
+
+/// Max queued error logs, the new ones are written to the application log when it's full
+const QUEUE_CAPACITY: usize = 10_000;
+
+/// Max error logs that are inserted in one statement
+const INSERT_BATCH_SIZE: usize = 100;
+
+/// The logger waits this much to fill the batch
+const FLUSH_INTERVAL: std::time::Duration = std::time::Duration::from_millis(500);
+
+static QUEUE: OnceLock<SyncSender<NewErrorLog>> = OnceLock::new();
+
+static QUEUED: AtomicU64 = AtomicU64::new(0);
+static WRITTEN: AtomicU64 = AtomicU64::new(0);
+static DROPPED: AtomicU64 = AtomicU64::new(0);
+static FALLBACK: AtomicU64 = AtomicU64::new(0);
+
+/// Counters of the error logger
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ErrorLogMetrics {
+    /// Queued to be inserted
+    pub queued: u64,
+
+    /// Inserted to app_error_logs
+    pub written: u64,
+
+    /// Written to the application log because the queue was full
+    pub dropped: u64,
+
+    /// Written to the application log because the database failed
+    pub fallback: u64,
+}
+
+impl ErrorLogMetrics {
+    /// Queued logs that are not handled yet
+    pub fn pending(&self) -> u64 {
+        self.queued
+            .saturating_sub(self.written)
+            .saturating_sub(self.fallback)
+    }
+}
+
+pub fn metrics() -> ErrorLogMetrics {
+    ErrorLogMetrics {
+        queued: QUEUED.load(Ordering::Relaxed),
+        written: WRITTEN.load(Ordering::Relaxed),
+        dropped: DROPPED.load(Ordering::Relaxed),
+        fallback: FALLBACK.load(Ordering::Relaxed),
+    }
+}
+
+/// Writes the error log to the application log (log4rs)
+fn write_fallback(log: &NewErrorLog) {
+    error!(
+        "{} ({}): {} | detail: {} | account: {:?} | request: {} | {} {}",
+        log.error_name,
+        log.status_code,
+        log.message,
+        log.detail.as_deref().unwrap_or_default(),
+        log.account_id,
+        log.request_id.as_deref().unwrap_or_default(),
+        log.request_ipv4,
+        log.request_url.as_deref().unwrap_or_default(),
+    );
+}
+
+fn insert(pool: &DbPool, logs: &[NewErrorLog]) -> Result<usize, String> {
+    use crate::schema::app_error_logs::dsl::app_error_logs;
+
+    let mut conn = pool.get().map_err(|err| err.to_string())?;
+
+    diesel::insert_into(app_error_logs)
+        .values(logs)
+        .execute(&mut conn)
+        .map_err(|err| err.to_string())
+}
+
+/// Starts the thread that inserts the queued error logs in batches
+pub fn start_logger(pool: DbPool) {
+    let (sender, receiver) = mpsc::sync_channel(QUEUE_CAPACITY);
+
+    if QUEUE.set(sender).is_err() {
+        return;
+    }
+
+    thread::spawn(move || {
+        let mut reported_dropped = 0;
+
+        while let Ok(first) = receiver.recv() {
+            let mut batch = vec![first];
+            let flush_at = Instant::now() + FLUSH_INTERVAL;
+
+            while batch.len() < INSERT_BATCH_SIZE {
+                match receiver.recv_timeout(flush_at.saturating_duration_since(Instant::now())) {
+                    Ok(log) => batch.push(log),
+                    Err(_) => break,
+                }
+            }
+
+            match insert(&pool, &batch) {
+                Ok(_) => {
+                    WRITTEN.fetch_add(batch.len() as u64, Ordering::Relaxed);
+                }
+
+                Err(err) => {
+                    error!("Can't insert {} error logs: {}", batch.len(), err);
+
+                    FALLBACK.fetch_add(batch.len() as u64, Ordering::Relaxed);
+                    batch.iter().for_each(write_fallback);
+                }
+            }
+
+            // Backpressure: the queue was full since the last report
+            let metrics = metrics();
+
+            if metrics.dropped > reported_dropped {
+                error!(
+                    "Error log queue was full, {} logs are only in the application log ({} pending)",
+                    metrics.dropped - reported_dropped,
+                    metrics.pending()
+                );
+
+                reported_dropped = metrics.dropped;
+            }
+        }
+    });
+}
+
+/// Queues the error log, never blocks the caller
+///
+/// Inserts it right away if the logger is not started (commands and tests)
+pub fn log(log: NewErrorLog, pool: &DbPool) {
+    let Some(queue) = QUEUE.get() else {
+        if let Err(err) = insert(pool, std::slice::from_ref(&log)) {
+            error!("Can't insert the error log: {}", err);
+            write_fallback(&log);
+        }
+
+        return;
+    };
+
+    match queue.try_send(log) {
+        Ok(()) => {
+            QUEUED.fetch_add(1, Ordering::Relaxed);
+        }
+
+        Err(TrySendError::Full(log)) | Err(TrySendError::Disconnected(log)) => {
+            DROPPED.fetch_add(1, Ordering::Relaxed);
+            write_fallback(&log);
+        }
+    }
+}
+
+/// Deletes the error logs that are older than the retention
+pub fn purge_expired(conn: &mut PgConnection, retention_days: i64) -> QueryResult<usize> {
+    let expired_before = Utc::now() - Duration::days(retention_days);
+
+    let mut purged = 0;
+
+    loop {
+        let deleted = sql_query(
+            "DELETE FROM app_error_logs WHERE id IN
+                (SELECT id FROM app_error_logs WHERE created_at < $1 LIMIT $2)",
+        )
+        .bind::<Timestamptz, _>(expired_before)
+        .bind::<BigInt, _>(PURGE_BATCH_SIZE)
+        .execute(conn)?;
+
+        purged += deleted;
+
+        if deleted < PURGE_BATCH_SIZE as usize {
+            return Ok(purged);
+        }
+    }
+}
+
+/// Background worker that purges the expired error logs
+pub async fn run_purge_worker(pool: DbPool, retention_days: i64) {
+    let mut interval = actix_web::rt::time::interval(PURGE_INTERVAL);
+
+    loop {
+        interval.tick().await;
+
+        let pool = pool.clone();
+
+        let result = web::block(move || {
+            let mut conn = pool.get().map_err(|err| err.to_string())?;
+
+            purge_expired(&mut conn, retention_days).map_err(|err| err.to_string())
+        })
+        .await;
+
+        match result {
+            Ok(Ok(0)) => {}
+            Ok(Ok(purged)) => info!("Purged {} expired error logs", purged),
+            Ok(Err(err)) => error!("Error log purge: {}", err),
+            Err(err) => error!("Error log purge: {}", err),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{redact_body, redact_text, redact_token, redact_url, ErrorLogMetrics};
+
+    #[test]
+    fn test_redact_body() {
+        let body = br#"{"email":"user@natiq.net","code":123456,"name":"ali","members":[{"note":"ask a.b@c.org"}]}"#;
+
+        let redacted: serde_json::Value = serde_json::from_slice(&redact_body(body)).unwrap();
+
+        assert_eq!(redacted["email"], "[REDACTED]");
+        assert_eq!(redacted["code"], "[REDACTED]");
+        assert_eq!(redacted["name"], "ali");
+        assert_eq!(redacted["members"][0]["note"], "ask [REDACTED]");
+
+        assert_eq!(redact_body(b"to user@natiq.net"), b"to [REDACTED]".to_vec());
+    }
+
+    #[test]
+    fn test_redact_url() {
+        assert_eq!(
+            redact_url("http://localhost/account/oidc/callback?code=abc&state=xyz&lang=fa"),
+            "http://localhost/account/oidc/callback?code=[REDACTED]&state=[REDACTED]&lang=fa"
+        );
+        assert_eq!(
+            redact_url("http://localhost/user?q=user%40natiq.net"),
+            "http://localhost/user?q=[REDACTED]"
+        );
+        assert_eq!(
+            redact_url("http://localhost/organization/1"),
+            "http://localhost/organization/1"
+        );
+    }
+
+    #[test]
+    fn test_redact_text_and_token() {
+        assert_eq!(
+            redact_text("missing field `x` for user@natiq.net"),
+            "missing field `x` for [REDACTED]"
+        );
+
+        let hash = redact_token("token");
+        assert_eq!(hash.len(), 64);
+        assert_ne!(hash, "token");
+    }
+
+    #[test]
+    fn test_error_log_metrics_pending() {
+        let metrics = ErrorLogMetrics {
+            queued: 10,
+            written: 6,
+            dropped: 3,
+            fallback: 1,
+        };
+
+        assert_eq!(metrics.pending(), 3);
+    }
+}
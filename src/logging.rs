@@ -0,0 +1,364 @@
+use crate::request_id::REQUEST_ID_HEADER;
+use actix_web::{
+    dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform},
+    Error, HttpMessage,
+};
+use log::{info, LevelFilter};
+use log4rs::{
+    append::{
+        console::ConsoleAppender,
+        rolling_file::{
+            policy::compound::{
+                roll::fixed_window::FixedWindowRoller, trigger::size::SizeTrigger, CompoundPolicy,
+            },
+            RollingFileAppender,
+        },
+    },
+    config::{load_config_file, Appender, Logger, Root},
+    encode::{json::JsonEncoder, pattern::PatternEncoder, Encode},
+    Config, Handle,
+};
+use serde::Deserialize;
+use std::{
+    future::{ready, Future, Ready},
+    path::PathBuf,
+    pin::Pin,
+    rc::Rc,
+    str::FromStr,
+    time::Instant,
+};
+
+/// Target of the access logs, `access=off` in LOG_LEVEL disables them
+pub const ACCESS_TARGET: &str = "access";
+
+/// Pattern of the `text` format
+const TEXT_PATTERN: &str = "{d(%Y-%m-%dT%H:%M:%S%.3f%:z)} {l} {t} - {m}{n}";
+
+/// Log files are rolled when they reach this size, if `file_max_size` is not set
+const DEFAULT_FILE_MAX_SIZE: u64 = 10 * 1024 * 1024;
+
+/// Rolled log files that are kept, if `file_count` is not set
+const DEFAULT_FILE_COUNT: u32 = 5;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogFormat {
+    /// One JSON object per line, for the log pipelines
+    #[default]
+    Json,
+
+    /// Human readable lines, for the development
+    Text,
+}
+
+impl FromStr for LogFormat {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "json" => Ok(Self::Json),
+            "text" => Ok(Self::Text),
+            _ => Err(format!("Unknown log format `{}`, use json or text", value)),
+        }
+    }
+}
+
+impl LogFormat {
+    fn encoder(&self) -> Box<dyn Encode> {
+        match self {
+            Self::Json => Box::new(JsonEncoder::new()),
+            Self::Text => Box::new(PatternEncoder::new(TEXT_PATTERN)),
+        }
+    }
+}
+
+/// Level of the root logger and of the modules
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "String")]
+pub struct LogLevels {
+    pub root: LevelFilter,
+    pub modules: Vec<(String, LevelFilter)>,
+}
+
+impl Default for LogLevels {
+    fn default() -> Self {
+        Self {
+            root: LevelFilter::Info,
+            modules: vec![],
+        }
+    }
+}
+
+impl FromStr for LogLevels {
+    type Err = String;
+
+    /// Same as RUST_LOG: `info,nq_api::email_outbox=debug,access=off`
+    fn from_str(spec: &str) -> Result<Self, Self::Err> {
+        let parse_level = |level: &str| {
+            LevelFilter::from_str(level.trim())
+                .map_err(|_| format!("Unknown log level `{}`", level.trim()))
+        };
+
+        let mut levels = Self::default();
+
+        for directive in spec.split(',').map(str::trim).filter(|d| !d.is_empty()) {
+            match directive.split_once('=') {
+                Some((module, level)) => levels
+                    .modules
+                    .push((module.trim().to_string(), parse_level(level)?)),
+
+                None => levels.root = parse_level(directive)?,
+            }
+        }
+
+        Ok(levels)
+    }
+}
+
+impl TryFrom<String> for LogLevels {
+    type Error = String;
+
+    fn try_from(spec: String) -> Result<Self, Self::Error> {
+        spec.parse()
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LogConfig {
+    pub format: LogFormat,
+
+    #[serde(rename = "level")]
+    pub levels: LogLevels,
+
+    /// Logs are also written to this file when it's set, they are
+    /// always written to the stdout
+    pub file_path: Option<PathBuf>,
+    pub file_max_size: u64,
+    pub file_count: u32,
+
+    /// A log4rs yaml config file, replaces all of the above
+    pub config_file: Option<PathBuf>,
+}
+
+impl Default for LogConfig {
+    fn default() -> Self {
+        Self {
+            format: LogFormat::default(),
+            levels: LogLevels::default(),
+            file_path: None,
+            file_max_size: DEFAULT_FILE_MAX_SIZE,
+            file_count: DEFAULT_FILE_COUNT,
+            config_file: None,
+        }
+    }
+}
+
+impl LogConfig {
+    fn build(&self) -> Result<Config, String> {
+        let mut builder = Config::builder().appender(
+            Appender::builder().build(
+                "stdout",
+                Box::new(
+                    ConsoleAppender::builder()
+                        .encoder(self.format.encoder())
+                        .build(),
+                ),
+            ),
+        );
+
+        let mut root = Root::builder().appender("stdout");
+
+        if let Some(path) = &self.file_path {
+            let roller = FixedWindowRoller::builder()
+                .build(&format!("{}.{{}}", path.display()), self.file_count)
+                .map_err(|err| format!("Can't create the log file roller: {}", err))?;
+
+            let policy = CompoundPolicy::new(
+                Box::new(SizeTrigger::new(self.file_max_size)),
+                Box::new(roller),
+            );
+
+            let file = RollingFileAppender::builder()
+                .encoder(self.format.encoder())
+                .build(path, Box::new(policy))
+                .map_err(|err| format!("Can't open the log file {}: {}", path.display(), err))?;
+
+            builder = builder.appender(Appender::builder().build("file", Box::new(file)));
+            root = root.appender("file");
+        }
+
+        for (module, level) in &self.levels.modules {
+            builder = builder.logger(Logger::builder().build(module, *level));
+        }
+
+        builder
+            .build(root.build(self.levels.root))
+            .map_err(|err| err.to_string())
+    }
+}
+
+/// Initializes the global logger
+pub fn init(config: &LogConfig) -> Result<Handle, String> {
+    let config = match &config.config_file {
+        Some(config_file) => load_config_file(config_file, Default::default())
+            .map_err(|err| format!("Can't load {}: {}", config_file.display(), err))?,
+
+        None => config.build()?,
+    };
+
+    log4rs::init_config(config).map_err(|err| err.to_string())
+}
+
+/// Logs every request with its status and latency
+///
+/// The fields are in the `mdc` of the JSON logs, must be
+/// wrapped inside the RequestId middleware
+#[derive(Clone, Default)]
+pub struct AccessLog;
+
+impl<S, B> Transform<S, ServiceRequest> for AccessLog
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Transform = AccessLogMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(AccessLogMiddleware {
+            service: Rc::new(service),
+        }))
+    }
+}
+
+pub struct AccessLogMiddleware<S> {
+    service: Rc<S>,
+}
+
+impl<S, B> Service<ServiceRequest> for AccessLogMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let service = Rc::clone(&self.service);
+
+        // The query is never logged, it may have secrets
+        let mut fields = vec![
+            ("method", req.method().to_string()),
+            ("path", req.path().to_string()),
+            (
+                "ip",
+                req.connection_info()
+                    .realip_remote_addr()
+                    .unwrap_or_default()
+                    .to_string(),
+            ),
+            (
+                "request_id",
+                req.headers()
+                    .get(REQUEST_ID_HEADER)
+                    .and_then(|value| value.to_str().ok())
+                    .unwrap_or_default()
+                    .to_string(),
+            ),
+        ];
+
+        let started_at = Instant::now();
+
+        Box::pin(async move {
+            let result = service.call(req).await;
+
+            let status = match &result {
+                Ok(res) => {
+                    if let Some(account) = res.request().extensions().get::<u32>() {
+                        fields.push(("account", account.to_string()));
+                    }
+
+                    res.status()
+                }
+
+                Err(err) => err.as_response_error().status_code(),
+            };
+
+            let latency_ms = started_at.elapsed().as_secs_f64() * 1000.0;
+
+            fields.push(("status", status.as_str().to_string()));
+            fields.push(("latency_ms", format!("{:.3}", latency_ms)));
+
+            {
+                // The mdc is per thread, nothing is awaited while it's set
+                let _mdc = log_mdc::extend_scoped(fields.iter().cloned());
+
+                info!(
+                    target: ACCESS_TARGET,
+                    "{} {} {} {:.3}ms",
+                    fields[0].1,
+                    fields[1].1,
+                    status.as_str(),
+                    latency_ms
+                );
+            }
+
+            result
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{LogConfig, LogFormat, LogLevels};
+    use log::LevelFilter;
+
+    #[test]
+    fn test_log_levels() {
+        let levels: LogLevels = "warn, nq_api::email_outbox=debug,access=off"
+            .parse()
+            .unwrap();
+
+        assert_eq!(levels.root, LevelFilter::Warn);
+        assert_eq!(
+            levels.modules,
+            vec![
+                ("nq_api::email_outbox".to_string(), LevelFilter::Debug),
+                ("access".to_string(), LevelFilter::Off),
+            ]
+        );
+
+        assert_eq!("".parse::<LogLevels>().unwrap(), LogLevels::default());
+        assert!("info,actix_web=loud".parse::<LogLevels>().is_err());
+    }
+
+    #[test]
+    fn test_log_format() {
+        assert_eq!("json".parse(), Ok(LogFormat::Json));
+        assert_eq!("text".parse(), Ok(LogFormat::Text));
+        assert!("xml".parse::<LogFormat>().is_err());
+    }
+
+    #[test]
+    fn test_build_config() {
+        let config = LogConfig {
+            levels: "error,access=info".parse().unwrap(),
+            ..Default::default()
+        };
+
+        let built = config.build().unwrap();
+
+        assert_eq!(built.root().level(), LevelFilter::Error);
+        assert_eq!(built.root().appenders(), ["stdout"]);
+        assert_eq!(built.loggers()[0].name(), "access");
+    }
+}
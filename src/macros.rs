@@ -1,32 +1,86 @@
 #[macro_export]
 /// This macro is used to impl the SelectModel for models
+///
+/// `select_model!(Model, table)` selects the model by its uuid,
+/// `select_model!(Model, table, account)` selects the model by the uuid of its account,
+/// `select_model!(Model, table, creator)` selects the model with its creator as `OwnedModel`
 macro_rules! select_model {
     ($struct:ty, $table_name:ident) => {
 
         #[async_trait]
         impl SelectModel for $struct {
-            async fn from_id(conn: DbPool, id: i32) -> Self {
+            async fn from_uuid(conn: DbPool, uuid: Uuid) -> Option<Self> {
                 use $crate::schema::$table_name::dsl as $table_name;
 
-                let mut conn = conn.get().unwrap();
+                let mut conn = conn.get().ok()?;
 
                 block(move || {
                     // Get the Required Resource
-                    let selected_model: Vec<$struct> = $table_name::$table_name
-                        // Filter it by id (select by id)
-                        .filter($table_name::id.eq(id))
-                        .load(&mut conn)
-                        .unwrap();
+                    $table_name::$table_name
+                        // Filter it by uuid (select by uuid)
+                        .filter($table_name::uuid.eq(uuid))
+                        .first::<$struct>(&mut conn)
+                        .ok()
+                })
+                .await
+                .ok()?
+            }
+        }
+    };
+
+    ($struct:ty, $table_name:ident, account) => {
 
-                    // Get the first item of vec
-                    let selected_model = selected_model.get(0).unwrap();
+        #[async_trait]
+        impl SelectModel for $struct {
+            async fn from_uuid(conn: DbPool, uuid: Uuid) -> Option<Self> {
+                use $crate::schema::app_accounts::dsl as app_accounts;
+                use $crate::schema::$table_name::dsl as $table_name;
 
-                    selected_model.clone()
+                let mut conn = conn.get().ok()?;
+
+                block(move || {
+                    // The model is identified by its account
+                    $table_name::$table_name
+                        .inner_join(app_accounts::app_accounts)
+                        .filter(app_accounts::uuid.eq(uuid))
+                        .select(<$struct>::as_select())
+                        .first::<$struct>(&mut conn)
+                        .ok()
                 })
                 .await
-                .unwrap()
+                .ok()?
             }
         }
     };
-}
 
+    ($struct:ty, $table_name:ident, creator) => {
+
+        #[async_trait]
+        impl SelectModel for OwnedModel<$struct> {
+            async fn from_uuid(conn: DbPool, uuid: Uuid) -> Option<Self> {
+                use $crate::schema::app_users::dsl as app_users;
+                use $crate::schema::$table_name::dsl as $table_name;
+
+                let mut conn = conn.get().ok()?;
+
+                block(move || {
+                    // The account of the creator user owns the model
+                    $table_name::$table_name
+                        .inner_join(
+                            app_users::app_users.on(app_users::id.eq($table_name::creator_user_id)),
+                        )
+                        .filter($table_name::uuid.eq(uuid))
+                        .select((<$struct>::as_select(), app_users::account_id))
+                        .first::<($struct, i32)>(&mut conn)
+                        .ok()
+                        .map(|(model, owner_account_id)| OwnedModel {
+                            model,
+                            owner_account_id,
+                        })
+                })
+                .await
+                .ok()?
+            }
+        }
+    };
+}
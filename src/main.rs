@@ -1,8 +1,8 @@
 use crate::error::PreDefinedResponseError;
-use actix_cors::Cors;
-use actix_web::web::PathConfig;
-use actix_web::{middleware, web, App, HttpServer};
+use actix_web::web::{JsonConfig, PathConfig, QueryConfig};
+use actix_web::{guard, middleware, web, App, HttpServer};
 use authz::AuthZController;
+use config::{AppConfig, DatabaseConfig, OidcConfig, TransportKind};
 use diesel_migrations::{embed_migrations, EmbeddedMigrations, MigrationHarness};
 
 use auth_n::middleware::TokenAuth;
@@ -10,31 +10,47 @@ use auth_z::middleware::AuthZ;
 use diesel::pg::PgConnection;
 use diesel::r2d2::{ConnectionManager, Pool};
 use dotenvy::dotenv;
-use email::EmailManager;
-use error::{path_error_handler, PreDefinedResponseErrors};
+use email::{EmailError, EmailManager, FileTransport, SmtpTransport};
+use error::{json_error_handler, path_error_handler, query_error_handler, PreDefinedResponseErrors};
+use error_language::LocalizeErrors;
+use metrics::RequestMetrics;
+use request_id::RequestId;
 use lettre::transport::smtp::authentication::Credentials;
-use log::LevelFilter;
-use log4rs::append::file::FileAppender;
-use log4rs::config::{Appender, Root};
-use log4rs::encode::pattern::PatternEncoder;
-use log4rs::Config;
+use logging::AccessLog;
+use oidc::OidcClient;
 use std::collections::HashMap;
 use std::error::Error;
-use std::io::ErrorKind;
+use std::io::Write;
 use std::sync::OnceLock;
-use std::{env, io};
+use std::{env, io, process};
 use token_checker::UserIdFromToken;
 
 mod authz;
+mod condition;
+mod config;
 mod datetime;
 mod email;
+mod email_outbox;
+mod email_template;
 mod error;
+mod error_language;
+mod error_log;
 mod filter;
+mod logging;
+mod metrics;
 pub mod models;
 mod models_filter;
+mod oidc;
+mod permission_cache;
+mod pg_listener;
+mod request_id;
+mod route_metadata;
 mod routers;
 mod schema;
+mod seed;
 mod select_model;
+#[cfg(test)]
+mod test_db;
 mod token_checker;
 mod validate;
 
@@ -42,12 +58,23 @@ mod difference;
 mod macros;
 
 use routers::account::logout;
+use routers::account::oidc as account_oidc;
 use routers::account::send_code;
 use routers::account::verify;
-use routers::error::errors_list;
-use routers::organization::{add, delete, edit, list, name, view};
+use routers::email::outbox_list;
+use routers::error::{errors_list, stats::errors_stats};
+use routers::health;
+use routers::organization::{add, delete, edit, employee, invitation, list, name, transfer, view};
+use routers::group::{
+    add as group_add, delete as group_delete, edit as group_edit, list as group_list, member,
+    view as group_view,
+};
+use routers::role::{
+    add as role_add, assignment, delete as role_delete, edit as role_edit, list as role_list,
+    view as role_view,
+};
 use routers::permission::{
-    add_permission, delete_permission, edit_permission, permissions_list, view_permission,
+    add_permission, delete_permission, edit_permission, explain, permissions_list, view_permission,
 };
 use routers::phrase::{add_phrase, delete_phrase, edit_phrase, phrase_list, view_phrase};
 use routers::profile::{profile_edit, profile_view};
@@ -74,90 +101,210 @@ fn run_migrations(
     Ok(())
 }
 
-pub fn create_emailer() -> EmailManager {
-    let host = env::var("SMTP_HOST").expect("SMTP_HOST must be set");
-    let port = env::var("SMTP_PORT").expect("SMTP_PORT must be set");
-    let from = env::var("SMTP_FROM").expect("SMTP_FROM must be set");
-    let username = env::var("SMTP_USERNAME").expect("SMTP_USERNAME must be set");
-    let password = env::var("SMTP_PASSWORD").expect("SMTP_PASSWORD must be set");
+/// Creates the emailer of the `email.transport`
+///
+/// `smtp` (default): sends emails with the `smtp` config
+///
+/// `file`: writes emails to the `email.file_dir`
+pub fn create_emailer(config: &AppConfig) -> Result<EmailManager, EmailError> {
+    let from = config.email.from();
+
+    match config.email.transport {
+        TransportKind::Smtp => {
+            let smtp = &config.smtp;
+
+            // Validated on the startup
+            let host = smtp.host.clone().unwrap_or_default();
+            let port = smtp.port.unwrap_or_default();
+            let tls = smtp.tls()?;
+
+            // Both or none of them are set
+            let credentials = smtp
+                .username
+                .clone()
+                .zip(smtp.password.clone())
+                .map(|(username, password)| Credentials::new(username, password));
+
+            let transport = SmtpTransport::new(&host, port, tls, credentials)?;
+
+            Ok(EmailManager::new(transport, from))
+        }
+
+        TransportKind::File => {
+            let transport = FileTransport::new(config.email.file_dir.clone())?;
 
-    let credentials = Credentials::new(username, password);
+            Ok(EmailManager::new(transport, from))
+        }
+    }
+}
+
+/// OpenID Connect login is optional, returns None
+/// if the provider is not configured
+pub fn create_oidc_client(config: &OidcConfig) -> Option<OidcClient> {
+    let issuer = config.issuer.clone().filter(|issuer| !issuer.is_empty())?;
 
-    EmailManager::new(&host, port.parse().unwrap(), credentials, from)
-        .expect("Cant create EmailManager")
+    // Validated on the startup
+    Some(OidcClient::new(
+        issuer,
+        config.client_id.clone().unwrap_or_default(),
+        config.client_secret.clone().unwrap_or_default(),
+        config.redirect_uri.clone().unwrap_or_default(),
+    ))
 }
 
-pub fn establish_database_connection() -> ConnectionManager<PgConnection> {
-    let database_url = env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+/// Runs the cli command instead of starting the server
+///
+/// `seed`: fills the database with development data
+fn run_command(command: &str, pool: &DbPool, out: &mut impl Write) -> io::Result<()> {
+    match command {
+        "seed" => {
+            let accounts = seed::seed(pool).map_err(|err| io::Error::other(err.to_string()))?;
+
+            for account in accounts {
+                writeln!(
+                    out,
+                    "{} <{}> token: {}",
+                    account.username, account.email, account.token
+                )?;
+            }
+
+            Ok(())
+        }
 
-    ConnectionManager::<PgConnection>::new(database_url)
+        _ => Err(io::Error::other(format!("Unknown command: {}", command))),
+    }
+}
+
+pub fn establish_database_connection(config: &DatabaseConfig) -> ConnectionManager<PgConnection> {
+    ConnectionManager::<PgConnection>::new(&config.url)
 }
 
 #[actix_web::main]
 async fn main() -> std::io::Result<()> {
     dotenv().ok();
 
-    let file_log = FileAppender::builder()
-        .encoder(Box::new(PatternEncoder::new("{d} - {m}{n}")))
-        .build("$ENV{LOG_FILE_PATH}")
-        .unwrap();
-
-    let log_config = Config::builder()
-        .appender(Appender::builder().build("logfile", Box::new(file_log)))
-        .build(
-            Root::builder()
-                .appender("logfile")
-                .build(LevelFilter::Error),
-        )
-        .unwrap();
+    // Every problem of the config is printed before exiting
+    let config = match AppConfig::load() {
+        Ok(config) => config,
+        Err(err) => {
+            eprint!("{}", err);
+            process::exit(1);
+        }
+    };
 
     // Don't need to change configuration on runtime for now
-    let _handle = log4rs::init_config(log_config).unwrap();
+    let _handle = logging::init(&config.log).map_err(io::Error::other)?;
 
     let Ok(json) =
         serde_json::from_str::<HashMap<String, PreDefinedResponseError>>(FIXED_ERROR_JSON)
     else {
-        return Err(io::Error::new(
-            ErrorKind::Other,
+        return Err(io::Error::other(
             "Failed to parse fixed_error_responses json!",
         ));
     };
 
     let Ok(()) = FIXED_ERROR_RESPONSES.set(PreDefinedResponseErrors { errors: json }) else {
-        return Err(io::Error::new(
-            ErrorKind::Other,
+        return Err(io::Error::other(
             "Could't set to static FIXED_ERROR_RESPONSES",
         ));
     };
 
-    let pg_manager = establish_database_connection();
+    let pg_manager = establish_database_connection(&config.database);
 
     let pool = Pool::builder()
+        .max_size(config.database.pool_size)
+        .connection_timeout(config.database.connection_timeout())
+        .idle_timeout(Some(config.database.idle_timeout()))
         .build(pg_manager)
         .expect("Failed to create pool.");
 
     run_migrations(&mut pool.get().unwrap()).unwrap();
 
-    let mailer = create_emailer();
+    // Commands are never run unless requested,
+    // `nq-api seed`
+    if let Some(command) = env::args().nth(1) {
+        return run_command(&command, &pool, &mut io::stdout());
+    }
 
-    let user_id_from_token = UserIdFromToken::new(pool.clone());
+    // Error logs are inserted in the background from now on
+    error_log::start_logger(pool.clone());
+
+    let mailer = create_emailer(&config).map_err(|err| io::Error::other(err.to_string()))?;
+
+    // Sends the queued emails in the background
+    actix_web::rt::spawn(email_outbox::run_worker(
+        pool.clone(),
+        mailer.clone(),
+        config.database.url.clone(),
+    ));
+
+    // Deletes the finished outbox emails in the background
+    actix_web::rt::spawn(email_outbox::run_purge_worker(
+        pool.clone(),
+        config.email.outbox_retention_days,
+    ));
+
+    // Deletes the expired error logs in the background
+    actix_web::rt::spawn(error_log::run_purge_worker(pool.clone(), config.error_log.retention_days));
+
+    let oidc_client = create_oidc_client(&config.oidc);
+
+    // Deletes the abandoned login sessions in the background
+    if oidc_client.is_some() {
+        actix_web::rt::spawn(oidc::run_session_purge_worker(pool.clone()));
+    }
+
+    let user_id_from_token = UserIdFromToken::new(pool.clone(), config.account.token_lifetime_seconds);
 
     let auth_z_controller = AuthZController::new(pool.clone());
+    let declared_routes = route_metadata::declared_routes();
+
+    // Keeps the permission cache of every replica consistent
+    auth_z_controller.listen_permission_changes(config.database.url.clone());
 
-    HttpServer::new(move || {
-        // Set All to the cors
-        let cors = Cors::permissive();
+    let server_config = config.server.clone();
+    let account_config = config.account.clone();
+
+    let server = HttpServer::new(move || {
+        // Permissive unless the origins are configured
+        let cors = server_config.cors();
 
         App::new()
+            // Must be the inner most, it needs the token of the request
+            .wrap(LocalizeErrors::new(pool.clone()))
             .wrap(cors)
             .wrap(middleware::DefaultHeaders::new().add(("Cache-Control", "no-cache")))
+            .wrap(RequestMetrics::new(declared_routes.clone()))
+            .wrap(AccessLog)
+            // Must be the outer most, every log and response has the request id
+            .wrap(RequestId)
             .app_data(PathConfig::default().error_handler(path_error_handler))
+            .app_data(JsonConfig::default().error_handler(json_error_handler))
+            .app_data(QueryConfig::default().error_handler(query_error_handler))
             .app_data(web::Data::new(pool.clone()))
-            .app_data(web::Data::new(mailer.clone()))
+            .route("/metrics", web::get().to(metrics::export))
+            .service(
+                web::scope("/health")
+                    .app_data(web::Data::new(mailer.clone()))
+                    .route("/live", web::get().to(health::live))
+                    .route("/ready", web::get().to(health::ready)),
+            )
             .service(
                 web::scope("/account")
+                    .app_data(web::Data::new(account_config.clone()))
                     .route("/sendCode", web::post().to(send_code::send_code))
                     .route("/verify", web::post().to(verify::verify))
+                    .configure(|cfg| {
+                        // Only available when the identity provider is configured
+                        if let Some(oidc_client) = oidc_client.clone() {
+                            cfg.app_data(web::Data::new(oidc_client))
+                                .route("/oidc/login", web::get().to(account_oidc::oidc_login))
+                                .route(
+                                    "/oidc/callback",
+                                    web::get().to(account_oidc::oidc_callback),
+                                );
+                        }
+                    })
                     .service(
                         web::resource("/logout")
                             .wrap(TokenAuth::new(user_id_from_token.clone(), true))
@@ -204,19 +351,28 @@ async fn main() -> std::io::Result<()> {
                             .route(web::delete().to(translation_delete::translation_delete)),
                     )
                     .service(
-                        web::scope("/text").service(
-                            web::resource("/{translation_uuid}")
-                                .wrap(TokenAuth::new(user_id_from_token.clone(), false))
-                                .route(web::get().to(translation_text_view::translation_text_view))
-                                .route(
-                                    web::post()
-                                        .to(translation_text_modify::translation_text_modify),
-                                )
-                                .route(
-                                    web::delete()
-                                        .to(translation_text_delete::translation_text_delete),
-                                ),
-                        ),
+                        web::scope("/text")
+                            .service(
+                                web::resource("/{translation_uuid}")
+                                    .guard(guard::Any(guard::Post()).or(guard::Delete()))
+                                    .wrap(
+                                        AuthZ::new(auth_z_controller.clone())
+                                            .routes(declared_routes.clone()),
+                                    )
+                                    .wrap(TokenAuth::new(user_id_from_token.clone(), true))
+                                    .route(
+                                        web::post()
+                                            .to(translation_text_modify::translation_text_modify),
+                                    )
+                                    .route(
+                                        web::delete()
+                                            .to(translation_text_delete::translation_text_delete),
+                                    ),
+                            )
+                            .route(
+                                "/{translation_uuid}",
+                                web::get().to(translation_text_view::translation_text_view),
+                            ),
                     ),
             )
             .service(
@@ -286,23 +442,106 @@ async fn main() -> std::io::Result<()> {
                 web::scope("/profile")
                     .wrap(TokenAuth::new(user_id_from_token.clone(), true))
                     .route("", web::get().to(profile_view::profile_view))
-                    .route("", web::post().to(profile_edit::profile_edit)),
+                    .route("", web::post().to(profile_edit::profile_edit))
+                    .route(
+                        "/invitations",
+                        web::get().to(invitation::account_invitations),
+                    )
+                    .route(
+                        "/invitations/{invitation_uuid}",
+                        web::post().to(invitation::accept_invitation),
+                    )
+                    .route(
+                        "/invitations/{invitation_uuid}",
+                        web::delete().to(invitation::decline_invitation),
+                    ),
+            )
+            .service(
+                web::scope("/organization/name")
+                    .service(
+                        web::resource("")
+                            .wrap(
+                                AuthZ::new(auth_z_controller.clone())
+                                    .routes(declared_routes.clone()),
+                            )
+                            .wrap(TokenAuth::new(user_id_from_token.clone(), true))
+                            .route(web::post().to(name::add_name)),
+                    )
+                    .service(
+                        web::resource("/{uuid}")
+                            .wrap(
+                                AuthZ::new(auth_z_controller.clone())
+                                    .routes(declared_routes.clone()),
+                            )
+                            .wrap(TokenAuth::new(user_id_from_token.clone(), true))
+                            .route(web::get().to(name::names))
+                            .route(web::post().to(name::edit_name))
+                            .route(web::delete().to(name::delete_name)),
+                    ),
             )
             .service(
                 web::scope("/organization")
-                    .wrap(AuthZ::new(auth_z_controller.clone()))
-                    .wrap(TokenAuth::new(user_id_from_token.clone(), true))
-                    .route("/name", web::post().to(name::add_name))
-                    .route("/name/{uuid}", web::get().to(name::names))
-                    .route("/name/{uuid}", web::post().to(name::edit_name))
-                    .route("/name/{uuid}", web::delete().to(name::delete_name))
-                    .route("", web::get().to(list::get_list_of_organizations))
-                    .route("", web::post().to(add::add))
-                    .route("/{account_uuid}", web::get().to(view::view))
-                    .route("/{account_uuid}", web::post().to(edit::edit_organization))
-                    .route(
-                        "/{account_uuid}",
-                        web::delete().to(delete::delete_organization),
+                    .service(
+                        web::resource("")
+                            .wrap(AuthZ::new(auth_z_controller.clone()))
+                            .wrap(TokenAuth::new(user_id_from_token.clone(), true))
+                            .route(web::get().to(list::get_list_of_organizations))
+                            .route(web::post().to(add::add)),
+                    )
+                    .service(
+                        web::resource("/{account_uuid}")
+                            .wrap(AuthZ::new(auth_z_controller.clone()))
+                            .wrap(TokenAuth::new(user_id_from_token.clone(), true))
+                            .route(web::get().to(view::view))
+                            .route(web::post().to(edit::edit_organization))
+                            .route(web::delete().to(delete::delete_organization)),
+                    )
+                    .service(
+                        web::resource("/{account_uuid}/employees")
+                            .wrap(
+                                AuthZ::new(auth_z_controller.clone())
+                                    .routes(declared_routes.clone()),
+                            )
+                            .wrap(TokenAuth::new(user_id_from_token.clone(), true))
+                            .route(web::get().to(employee::employees))
+                            .route(web::post().to(employee::add_employee)),
+                    )
+                    .service(
+                        web::resource("/{account_uuid}/employees/{employee_uuid}")
+                            .wrap(
+                                AuthZ::new(auth_z_controller.clone())
+                                    .routes(declared_routes.clone()),
+                            )
+                            .wrap(TokenAuth::new(user_id_from_token.clone(), true))
+                            .route(web::delete().to(employee::remove_employee)),
+                    )
+                    .service(
+                        web::resource("/{account_uuid}/invitations")
+                            .wrap(
+                                AuthZ::new(auth_z_controller.clone())
+                                    .routes(declared_routes.clone()),
+                            )
+                            .wrap(TokenAuth::new(user_id_from_token.clone(), true))
+                            .route(web::get().to(invitation::invitations))
+                            .route(web::post().to(invitation::invite)),
+                    )
+                    .service(
+                        web::resource("/{account_uuid}/invitations/{invitation_uuid}")
+                            .wrap(
+                                AuthZ::new(auth_z_controller.clone())
+                                    .routes(declared_routes.clone()),
+                            )
+                            .wrap(TokenAuth::new(user_id_from_token.clone(), true))
+                            .route(web::delete().to(invitation::revoke_invitation)),
+                    )
+                    .service(
+                        web::resource("/{account_uuid}/transfer")
+                            .wrap(
+                                AuthZ::new(auth_z_controller.clone())
+                                    .routes(declared_routes.clone()),
+                            )
+                            .wrap(TokenAuth::new(user_id_from_token.clone(), true))
+                            .route(web::post().to(transfer::transfer_ownership)),
                     ),
             )
             .service(
@@ -311,6 +550,12 @@ async fn main() -> std::io::Result<()> {
                     .wrap(TokenAuth::new(user_id_from_token.clone(), true))
                     .route("", web::get().to(permissions_list::get_list_of_permissions))
                     .route("", web::post().to(add_permission::add_permission))
+                    .service(
+                        web::resource("/explain")
+                            .app_data(web::Data::new(auth_z_controller.clone()))
+                            .app_data(web::Data::new(declared_routes.clone()))
+                            .route(web::get().to(explain::explain_permission)),
+                    )
                     .route(
                         "/{permission_uuid}",
                         web::get().to(view_permission::get_permission),
@@ -324,11 +569,56 @@ async fn main() -> std::io::Result<()> {
                         web::delete().to(delete_permission::delete_permission),
                     ),
             )
+            .service(
+                web::scope("/role")
+                    .wrap(AuthZ::new(auth_z_controller.clone()))
+                    .wrap(TokenAuth::new(user_id_from_token.clone(), true))
+                    .route("", web::get().to(role_list::get_list_of_roles))
+                    .route("", web::post().to(role_add::add))
+                    .route("/{role_uuid}", web::get().to(role_view::view))
+                    .route("/{role_uuid}", web::post().to(role_edit::edit_role))
+                    .route("/{role_uuid}", web::delete().to(role_delete::delete_role))
+                    .route(
+                        "/{role_uuid}/assignments",
+                        web::get().to(assignment::assignments),
+                    )
+                    .route(
+                        "/{role_uuid}/assignments",
+                        web::post().to(assignment::assign),
+                    )
+                    .route(
+                        "/{role_uuid}/assignments/{subject_uuid}",
+                        web::delete().to(assignment::unassign),
+                    ),
+            )
+            .service(
+                web::scope("/group")
+                    .wrap(AuthZ::new(auth_z_controller.clone()))
+                    .wrap(TokenAuth::new(user_id_from_token.clone(), true))
+                    .route("", web::get().to(group_list::get_list_of_groups))
+                    .route("", web::post().to(group_add::add))
+                    .route("/{group_uuid}", web::get().to(group_view::view))
+                    .route("/{group_uuid}", web::post().to(group_edit::edit_group))
+                    .route("/{group_uuid}", web::delete().to(group_delete::delete_group))
+                    .route("/{group_uuid}/members", web::get().to(member::members))
+                    .route("/{group_uuid}/members", web::post().to(member::add_member))
+                    .route(
+                        "/{group_uuid}/members/{account_uuid}",
+                        web::delete().to(member::remove_member),
+                    ),
+            )
             .service(
                 web::scope("/error")
                     .wrap(AuthZ::new(auth_z_controller.clone()))
                     .wrap(TokenAuth::new(user_id_from_token.clone(), true))
-                    .route("", web::get().to(errors_list)),
+                    .route("", web::get().to(errors_list))
+                    .route("/stats", web::get().to(errors_stats)),
+            )
+            .service(
+                web::scope("/email")
+                    .wrap(AuthZ::new(auth_z_controller.clone()))
+                    .wrap(TokenAuth::new(user_id_from_token.clone(), true))
+                    .route("", web::get().to(outbox_list)),
             )
             .service(
                 web::scope("/phrase")
@@ -343,8 +633,155 @@ async fn main() -> std::io::Result<()> {
                         web::delete().to(delete_phrase::delete_phrase),
                     ),
             )
-    })
-    .bind(("0.0.0.0", 8080))?
-    .run()
-    .await
+    });
+
+    let server = match config.server.workers {
+        Some(workers) => server.workers(workers),
+        None => server,
+    };
+
+    server.bind(config.server.bind)?.run().await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{run_command, DbPool};
+    use crate::{test_db, token_checker::UserIdFromToken};
+    use actix_web::http::{header::HeaderMap, Uri};
+    use auth_n::middleware::TokenChecker;
+    use diesel::prelude::*;
+    use std::sync::Mutex;
+
+    /// The seed of a fresh database is not safe to run concurrently
+    static SEED_LOCK: Mutex<()> = Mutex::new(());
+
+    /// Seeded accounts, admin permissions, sample mushafs and their ayahs
+    fn seeded_counts(pool: &DbPool) -> (i64, i64, i64, i64) {
+        use crate::schema::app_accounts::dsl::{app_accounts, id, username};
+        use crate::schema::app_permissions::dsl::{account_id, app_permissions};
+        use crate::schema::quran_ayahs::dsl::quran_ayahs;
+        use crate::schema::quran_mushafs::dsl::{quran_mushafs, short_name};
+        use crate::schema::quran_surahs::dsl::quran_surahs;
+
+        let mut conn = pool.get().unwrap();
+
+        let admin_id: i32 = app_accounts
+            .filter(username.eq("admin"))
+            .select(id)
+            .get_result(&mut conn)
+            .unwrap();
+
+        (
+            app_accounts
+                .filter(username.eq_any(["admin", "user"]))
+                .count()
+                .get_result(&mut conn)
+                .unwrap(),
+            app_permissions
+                .filter(account_id.eq(admin_id))
+                .count()
+                .get_result(&mut conn)
+                .unwrap(),
+            quran_mushafs
+                .filter(short_name.eq("hafs"))
+                .count()
+                .get_result(&mut conn)
+                .unwrap(),
+            quran_ayahs
+                .inner_join(quran_surahs.inner_join(quran_mushafs))
+                .filter(short_name.eq("hafs"))
+                .count()
+                .get_result(&mut conn)
+                .unwrap(),
+        )
+    }
+
+    /// Runs the seed command and returns the printed tokens by username
+    fn seed(pool: &DbPool) -> Vec<(String, String)> {
+        let mut out = vec![];
+
+        {
+            let _lock = SEED_LOCK.lock().unwrap();
+
+            run_command("seed", pool, &mut out).unwrap();
+        }
+
+        String::from_utf8(out)
+            .unwrap()
+            .lines()
+            .map(|line| {
+                let (account, token) = line.split_once(" token: ").unwrap();
+                let (username, _email) = account.split_once(' ').unwrap();
+
+                (username.to_string(), token.to_string())
+            })
+            .collect()
+    }
+
+    #[test]
+    #[ignore = "needs TEST_DATABASE_URL"]
+    fn test_seed_twice() {
+        let pool = test_db::pool();
+
+        crate::error::load_predefined_errors();
+
+        let first = seed(&pool);
+        let counts = seeded_counts(&pool);
+
+        let second = seed(&pool);
+
+        assert_eq!(seeded_counts(&pool), counts);
+        assert_eq!(counts.0, 2);
+        assert_eq!(counts.1, 13 * 4 + 2);
+        assert_eq!(counts.2, 1);
+        assert_eq!(counts.3, 7);
+
+        let usernames: Vec<&str> = second.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(usernames, ["admin", "user"]);
+        assert_ne!(first, second);
+    }
+
+    #[actix_web::test]
+    #[ignore = "needs TEST_DATABASE_URL"]
+    async fn test_seed_admin_token() {
+        use crate::schema::app_accounts::dsl::{app_accounts, id, username};
+
+        let pool = test_db::pool();
+
+        crate::error::load_predefined_errors();
+
+        let tokens = seed(&pool);
+        let (_, admin_token) = tokens.iter().find(|(name, _)| name == "admin").unwrap();
+
+        let admin_id: i32 = app_accounts
+            .filter(username.eq("admin"))
+            .select(id)
+            .get_result(&mut pool.get().unwrap())
+            .unwrap();
+
+        let checker = UserIdFromToken::new(pool, None);
+
+        let user_id = checker
+            .get_user_id(
+                "127.0.0.1:8080".parse().unwrap(),
+                HeaderMap::new(),
+                Uri::from_static("/profile"),
+                admin_token,
+            )
+            .await
+            .ok();
+
+        assert_eq!(user_id, Some(admin_id as u32));
+
+        let invalid = checker
+            .get_user_id(
+                "127.0.0.1:8080".parse().unwrap(),
+                HeaderMap::new(),
+                Uri::from_static("/profile"),
+                "not-a-seeded-token",
+            )
+            .await;
+
+        assert!(invalid.is_err());
+    }
 }
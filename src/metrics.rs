@@ -0,0 +1,311 @@
+use crate::{authz::Action, error_log, DbPool};
+use actix_web::{
+    dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform},
+    http::{header::ContentType, Method},
+    web, Error, HttpResponse,
+};
+use auth_z::DeclaredRoutes;
+use prometheus::{
+    Encoder, HistogramOpts, HistogramVec, IntCounter, IntCounterVec, IntGauge, IntGaugeVec, Opts,
+    Registry, TextEncoder,
+};
+use std::{
+    future::{ready, Future, Ready},
+    pin::Pin,
+    rc::Rc,
+    sync::OnceLock,
+    time::Instant,
+};
+
+/// Controller of the requests that don't match any route
+const UNMATCHED: &str = "unmatched";
+
+pub struct Metrics {
+    registry: Registry,
+
+    pub http_requests: IntCounterVec,
+    pub http_request_duration: HistogramVec,
+    pub authz_denials: IntCounter,
+    pub verification_codes_queued: IntCounter,
+    pub emails_sent: IntCounter,
+    pub email_send_failures: IntCounter,
+
+    db_pool_connections: IntGaugeVec,
+    error_logs: IntGaugeVec,
+}
+
+impl Metrics {
+    fn new() -> prometheus::Result<Self> {
+        let registry = Registry::new_custom(Some("nq_api".to_string()), None)?;
+
+        let http_requests = IntCounterVec::new(
+            Opts::new("http_requests_total", "Handled requests"),
+            &["controller", "action", "status"],
+        )?;
+
+        let http_request_duration = HistogramVec::new(
+            HistogramOpts::new("http_request_duration_seconds", "Latency of the requests"),
+            &["controller", "action"],
+        )?;
+
+        let authz_denials = IntCounter::new("authz_denials_total", "Requests denied by AuthZ")?;
+
+        let verification_codes_queued = IntCounter::new(
+            "verification_codes_queued_total",
+            "Verification codes queued to be emailed",
+        )?;
+
+        let emails_sent = IntCounter::new("emails_sent_total", "Emails delivered by the outbox")?;
+
+        let email_send_failures =
+            IntCounter::new("email_send_failures_total", "Failed email send attempts")?;
+
+        let db_pool_connections = IntGaugeVec::new(
+            Opts::new("db_pool_connections", "Connections of the database pool"),
+            &["state"],
+        )?;
+
+        let error_logs = IntGaugeVec::new(
+            Opts::new("error_logs", "Error logger counters since the start"),
+            &["state"],
+        )?;
+
+        registry.register(Box::new(http_requests.clone()))?;
+        registry.register(Box::new(http_request_duration.clone()))?;
+        registry.register(Box::new(authz_denials.clone()))?;
+        registry.register(Box::new(verification_codes_queued.clone()))?;
+        registry.register(Box::new(emails_sent.clone()))?;
+        registry.register(Box::new(email_send_failures.clone()))?;
+        registry.register(Box::new(db_pool_connections.clone()))?;
+        registry.register(Box::new(error_logs.clone()))?;
+
+        Ok(Self {
+            registry,
+            http_requests,
+            http_request_duration,
+            authz_denials,
+            verification_codes_queued,
+            emails_sent,
+            email_send_failures,
+            db_pool_connections,
+            error_logs,
+        })
+    }
+
+    /// Gauges are read when they are scraped
+    fn update_gauges(&self, pool: &DbPool) {
+        let state = pool.state();
+
+        let gauge =
+            |vec: &IntGaugeVec, label: &str| -> IntGauge { vec.with_label_values(&[label]) };
+
+        gauge(&self.db_pool_connections, "max").set(pool.max_size() as i64);
+        gauge(&self.db_pool_connections, "open").set(state.connections as i64);
+        gauge(&self.db_pool_connections, "idle").set(state.idle_connections as i64);
+        gauge(&self.db_pool_connections, "in_use")
+            .set(state.connections.saturating_sub(state.idle_connections) as i64);
+
+        let logs = error_log::metrics();
+
+        gauge(&self.error_logs, "queued").set(logs.queued as i64);
+        gauge(&self.error_logs, "written").set(logs.written as i64);
+        gauge(&self.error_logs, "dropped").set(logs.dropped as i64);
+        gauge(&self.error_logs, "fallback").set(logs.fallback as i64);
+        gauge(&self.error_logs, "pending").set(logs.pending() as i64);
+    }
+}
+
+/// Metrics of this process
+pub fn metrics() -> &'static Metrics {
+    static METRICS: OnceLock<Metrics> = OnceLock::new();
+
+    METRICS.get_or_init(|| Metrics::new().expect("Can't register the metrics"))
+}
+
+/// Controller and AuthZ action of the matched route
+///
+/// The route pattern is used, so ids don't make new labels.
+/// The declared action is used first, like the AuthZ of the route
+fn route_labels(
+    routes: &DeclaredRoutes,
+    pattern: Option<&str>,
+    method: &Method,
+) -> (String, String) {
+    let Some(pattern) = pattern else {
+        return (UNMATCHED.to_string(), String::new());
+    };
+
+    let route = routes.resolve(method, pattern);
+
+    let action = route.action.unwrap_or_else(|| {
+        let action: &str = Action::from_auth_z(&route.path, method.as_str()).into();
+
+        action.to_string()
+    });
+
+    (route.path.controller.unwrap_or_default(), action)
+}
+
+/// Counts the requests and observes their latency
+#[derive(Clone)]
+pub struct RequestMetrics {
+    routes: Rc<DeclaredRoutes>,
+}
+
+impl RequestMetrics {
+    /// The action label of the declared routes is their declared action
+    pub fn new(routes: DeclaredRoutes) -> Self {
+        Self {
+            routes: Rc::new(routes),
+        }
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for RequestMetrics
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Transform = RequestMetricsMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(RequestMetricsMiddleware {
+            service: Rc::new(service),
+            routes: Rc::clone(&self.routes),
+        }))
+    }
+}
+
+pub struct RequestMetricsMiddleware<S> {
+    service: Rc<S>,
+    routes: Rc<DeclaredRoutes>,
+}
+
+impl<S, B> Service<ServiceRequest> for RequestMetricsMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let service = Rc::clone(&self.service);
+        let routes = Rc::clone(&self.routes);
+        let method = req.method().clone();
+        let path = req.path().to_string();
+        let started_at = Instant::now();
+
+        Box::pin(async move {
+            let result = service.call(req).await;
+
+            // Middleware errors (token and permission) don't have the matched
+            // route, but their path is under a route scope
+            let (pattern, status) = match &result {
+                Ok(res) => (res.request().match_pattern(), res.status()),
+                Err(err) => (Some(path), err.as_response_error().status_code()),
+            };
+
+            let (controller, action) = route_labels(&routes, pattern.as_deref(), &method);
+            let metrics = metrics();
+
+            metrics
+                .http_requests
+                .with_label_values(&[&controller, &action, status.as_str()])
+                .inc();
+            metrics
+                .http_request_duration
+                .with_label_values(&[&controller, &action])
+                .observe(started_at.elapsed().as_secs_f64());
+
+            result
+        })
+    }
+}
+
+/// Returns the metrics in the Prometheus text format
+pub async fn export(pool: web::Data<DbPool>) -> HttpResponse {
+    let metrics = metrics();
+
+    metrics.update_gauges(&pool);
+
+    let mut buffer = vec![];
+
+    if let Err(err) = TextEncoder::new().encode(&metrics.registry.gather(), &mut buffer) {
+        return HttpResponse::InternalServerError().body(err.to_string());
+    }
+
+    HttpResponse::Ok()
+        .insert_header(ContentType::plaintext())
+        .body(buffer)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{metrics, route_labels};
+    use crate::route_metadata::declared_routes;
+    use actix_web::http::Method;
+
+    #[test]
+    fn test_route_labels() {
+        let routes = declared_routes();
+
+        assert_eq!(
+            route_labels(&routes, Some("/organization/{uuid}"), &Method::GET),
+            ("organization".to_string(), "view".to_string())
+        );
+        assert_eq!(
+            route_labels(
+                &routes,
+                Some("/organization/{account_uuid}/employees"),
+                &Method::POST
+            ),
+            ("organization".to_string(), "manage_employees".to_string())
+        );
+        assert_eq!(
+            route_labels(
+                &routes,
+                Some("/organization/{account_uuid}/transfer"),
+                &Method::POST
+            ),
+            ("organization".to_string(), "transfer".to_string())
+        );
+        assert_eq!(
+            route_labels(
+                &routes,
+                Some("/translation/text/{translation_uuid}"),
+                &Method::DELETE
+            ),
+            ("translation".to_string(), "edit".to_string())
+        );
+        assert_eq!(
+            route_labels(&routes, None, &Method::GET),
+            ("unmatched".to_string(), String::new())
+        );
+    }
+
+    #[test]
+    fn test_metrics_names() {
+        let metrics = metrics();
+
+        metrics.authz_denials.inc();
+
+        let names: Vec<String> = metrics
+            .registry
+            .gather()
+            .iter()
+            .map(|family| family.get_name().to_string())
+            .collect();
+
+        assert!(names.contains(&"nq_api_authz_denials_total".to_string()));
+    }
+}
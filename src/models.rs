@@ -151,6 +151,85 @@ pub struct NewEmail<'a> {
     pub deleted: bool,
 }
 
+#[derive(Identifiable, Queryable, Selectable, Debug, Clone, Serialize)]
+#[diesel(table_name = app_email_outbox)]
+pub struct OutboxEmail {
+    #[serde(skip_serializing)]
+    pub id: i32,
+
+    pub uuid: Uuid,
+    pub recipient: String,
+
+    // The content may have secrets, like the verification code
+    #[serde(skip_serializing)]
+    pub subject: String,
+    #[serde(skip_serializing)]
+    pub text_body: String,
+    #[serde(skip_serializing)]
+    pub html_body: String,
+
+    pub status: String,
+    pub attempts: i32,
+    pub last_error: Option<String>,
+    pub next_attempt_at: NaiveDateTime,
+    pub sent_at: Option<NaiveDateTime>,
+    pub created_at: NaiveDateTime,
+
+    #[serde(skip_serializing)]
+    pub updated_at: NaiveDateTime,
+
+    pub expires_at: Option<NaiveDateTime>,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = app_email_outbox)]
+pub struct NewOutboxEmail<'a> {
+    pub recipient: &'a str,
+    pub subject: &'a str,
+    pub text_body: &'a str,
+    pub html_body: &'a str,
+    pub expires_at: Option<NaiveDateTime>,
+}
+
+#[derive(Identifiable, Queryable, Debug, Clone)]
+#[diesel(table_name = app_oidc_sessions)]
+pub struct OidcSession {
+    pub id: i32,
+    pub state: String,
+    pub nonce: String,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+    pub expires_at: NaiveDateTime,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = app_oidc_sessions)]
+pub struct NewOidcSession<'a> {
+    pub state: &'a str,
+    pub nonce: &'a str,
+    pub expires_at: NaiveDateTime,
+}
+
+#[derive(Identifiable, Queryable, Associations, Debug, Clone)]
+#[diesel(belongs_to(Account))]
+#[diesel(table_name = app_oidc_identities)]
+pub struct OidcIdentity {
+    pub id: i32,
+    pub account_id: i32,
+    pub issuer: String,
+    pub subject: String,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = app_oidc_identities)]
+pub struct NewOidcIdentity<'a> {
+    pub account_id: i32,
+    pub issuer: &'a str,
+    pub subject: &'a str,
+}
+
 #[derive(Selectable, Identifiable, Associations, Queryable, PartialEq, Debug, Serialize, Clone)]
 #[diesel(belongs_to(Account, foreign_key = account_id))]
 #[diesel(table_name = app_organizations)]
@@ -220,6 +299,38 @@ pub struct NewEmployee {
     pub employee_account_id: i32,
 }
 
+#[derive(Identifiable, Queryable, Selectable, Debug, Clone, Serialize)]
+#[diesel(table_name = app_organization_invitations)]
+pub struct OrganizationInvitation {
+    #[serde(skip_serializing)]
+    pub id: i32,
+
+    pub uuid: Uuid,
+
+    #[serde(skip_serializing)]
+    pub org_account_id: i32,
+
+    #[serde(skip_serializing)]
+    pub creator_user_id: i32,
+
+    pub email: String,
+    pub expires_at: NaiveDateTime,
+    pub accepted_at: Option<NaiveDateTime>,
+    pub created_at: NaiveDateTime,
+
+    #[serde(skip_serializing)]
+    pub updated_at: NaiveDateTime,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = app_organization_invitations)]
+pub struct NewOrganizationInvitation<'a> {
+    pub org_account_id: i32,
+    pub creator_user_id: i32,
+    pub email: &'a str,
+    pub expires_at: NaiveDateTime,
+}
+
 #[derive(
     Selectable,
     Insertable,
@@ -374,7 +485,7 @@ pub struct Permission {
 
     pub creator_user_id: i32,
 
-    pub account_id: i32,
+    pub account_id: Option<i32>,
     pub object: String,
     pub action: String,
 
@@ -382,15 +493,99 @@ pub struct Permission {
     pub created_at: NaiveDateTime,
     #[serde(skip_serializing)]
     pub updated_at: NaiveDateTime,
+
+    pub role_id: Option<i32>,
+    pub group_id: Option<i32>,
+
+    /// How the conditions are combined, `and` or `or`
+    pub condition_operator: String,
+
+    /// `everyone` or `authenticated` if the permission is granted to them
+    pub pseudo_subject: Option<String>,
 }
 
 #[derive(Insertable)]
 #[diesel(table_name = app_permissions)]
 pub struct NewPermission<'a> {
     pub creator_user_id: i32,
-    pub account_id: i32,
+    pub account_id: Option<i32>,
+    pub role_id: Option<i32>,
+    pub group_id: Option<i32>,
     pub object: &'a String,
     pub action: &'a String,
+    pub condition_operator: &'a str,
+    pub pseudo_subject: Option<&'a str>,
+}
+
+#[derive(Identifiable, Queryable, Selectable, Debug, Clone, Serialize)]
+#[diesel(table_name = app_roles)]
+pub struct Role {
+    #[serde(skip_serializing)]
+    pub id: i32,
+    pub uuid: Uuid,
+
+    #[serde(skip_serializing)]
+    pub creator_user_id: i32,
+
+    pub name: String,
+    pub description: Option<String>,
+
+    #[serde(skip_serializing)]
+    pub created_at: NaiveDateTime,
+    #[serde(skip_serializing)]
+    pub updated_at: NaiveDateTime,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = app_roles)]
+pub struct NewRole<'a> {
+    pub creator_user_id: i32,
+    pub name: &'a String,
+    pub description: Option<&'a String>,
+}
+
+#[derive(Identifiable, Queryable, Selectable, Debug, Clone, Serialize)]
+#[diesel(table_name = app_groups)]
+pub struct Group {
+    #[serde(skip_serializing)]
+    pub id: i32,
+    pub uuid: Uuid,
+
+    #[serde(skip_serializing)]
+    pub creator_user_id: i32,
+
+    pub name: String,
+    pub description: Option<String>,
+
+    #[serde(skip_serializing)]
+    pub created_at: NaiveDateTime,
+    #[serde(skip_serializing)]
+    pub updated_at: NaiveDateTime,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = app_groups)]
+pub struct NewGroup<'a> {
+    pub creator_user_id: i32,
+    pub name: &'a String,
+    pub description: Option<&'a String>,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = app_group_members)]
+pub struct NewGroupMember {
+    pub creator_user_id: i32,
+    pub group_id: i32,
+    pub account_id: i32,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = app_role_assignments)]
+pub struct NewRoleAssignment {
+    pub creator_user_id: i32,
+    pub role_id: i32,
+    pub account_id: Option<i32>,
+    pub group_id: Option<i32>,
 }
 
 #[derive(
@@ -561,14 +756,15 @@ pub struct ErrorLog {
     pub updated_at: NaiveDateTime,
 }
 
-#[derive(Insertable)]
+/// Owned, so it can be queued for the error logger
+#[derive(Insertable, Debug)]
 #[diesel(table_name = app_error_logs)]
-pub struct NewErrorLog<'a> {
-    pub error_name: &'a String,
+pub struct NewErrorLog {
+    pub error_name: String,
     pub status_code: i32,
-    pub message: &'a String,
-    pub detail: Option<&'a String>,
-    pub request_user_agent: Option<&'a String>,
+    pub message: String,
+    pub detail: Option<String>,
+    pub request_user_agent: Option<String>,
     pub request_ipv4: IpNetwork,
     pub account_id: Option<i32>,
     pub request_token: Option<String>,
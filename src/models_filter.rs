@@ -1,4 +1,7 @@
-use crate::models::{ErrorLog, QuranAyah, QuranMushaf, QuranSurah, QuranWord, Translation};
+use crate::models::{
+    ErrorLog, OutboxEmail, QuranAyah, QuranMushaf, QuranSurah, QuranWord, Translation,
+};
+use crate::schema::app_email_outbox::BoxedQuery as OutboxBoxedQuery;
 use crate::schema::app_error_logs::BoxedQuery as AppErrorBoxedQuery;
 use crate::schema::quran_ayahs::BoxedQuery as AyahBoxedQuery;
 use crate::schema::quran_mushafs::BoxedQuery as MushafBoxedQuery;
@@ -320,3 +323,59 @@ impl Filter for ErrorLog {
         Ok(_query)
     }
 }
+
+impl Filter for OutboxEmail {
+    type Output = Result<OutboxBoxedQuery<'static, Pg>, RouterError>;
+
+    fn filter(filters: Box<dyn Filters>) -> Self::Output {
+        use crate::schema::app_email_outbox::dsl::*;
+
+        let mut _query = app_email_outbox.into_boxed();
+
+        _query = match filters.sort() {
+            Some(sort_str) => match sort_str.as_str() {
+                "createTime" => Ok(match filters.order().unwrap_or_default() {
+                    Order::Asc => app_email_outbox
+                        .order(created_at.asc())
+                        .internal_into_boxed(),
+                    Order::Desc => app_email_outbox
+                        .order(created_at.desc())
+                        .internal_into_boxed(),
+                }),
+
+                "updateTime" => Ok(match filters.order().unwrap_or_default() {
+                    Order::Asc => app_email_outbox
+                        .order(updated_at.asc())
+                        .internal_into_boxed(),
+                    Order::Desc => app_email_outbox
+                        .order(updated_at.desc())
+                        .internal_into_boxed(),
+                }),
+
+                "attempts" => Ok(match filters.order().unwrap_or_default() {
+                    Order::Asc => app_email_outbox.order(attempts.asc()).internal_into_boxed(),
+                    Order::Desc => app_email_outbox
+                        .order(attempts.desc())
+                        .internal_into_boxed(),
+                }),
+
+                _ => Err(RouterError::from_predefined(
+                    "FILTER_SORT_VALUE_NOT_DEFINED",
+                )),
+            },
+
+            None => Ok(app_email_outbox
+                .internal_into_boxed()
+                .order(updated_at.desc())),
+        }?;
+
+        _query = match filters.to() {
+            Some(limit) => _query
+                .limit(limit as i64)
+                .offset(filters.from().unwrap_or_default() as i64),
+            None => _query.offset(filters.from().unwrap_or_default() as i64),
+        };
+
+        Ok(_query)
+    }
+}
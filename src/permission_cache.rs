@@ -0,0 +1,160 @@
+use crate::pg_listener::{Listener, RECONNECT_INTERVAL};
+use diesel::{prelude::*, sql_query};
+use log::{error, info};
+use std::{
+    collections::HashMap,
+    sync::{Arc, RwLock},
+    thread,
+    time::{Duration, Instant},
+};
+
+/// Postgres channel that the permission changes are notified on
+pub const PERMISSIONS_CHANNEL: &str = "app_permissions_changed";
+
+/// Entries are loaded again after this
+const ENTRY_TTL: Duration = Duration::from_secs(300);
+
+/// The cache is cleared when it grows more than this
+const MAX_ENTRIES: usize = 10_000;
+
+/// Account (None for anonymous), object and action of the request
+pub type CacheKey = (Option<i32>, String, String);
+
+struct CacheEntry<V> {
+    value: V,
+    loaded_at: Instant,
+}
+
+/// In-process cache of the loaded permissions
+///
+/// Cleared when any of the replicas notifies a permission change
+#[derive(Clone)]
+pub struct PermissionCache<V> {
+    entries: Arc<RwLock<HashMap<CacheKey, CacheEntry<V>>>>,
+}
+
+impl<V> Default for PermissionCache<V> {
+    fn default() -> Self {
+        Self {
+            entries: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+}
+
+impl<V> PermissionCache<V> {
+    pub fn clear(&self) {
+        self.entries.write().unwrap().clear();
+    }
+}
+
+impl<V: Clone> PermissionCache<V> {
+    pub fn get(&self, key: &CacheKey) -> Option<V> {
+        let entries = self.entries.read().unwrap();
+
+        entries
+            .get(key)
+            .filter(|entry| entry.loaded_at.elapsed() < ENTRY_TTL)
+            .map(|entry| entry.value.clone())
+    }
+
+    pub fn insert(&self, key: CacheKey, value: V) {
+        let mut entries = self.entries.write().unwrap();
+
+        if entries.len() >= MAX_ENTRIES {
+            entries.clear();
+        }
+
+        entries.insert(
+            key,
+            CacheEntry {
+                value,
+                loaded_at: Instant::now(),
+            },
+        );
+    }
+}
+
+/// Notifies every replica (this one included) that permissions are changed
+///
+/// Inside a transaction, the notification is sent on commit
+pub fn notify_changed(conn: &mut PgConnection) -> QueryResult<()> {
+    sql_query(format!("NOTIFY {}", PERMISSIONS_CHANNEL)).execute(conn)?;
+
+    Ok(())
+}
+
+/// Starts the thread that clears the cache on permission changes
+///
+/// Notifications may be lost while the listener is disconnected,
+/// so the cache is cleared on every (re)connection too
+pub fn listen<V: Send + Sync + 'static>(cache: PermissionCache<V>, database_url: String) {
+    thread::spawn(move || loop {
+        match Listener::connect(&database_url, PERMISSIONS_CHANNEL) {
+            Ok(listener) => {
+                info!("Listening to {}", PERMISSIONS_CHANNEL);
+                cache.clear();
+
+                loop {
+                    match listener.wait() {
+                        Ok(0) => {}
+                        Ok(_) => cache.clear(),
+                        Err(err) => {
+                            error!("Permission listener: {}", err);
+                            break;
+                        }
+                    }
+                }
+            }
+
+            Err(err) => error!("Permission listener can't connect: {}", err),
+        }
+
+        thread::sleep(RECONNECT_INTERVAL);
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{PermissionCache, MAX_ENTRIES};
+
+    #[test]
+    fn test_permission_cache() {
+        let cache: PermissionCache<Vec<i32>> = PermissionCache::default();
+        let key = (Some(1), "translation".to_string(), "edit".to_string());
+
+        assert_eq!(cache.get(&key), None);
+
+        cache.insert(key.clone(), vec![1, 2]);
+        assert_eq!(cache.get(&key), Some(vec![1, 2]));
+        assert_eq!(
+            cache.get(&(None, "translation".to_string(), "edit".to_string())),
+            None
+        );
+
+        cache.clear();
+        assert_eq!(cache.get(&key), None);
+    }
+
+    #[test]
+    fn test_permission_cache_limit() {
+        let cache: PermissionCache<i32> = PermissionCache::default();
+
+        for account in 0..MAX_ENTRIES as i32 {
+            cache.insert(
+                (Some(account), "word".to_string(), "view".to_string()),
+                account,
+            );
+        }
+
+        cache.insert((None, "word".to_string(), "view".to_string()), -1);
+
+        assert_eq!(
+            cache.get(&(Some(0), "word".to_string(), "view".to_string())),
+            None
+        );
+        assert_eq!(
+            cache.get(&(None, "word".to_string(), "view".to_string())),
+            Some(-1)
+        );
+    }
+}
@@ -0,0 +1,92 @@
+use std::{
+    ffi::{CStr, CString},
+    time::Duration,
+};
+
+/// Wait this much before connecting the listener again
+pub const RECONNECT_INTERVAL: Duration = Duration::from_secs(5);
+
+/// The listener checks its connection at least with this interval
+const LISTEN_TIMEOUT_MILLIS: i32 = 30_000;
+
+/// Dedicated libpq connection that listens to the channel,
+/// diesel connections can't receive notifications
+pub struct Listener {
+    conn: *mut pq_sys::PGconn,
+}
+
+impl Listener {
+    pub fn connect(database_url: &str, channel: &str) -> Result<Self, String> {
+        let conninfo = CString::new(database_url).map_err(|err| err.to_string())?;
+
+        let listener = Self {
+            conn: unsafe { pq_sys::PQconnectdb(conninfo.as_ptr()) },
+        };
+
+        if unsafe { pq_sys::PQstatus(listener.conn) } != pq_sys::ConnStatusType::CONNECTION_OK {
+            return Err(listener.error_message());
+        }
+
+        let query = CString::new(format!("LISTEN {}", channel)).unwrap();
+
+        unsafe {
+            let result = pq_sys::PQexec(listener.conn, query.as_ptr());
+            let status = pq_sys::PQresultStatus(result);
+            pq_sys::PQclear(result);
+
+            if status != pq_sys::ExecStatusType::PGRES_COMMAND_OK {
+                return Err(listener.error_message());
+            }
+        }
+
+        Ok(listener)
+    }
+
+    fn error_message(&self) -> String {
+        unsafe { CStr::from_ptr(pq_sys::PQerrorMessage(self.conn)) }
+            .to_string_lossy()
+            .trim()
+            .to_string()
+    }
+
+    /// Blocks until notifications arrive or the timeout passes,
+    /// returns the count of received notifications
+    pub fn wait(&self) -> Result<usize, String> {
+        let mut poll_fd = libc::pollfd {
+            fd: unsafe { pq_sys::PQsocket(self.conn) },
+            events: libc::POLLIN,
+            revents: 0,
+        };
+
+        if poll_fd.fd < 0 {
+            return Err(self.error_message());
+        }
+
+        if unsafe { libc::poll(&mut poll_fd, 1, LISTEN_TIMEOUT_MILLIS) } < 0 {
+            return Err(std::io::Error::last_os_error().to_string());
+        }
+
+        if unsafe { pq_sys::PQconsumeInput(self.conn) } == 0 {
+            return Err(self.error_message());
+        }
+
+        let mut received = 0;
+
+        loop {
+            let notify = unsafe { pq_sys::PQnotifies(self.conn) };
+
+            if notify.is_null() {
+                return Ok(received);
+            }
+
+            unsafe { pq_sys::PQfreemem(notify.cast()) };
+            received += 1;
+        }
+    }
+}
+
+impl Drop for Listener {
+    fn drop(&mut self) {
+        unsafe { pq_sys::PQfinish(self.conn) };
+    }
+}
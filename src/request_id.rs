@@ -0,0 +1,185 @@
+use actix_web::{
+    dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform},
+    error::InternalError,
+    http::header::{HeaderName, HeaderValue},
+    Error,
+};
+use std::{
+    future::{ready, Future, Ready},
+    pin::Pin,
+    rc::Rc,
+};
+use uuid::Builder;
+
+/// Header of the request id, in both the request and the response
+pub const REQUEST_ID_HEADER: HeaderName = HeaderName::from_static("x-request-id");
+
+/// Max length of the request ids that are accepted from the clients
+const MAX_REQUEST_ID_LENGTH: usize = 128;
+
+/// Request ids of the clients (or the proxies) are kept if they are
+/// short and only have `[A-Za-z0-9-_.:]`
+fn valid_request_id(id: &str) -> bool {
+    !id.is_empty()
+        && id.len() <= MAX_REQUEST_ID_LENGTH
+        && id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.' | ':'))
+}
+
+fn new_request_id() -> String {
+    Builder::from_random_bytes(rand::random())
+        .into_uuid()
+        .to_string()
+}
+
+/// Assigns the X-Request-Id of the request, or accepts the one it has
+///
+/// The id is set on the request headers so the error logs can find it,
+/// and returned in the response headers
+#[derive(Clone, Default)]
+pub struct RequestId;
+
+impl<S, B> Transform<S, ServiceRequest> for RequestId
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Transform = RequestIdMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(RequestIdMiddleware {
+            service: Rc::new(service),
+        }))
+    }
+}
+
+pub struct RequestIdMiddleware<S> {
+    service: Rc<S>,
+}
+
+impl<S, B> Service<ServiceRequest> for RequestIdMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    forward_ready!(service);
+
+    fn call(&self, mut req: ServiceRequest) -> Self::Future {
+        let service = Rc::clone(&self.service);
+
+        let request_id = req
+            .headers()
+            .get(REQUEST_ID_HEADER)
+            .and_then(|value| value.to_str().ok())
+            .filter(|id| valid_request_id(id))
+            .map(String::from)
+            .unwrap_or_else(new_request_id);
+
+        // Only has the valid characters
+        let request_id = HeaderValue::from_str(&request_id).unwrap();
+
+        req.headers_mut()
+            .insert(REQUEST_ID_HEADER, request_id.clone());
+
+        Box::pin(async move {
+            match service.call(req).await {
+                Ok(mut res) => {
+                    res.headers_mut().insert(REQUEST_ID_HEADER, request_id);
+
+                    Ok(res)
+                }
+
+                // Middleware errors are responded after this
+                Err(err) => {
+                    let mut response = err.error_response();
+                    response.headers_mut().insert(REQUEST_ID_HEADER, request_id);
+
+                    Err(InternalError::from_response(err, response).into())
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{valid_request_id, RequestId, REQUEST_ID_HEADER};
+    use crate::{
+        error::{load_predefined_errors, RouterError},
+        error_language::LocalizeErrors,
+    };
+    use actix_web::{
+        test::{call_service, init_service, read_body_json, TestRequest},
+        web, App,
+    };
+    use diesel::r2d2::{ConnectionManager, Pool};
+
+    #[test]
+    fn test_valid_request_id() {
+        assert!(valid_request_id("6f1c2a3e-aa01-4b7e-9d3c-0e8a1b2c3d4e"));
+        assert!(valid_request_id("lb:1234.abc_DEF"));
+        assert!(!valid_request_id(""));
+        assert!(!valid_request_id("id with spaces"));
+        assert!(!valid_request_id(&"a".repeat(129)));
+    }
+
+    #[actix_web::test]
+    async fn test_request_id() {
+        load_predefined_errors();
+
+        let pool = Pool::builder().build_unchecked(ConnectionManager::new("postgres://localhost"));
+
+        let app = init_service(
+            App::new()
+                .wrap(LocalizeErrors::new(pool))
+                .wrap(RequestId)
+                .route(
+                    "/",
+                    web::get().to(|| async {
+                        Err::<String, _>(RouterError::from_predefined("NOT_FOUND"))
+                    }),
+                ),
+        )
+        .await;
+
+        let req = TestRequest::get()
+            .uri("/")
+            .insert_header(("X-Request-Id", "support-1234"))
+            .insert_header(("Accept-Language", "en"))
+            .to_request();
+
+        let res = call_service(&app, req).await;
+        assert_eq!(
+            res.headers().get(REQUEST_ID_HEADER).unwrap(),
+            "support-1234"
+        );
+
+        let body: serde_json::Value = read_body_json(res).await;
+        assert_eq!(body["request_id"], "support-1234");
+
+        // Not valid ids are replaced
+        let req = TestRequest::get()
+            .uri("/")
+            .insert_header(("X-Request-Id", "not valid"))
+            .insert_header(("Accept-Language", "en"))
+            .to_request();
+
+        let res = call_service(&app, req).await;
+        let request_id = res.headers().get(REQUEST_ID_HEADER).unwrap().clone();
+        assert_eq!(request_id.len(), 36);
+
+        let body: serde_json::Value = read_body_json(res).await;
+        assert_eq!(body["request_id"], request_id.to_str().unwrap());
+    }
+}
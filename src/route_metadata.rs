@@ -0,0 +1,111 @@
+use actix_web::http::Method;
+use auth_z::{DeclaredRoutes, ResourceId, RouteMetadata};
+
+/// AuthZ metadata of the routes whose paths don't follow `/{object}/{action}/{id}`
+///
+/// Routes are wrapped with these and the permission explain
+/// endpoint evaluates against them
+pub fn declared_routes() -> DeclaredRoutes {
+    let organization = || {
+        RouteMetadata::default()
+            .object("organization")
+            .resource_id(ResourceId::Path("account_uuid"))
+    };
+
+    DeclaredRoutes::default()
+        // Texts are part of the translation, changing them edits it
+        .route(
+            "/translation/text/{translation_uuid}",
+            RouteMetadata::default()
+                .object("translation")
+                .action(Method::POST, "edit")
+                .action(Method::DELETE, "edit")
+                .resource_id(ResourceId::Path("translation_uuid")),
+        )
+        // Names are part of the organization, changing them edits it
+        .route(
+            "/organization/name",
+            RouteMetadata::default()
+                .object("organization")
+                .action(Method::POST, "edit")
+                .resource_id(ResourceId::Nothing),
+        )
+        // The uuid is of the name, it's checked as its organization
+        .route(
+            "/organization/name/{uuid}",
+            RouteMetadata::default()
+                .object("organization")
+                .action(Method::POST, "edit")
+                .action(Method::DELETE, "edit")
+                .resource_id(ResourceId::Path("uuid"))
+                .model("organization_name"),
+        )
+        // The uuid is of the organization
+        .route(
+            "/organization/name/{uuid}",
+            RouteMetadata::default()
+                .object("organization")
+                .action(Method::GET, "view")
+                .resource_id(ResourceId::Path("uuid")),
+        )
+        // Staff and ownership have their own actions,
+        // editing the organization is not enough for them
+        .route(
+            "/organization/{account_uuid}/employees",
+            organization()
+                .action(Method::GET, "view")
+                .action(Method::POST, "manage_employees"),
+        )
+        .route(
+            "/organization/{account_uuid}/employees/{employee_uuid}",
+            organization().action(Method::DELETE, "manage_employees"),
+        )
+        .route(
+            "/organization/{account_uuid}/invitations",
+            organization()
+                .action(Method::GET, "manage_employees")
+                .action(Method::POST, "manage_employees"),
+        )
+        .route(
+            "/organization/{account_uuid}/invitations/{invitation_uuid}",
+            organization().action(Method::DELETE, "manage_employees"),
+        )
+        .route(
+            "/organization/{account_uuid}/transfer",
+            organization().action(Method::POST, "transfer"),
+        )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::declared_routes;
+    use actix_web::http::Method;
+
+    #[test]
+    fn test_declared_routes() {
+        let routes = declared_routes();
+
+        let route = routes.resolve(&Method::DELETE, "/organization/name/1");
+        assert_eq!(route.object, Some("organization".to_string()));
+        assert_eq!(route.action, Some("edit".to_string()));
+        assert_eq!(route.model, Some("organization_name".to_string()));
+
+        let route = routes.resolve(&Method::GET, "/organization/name/1");
+        assert_eq!(route.action, Some("view".to_string()));
+        assert_eq!(route.model, None);
+
+        let route = routes.resolve(&Method::POST, "/organization/1/transfer");
+        assert_eq!(route.action, Some("transfer".to_string()));
+        assert_eq!(route.resource_id, Some(Some("1".to_string())));
+
+        let route = routes.resolve(&Method::DELETE, "/translation/text/1");
+        assert_eq!(route.object, Some("translation".to_string()));
+        assert_eq!(route.action, Some("edit".to_string()));
+
+        // Others are read from the path
+        assert_eq!(
+            routes.resolve(&Method::POST, "/organization/1").action,
+            None
+        );
+    }
+}
@@ -1,8 +1,14 @@
 pub mod logout;
+pub mod oidc;
 pub mod send_code;
 pub mod verify;
 
+use crate::error::RouterError;
+use crate::models::{Account, NewAccount, NewEmail, NewToken, NewUser, User};
+use auth_n::HashBuilder;
 use chrono::{offset::Utc, DateTime, Duration, NaiveDateTime};
+use diesel::prelude::*;
+use rand::Rng;
 
 // This constants will make code length
 // equal to 6
@@ -16,3 +22,89 @@ pub fn time_deference(past_time: NaiveDateTime) -> Duration {
 
     current_date.signed_duration_since(past_date_time)
 }
+
+/// Creates a new user account with the given (verified) email
+pub fn create_user_with_email(conn: &mut PgConnection, email: &String) -> QueryResult<User> {
+    use crate::schema::app_accounts::dsl::{app_accounts, username};
+    use crate::schema::app_emails::dsl::app_emails;
+    use crate::schema::app_users::dsl::app_users;
+
+    // Create a new account
+    let new_account = NewAccount {
+        username: &String::from(""),
+        account_type: &String::from("user"),
+    }
+    .insert_into(app_accounts)
+    .get_result::<Account>(conn)?;
+
+    let new_user = NewUser {
+        birthday: None,
+        account_id: new_account.id,
+        language: None,
+    }
+    .insert_into(app_users)
+    .get_result::<User>(conn)?;
+
+    NewEmail {
+        creator_user_id: new_user.id,
+        email,
+        account_id: new_account.id,
+        verified: true,
+        primary: false,
+        deleted: false,
+    }
+    .insert_into(app_emails)
+    .execute(conn)?;
+
+    // Update the account and set the user name to the
+    // u{the new account id}
+    diesel::update(&new_account)
+        .set(username.eq(format!("u{}", &new_account.id)))
+        .execute(conn)?;
+
+    Ok(new_user)
+}
+
+/// Generates a new token for the account and saves its hash
+///
+/// Returns the token itself, the caller must send it to the user
+pub fn generate_token(conn: &mut PgConnection, account_id: i32) -> Result<String, RouterError> {
+    use crate::schema::app_tokens::dsl::app_tokens;
+
+    // Some salts
+    let account_id_as_string = account_id.to_string();
+    let time_as_string = Utc::now().timestamp().to_string();
+    let mut random_bytes = rand::thread_rng().gen::<[u8; 32]>().to_vec();
+
+    // source buffer for token
+    let mut source = vec![];
+
+    // append salts to the source
+    source.append(&mut account_id_as_string.as_bytes().to_vec());
+    source.append(&mut random_bytes);
+    source.append(&mut time_as_string.as_bytes().to_vec());
+
+    let token = HashBuilder::default().set_source(&source).generate();
+
+    let Some(result) = token.get_result() else {
+        return Err(RouterError::from_predefined("CANT_GENERATE_TOKEN"));
+    };
+
+    // Hash the token itself
+    let result_bytes = result.as_bytes().to_vec();
+    let Some(token_hash) = token.set_source(&result_bytes).generate().get_result() else {
+        return Err(RouterError::from_predefined("CANT_GENERATE_TOKEN"));
+    };
+
+    let new_token = NewToken {
+        account_id,
+        token_hash: &token_hash,
+    };
+
+    // Save token to the Db
+    diesel::insert_into(app_tokens)
+        .values(&new_token)
+        .execute(conn)?;
+
+    Ok(result)
+}
@@ -0,0 +1,200 @@
+use super::{create_user_with_email, generate_token};
+use crate::error::{RouterError, RouterErrorDetailBuilder};
+use crate::models::{NewOidcIdentity, NewOidcSession, OidcSession};
+use crate::oidc::{generate_random_string, OidcClient};
+use crate::DbPool;
+use actix_web::{http::header, web, HttpRequest, HttpResponse};
+use chrono::{Duration, Utc};
+use diesel::prelude::*;
+use diesel::result::{DatabaseErrorKind, Error::DatabaseError};
+use log::error;
+use serde::Deserialize;
+
+/// Login session (state, nonce) is valid for 10 minutes
+const OIDC_SESSION_EXPIRE_SECONDS: i64 = 600;
+
+#[derive(Deserialize)]
+pub struct OidcCallbackQuery {
+    code: String,
+    state: String,
+}
+
+/// Redirects the user to the identity provider login page
+pub async fn oidc_login(
+    pool: web::Data<DbPool>,
+    oidc: web::Data<OidcClient>,
+    req: HttpRequest,
+) -> Result<HttpResponse, RouterError> {
+    use crate::schema::app_oidc_sessions::dsl::app_oidc_sessions;
+
+    let pool = pool.into_inner();
+    let error_detail = RouterErrorDetailBuilder::from_http_request(&req).build();
+
+    let state = generate_random_string();
+    let nonce = generate_random_string();
+
+    let url = match oidc.authorization_url(&state, &nonce).await {
+        Ok(url) => url,
+        Err(err) => {
+            error!("{}", err);
+
+            return Err(
+                RouterError::from_predefined("OIDC_PROVIDER_ERROR").log_to_db(pool, error_detail)
+            );
+        }
+    };
+
+    let pool_clone = pool.clone();
+    web::block(move || {
+        let mut conn = pool_clone.get().unwrap();
+
+        NewOidcSession {
+            state: &state,
+            nonce: &nonce,
+            expires_at: Utc::now().naive_utc() + Duration::seconds(OIDC_SESSION_EXPIRE_SECONDS),
+        }
+        .insert_into(app_oidc_sessions)
+        .execute(&mut conn)
+    })
+    .await
+    .unwrap()?;
+
+    Ok(HttpResponse::Found()
+        .insert_header((header::LOCATION, url))
+        .finish())
+}
+
+/// The identity provider redirects the user back here
+///
+/// Maps the external identity to an account and returns a new token,
+/// accounts are matched with the verified email if the identity
+/// is not linked to any account yet
+pub async fn oidc_callback(
+    pool: web::Data<DbPool>,
+    oidc: web::Data<OidcClient>,
+    web::Query(query): web::Query<OidcCallbackQuery>,
+    req: HttpRequest,
+) -> Result<String, RouterError> {
+    use crate::schema::app_oidc_sessions::dsl::{app_oidc_sessions, state};
+
+    let pool = pool.into_inner();
+    let error_detail = RouterErrorDetailBuilder::from_http_request(&req).build();
+
+    let pool_clone = pool.clone();
+    let error_detail_clone = error_detail.clone();
+    let request_state = query.state.clone();
+
+    // Find and consume the login session
+    let nonce: String = web::block(move || {
+        let mut conn = pool_clone.get().unwrap();
+
+        let session: Option<OidcSession> = app_oidc_sessions
+            .filter(state.eq(&request_state))
+            .first(&mut conn)
+            .optional()?;
+
+        let Some(session) = session else {
+            return Err(RouterError::from_predefined("OIDC_STATE_NOT_VALID")
+                .log_to_db(pool_clone, error_detail_clone));
+        };
+
+        // State can be used only once
+        diesel::delete(&session).execute(&mut conn)?;
+
+        if session.expires_at <= Utc::now().naive_utc() {
+            return Err(RouterError::from_predefined("OIDC_STATE_NOT_VALID")
+                .log_to_db(pool_clone, error_detail_clone));
+        }
+
+        Ok(session.nonce)
+    })
+    .await
+    .unwrap()?;
+
+    let claims = match oidc.exchange_code(&query.code, &nonce).await {
+        Ok(claims) => claims,
+        Err(err) => {
+            error!("{}", err);
+
+            return Err(
+                RouterError::from_predefined("OIDC_TOKEN_NOT_VALID").log_to_db(pool, error_detail)
+            );
+        }
+    };
+
+    web::block(move || {
+        use crate::schema::app_emails::dsl::{
+            account_id as email_account_id, app_emails, deleted, email, verified,
+        };
+        use crate::schema::app_oidc_identities::dsl::{
+            account_id as identity_account_id, app_oidc_identities, issuer, subject,
+        };
+
+        let mut conn = pool.get().unwrap();
+
+        let find_linked = |conn: &mut PgConnection| -> QueryResult<Option<i32>> {
+            app_oidc_identities
+                .filter(issuer.eq(&claims.iss))
+                .filter(subject.eq(&claims.sub))
+                .select(identity_account_id)
+                .first(conn)
+                .optional()
+        };
+
+        // Is this identity already linked to an account ?
+        let linked_account = find_linked(&mut conn)?;
+
+        let account = match linked_account {
+            Some(account) => account,
+
+            None => {
+                // We only trust the emails that provider verified
+                let Some(claims_email) = claims.email.filter(|_| claims.email_verified) else {
+                    return Err(RouterError::from_predefined("OIDC_EMAIL_NOT_VERIFIED")
+                        .log_to_db(pool, error_detail));
+                };
+
+                // The new account is never left without its identity
+                let linked = conn.transaction(|conn| {
+                    let existing_account: Option<i32> = app_emails
+                        .filter(email.eq(&claims_email))
+                        .filter(verified.eq(true))
+                        .filter(deleted.eq(false))
+                        .select(email_account_id)
+                        .first(conn)
+                        .optional()?;
+
+                    let account = match existing_account {
+                        Some(account) => account,
+                        None => create_user_with_email(conn, &claims_email)?.account_id,
+                    };
+
+                    NewOidcIdentity {
+                        account_id: account,
+                        issuer: &claims.iss,
+                        subject: &claims.sub,
+                    }
+                    .insert_into(app_oidc_identities)
+                    .execute(conn)?;
+
+                    Ok(account)
+                });
+
+                match linked {
+                    Ok(account) => account,
+
+                    // Another callback of the same user linked it first
+                    Err(err @ DatabaseError(DatabaseErrorKind::UniqueViolation, _)) => {
+                        find_linked(&mut conn)?.ok_or(err)?
+                    }
+
+                    Err(err) => return Err(err.into()),
+                }
+            }
+        };
+
+        generate_token(&mut conn, account).map_err(|err| err.log_to_db(pool, error_detail))
+    })
+    .await
+    .unwrap()
+}
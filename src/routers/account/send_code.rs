@@ -1,10 +1,14 @@
 use super::{time_deference, MAX_RANDOM_CODE, MIN_RANDOM_CODE};
-use crate::email::EmailManager;
+use crate::config::AccountConfig;
+use crate::email_outbox;
+use crate::email_template::EmailTemplate;
 use crate::error::{RouterError, RouterErrorDetailBuilder};
+use crate::metrics::metrics;
 use crate::models::{NewVerifyCode, VerifyCode};
 use crate::validate::validate;
 use crate::DbPool;
 use actix_web::{web, HttpRequest};
+use chrono::{Duration, Utc};
 use diesel::prelude::*;
 use rand::Rng;
 use serde::{Deserialize, Serialize};
@@ -16,12 +20,12 @@ pub fn generate_random_code(min: i32, max: i32) -> i32 {
 }
 
 enum SendCodeStatus {
-    /// This means we need to send code
-    /// to email
-    SendCode(String),
+    /// This means the code is created and
+    /// the email is queued in the outbox
+    Queued,
 
     /// This means we already sended code
-    /// in past 10 seconds
+    /// in the resend interval
     AlreadySent,
 }
 
@@ -33,13 +37,17 @@ pub struct SendCodeInfo {
 
 /// Data -> Email,
 /// Send Random generated code to user email
+///
+/// The email is sent by the outbox worker, so a slow or
+/// down smtp server doesn't fail the request
 pub async fn send_code(
     pool: web::Data<DbPool>,
-    emailer: web::Data<EmailManager>,
+    account_config: web::Data<AccountConfig>,
     info: web::Json<SendCodeInfo>,
     req: HttpRequest,
 ) -> Result<String, RouterError> {
     use crate::schema::app_verify_codes::dsl::*;
+    use crate::schema::{app_emails, app_users};
 
     validate(&info.0)?;
 
@@ -50,7 +58,7 @@ pub async fn send_code(
         .request_body(serde_json::to_string(&info.0).unwrap().as_bytes().to_vec())
         .build();
 
-    let send_status: Result<SendCodeStatus, RouterError> = web::block(move || {
+    web::block(move || {
         let random_code = generate_random_code(MIN_RANDOM_CODE, MAX_RANDOM_CODE);
         let mut conn = pool.get().unwrap();
 
@@ -66,48 +74,69 @@ pub async fn send_code(
             let diff = time_deference(last_sended_code[0].created_at);
 
             // Check if code not expired
-            if diff.num_seconds() < 10 {
+            if diff.num_seconds() < account_config.verify_code_resend_seconds {
                 return Ok(SendCodeStatus::AlreadySent);
             }
         }
 
-        // Create new code
-        let new_code = NewVerifyCode {
-            code: &random_code,
-            email: &info_copy.email,
-            status: &"notUsed".to_string(),
-        };
-
-        // Insert code to app_verify_code table
-        diesel::insert_into(app_verify_codes)
-            .values(&new_code)
-            .execute(&mut conn)?;
-
-        Ok(SendCodeStatus::SendCode(random_code.to_string()))
+        // Emails are sent with the user language
+        let user_language: Option<String> = app_users::dsl::app_users
+            .filter(
+                app_users::dsl::account_id.eq_any(
+                    app_emails::dsl::app_emails
+                        .filter(app_emails::dsl::email.eq(&info_copy.email))
+                        .filter(app_emails::dsl::deleted.eq(false))
+                        .select(app_emails::dsl::account_id),
+                ),
+            )
+            .select(app_users::dsl::language)
+            .first::<Option<String>>(&mut conn)
+            .optional()?
+            .flatten();
+
+        let email_message = EmailTemplate::Verification {
+            code: &random_code.to_string(),
+            expire_seconds: account_config.verify_code_expire_seconds,
+        }
+        .render(user_language.as_deref())?;
+
+        // The code and its email are saved together
+        conn.transaction::<_, diesel::result::Error, _>(|conn| {
+            // Create new code
+            let new_code = NewVerifyCode {
+                code: &random_code,
+                email: &info_copy.email,
+                status: &"notUsed".to_string(),
+            };
+
+            // Insert code to app_verify_code table
+            diesel::insert_into(app_verify_codes)
+                .values(&new_code)
+                .execute(conn)?;
+
+            // The email is useless when the code is expired
+            let code_expires_at = Utc::now().naive_utc()
+                + Duration::seconds(account_config.verify_code_expire_seconds);
+
+            email_outbox::enqueue(
+                conn,
+                &info_copy.email,
+                &email_message,
+                Some(code_expires_at),
+            )
+        })?;
+
+        Ok(SendCodeStatus::Queued)
     })
     .await
-    .unwrap();
-
-    if let Ok(send_status) = send_status {
-        match send_status {
-            SendCodeStatus::SendCode(new_code) => {
-                let result = emailer
-                    .send_email(
-                        &info.email,
-                        "Verification Code",
-                        format!("Code: {}", new_code),
-                    )
-                    .await;
-
-                match result {
-                    Ok(()) => Ok("Code sended".to_string()),
-                    Err(_error) => Err(RouterError::from_predefined("SEND_CODE_INTERNAL_ERROR")
-                        .log_to_db(pool_clone.into_inner(), error_detail)),
-                }
-            }
-            SendCodeStatus::AlreadySent => Ok("Already sent".to_string()),
+    .unwrap()
+    .map(|send_status| match send_status {
+        SendCodeStatus::Queued => {
+            metrics().verification_codes_queued.inc();
+
+            "Code sended".to_string()
         }
-    } else {
-        Err(send_status.err().unwrap())
-    }
+        SendCodeStatus::AlreadySent => "Already sent".to_string(),
+    })
+    .map_err(|err: RouterError| err.log_to_db(pool_clone.into_inner(), error_detail))
 }
@@ -0,0 +1,71 @@
+use crate::email_outbox::STATUS_FAILED;
+use crate::error::{RouterError, RouterErrorDetailBuilder};
+use crate::filter::{Filter, Filters, Order};
+use crate::models::OutboxEmail;
+use crate::DbPool;
+use actix_web::{web, HttpRequest};
+use diesel::prelude::*;
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+pub struct OutboxQuery {
+    /// Delivery status, default is failed
+    status: Option<String>,
+
+    sort: Option<String>,
+    order: Option<Order>,
+
+    from: Option<u64>,
+    to: Option<u64>,
+}
+
+impl Filters for OutboxQuery {
+    fn sort(&self) -> Option<String> {
+        self.sort.clone()
+    }
+
+    fn order(&self) -> Option<Order> {
+        self.order.clone()
+    }
+
+    fn from(&self) -> Option<u64> {
+        self.from
+    }
+
+    fn to(&self) -> Option<u64> {
+        self.to
+    }
+}
+
+/// List of the outbox emails with their delivery status,
+/// failed emails by default
+pub async fn outbox_list(
+    pool: web::Data<DbPool>,
+    web::Query(query): web::Query<OutboxQuery>,
+    req: HttpRequest,
+) -> Result<web::Json<Vec<OutboxEmail>>, RouterError> {
+    use crate::schema::app_email_outbox::dsl::status;
+
+    let pool = pool.into_inner();
+
+    let error_detail = RouterErrorDetailBuilder::from_http_request(&req).build();
+
+    web::block(move || {
+        let mut conn = pool.get().unwrap();
+
+        let requested_status = query.status.clone().unwrap_or(STATUS_FAILED.to_string());
+
+        let filtered_emails = match OutboxEmail::filter(Box::from(query)) {
+            Ok(filtred) => filtred,
+            Err(err) => return Err(err.log_to_db(pool, error_detail)),
+        };
+
+        let emails: Vec<OutboxEmail> = filtered_emails
+            .filter(status.eq(requested_status))
+            .get_results(&mut conn)?;
+
+        Ok(web::Json(emails))
+    })
+    .await
+    .unwrap()
+}
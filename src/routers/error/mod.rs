@@ -1,3 +1,5 @@
+pub mod stats;
+
 use crate::error::{RouterError, RouterErrorDetailBuilder};
 use crate::filter::{Filter, Filters, Order};
 use crate::models::ErrorLog;
@@ -13,6 +15,9 @@ pub struct ErrorLogQuery {
 
     from: Option<u64>,
     to: Option<u64>,
+
+    /// X-Request-Id that the errors are logged with
+    request_id: Option<String>,
 }
 
 impl Filters for ErrorLogQuery {
@@ -45,11 +50,19 @@ pub async fn errors_list(
     web::block(move || {
         let mut conn = pool.get().unwrap();
 
-        let filtered_logs = match ErrorLog::filter(Box::from(query)) {
+        let request_id = query.request_id.clone();
+
+        let mut filtered_logs = match ErrorLog::filter(Box::from(query)) {
             Ok(filtred) => filtred,
             Err(err) => return Err(err.log_to_db(pool, error_detail)),
         };
 
+        if let Some(id) = request_id {
+            use crate::schema::app_error_logs::dsl::request_id;
+
+            filtered_logs = filtered_logs.filter(request_id.eq(id));
+        }
+
         // Get the list of words from the database
         let errors_list: Vec<ErrorLog> = filtered_logs.get_results(&mut conn)?;
 
@@ -0,0 +1,89 @@
+use crate::error::RouterError;
+use crate::DbPool;
+use actix_web::web;
+use chrono::{DateTime, Duration, Utc};
+use diesel::prelude::*;
+use diesel::sql_query;
+use diesel::sql_types::{BigInt, Nullable, Text, Timestamptz, Varchar};
+use serde::{Deserialize, Serialize};
+
+/// Stats are for the last week if `since` is not set
+const DEFAULT_STATS_DAYS: i64 = 7;
+
+#[derive(Deserialize, Clone, Copy, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum StatsBucket {
+    Hour,
+    #[default]
+    Day,
+    Week,
+}
+
+impl StatsBucket {
+    /// Field of the postgres `date_trunc`
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::Hour => "hour",
+            Self::Day => "day",
+            Self::Week => "week",
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct ErrorStatsQuery {
+    bucket: Option<StatsBucket>,
+
+    since: Option<DateTime<Utc>>,
+    until: Option<DateTime<Utc>>,
+}
+
+#[derive(Serialize, QueryableByName)]
+pub struct ErrorStat {
+    #[diesel(sql_type = Varchar)]
+    error_name: String,
+
+    #[diesel(sql_type = Nullable<Text>)]
+    controller: Option<String>,
+
+    /// Start of the time bucket
+    #[diesel(sql_type = Timestamptz)]
+    bucket: DateTime<Utc>,
+
+    #[diesel(sql_type = BigInt)]
+    count: i64,
+}
+
+/// Returns the count of the errors grouped by
+/// error name, controller and time bucket
+pub async fn errors_stats(
+    pool: web::Data<DbPool>,
+    web::Query(query): web::Query<ErrorStatsQuery>,
+) -> Result<web::Json<Vec<ErrorStat>>, RouterError> {
+    let bucket = query.bucket.unwrap_or_default();
+    let until = query.until.unwrap_or_else(Utc::now);
+    let since = query
+        .since
+        .unwrap_or_else(|| until - Duration::days(DEFAULT_STATS_DAYS));
+
+    web::block(move || {
+        let mut conn = pool.get().unwrap();
+
+        let stats: Vec<ErrorStat> = sql_query(
+            "SELECT error_name, request_controller AS controller,
+                date_trunc($1, created_at) AS bucket, COUNT(*) AS count
+            FROM app_error_logs
+            WHERE created_at >= $2 AND created_at < $3
+            GROUP BY error_name, request_controller, bucket
+            ORDER BY bucket DESC, count DESC",
+        )
+        .bind::<Text, _>(bucket.as_str())
+        .bind::<Timestamptz, _>(since)
+        .bind::<Timestamptz, _>(until)
+        .load(&mut conn)?;
+
+        Ok(web::Json(stats))
+    })
+    .await
+    .unwrap()
+}
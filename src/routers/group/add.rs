@@ -0,0 +1,73 @@
+use actix_web::{
+    web::{self, ReqData},
+    HttpRequest,
+};
+use diesel::{dsl::exists, prelude::*, select};
+
+use crate::{
+    error::{RouterError, RouterErrorDetailBuilder},
+    models::NewGroup,
+    validate::validate,
+    DbPool,
+};
+
+use super::ReqGroup;
+
+/// Add a new Group
+///
+/// Permissions are granted to the group from /permission
+/// with the `group` subject_type
+pub async fn add(
+    pool: web::Data<DbPool>,
+    new_group: web::Json<ReqGroup>,
+    data: ReqData<u32>,
+    req: HttpRequest,
+) -> Result<&'static str, RouterError> {
+    use crate::schema::app_groups::dsl::{app_groups, name};
+    use crate::schema::app_users::dsl::{account_id as user_acc_id, app_users, id as user_id};
+
+    let new_group_info = new_group.into_inner();
+    let user_account_id = data.into_inner();
+
+    validate(&new_group_info)?;
+
+    let pool = pool.into_inner();
+
+    let error_detail = RouterErrorDetailBuilder::from_http_request(&req)
+        .request_body(
+            serde_json::to_string(&new_group_info)
+                .unwrap()
+                .as_bytes()
+                .to_vec(),
+        )
+        .build();
+
+    web::block(move || {
+        let mut conn = pool.get().unwrap();
+
+        let group_exists = select(exists(app_groups.filter(name.eq(&new_group_info.name))))
+            .get_result::<bool>(&mut conn)?;
+
+        if group_exists {
+            return Err(RouterError::from_predefined("GROUP_NAME_NOT_AVAILABLE")
+                .log_to_db(pool, error_detail));
+        }
+
+        let user: i32 = app_users
+            .filter(user_acc_id.eq(user_account_id as i32))
+            .select(user_id)
+            .get_result(&mut conn)?;
+
+        NewGroup {
+            creator_user_id: user,
+            name: &new_group_info.name,
+            description: new_group_info.description.as_ref(),
+        }
+        .insert_into(app_groups)
+        .execute(&mut conn)?;
+
+        Ok("Created")
+    })
+    .await
+    .unwrap()
+}
@@ -0,0 +1,33 @@
+use crate::error::RouterError;
+use crate::permission_cache;
+use crate::DbPool;
+use ::uuid::Uuid;
+use actix_web::web;
+use diesel::prelude::*;
+
+/// Delete's a single group
+///
+/// Group members, permissions and role assignments are removed with it
+pub async fn delete_group(
+    path: web::Path<Uuid>,
+    pool: web::Data<DbPool>,
+) -> Result<&'static str, RouterError> {
+    use crate::schema::app_groups::dsl::{app_groups, uuid as group_uuid};
+
+    let target_group_uuid = path.into_inner();
+
+    web::block(move || {
+        let mut conn = pool.get().unwrap();
+
+        conn.transaction::<_, diesel::result::Error, _>(|conn| {
+            diesel::delete(app_groups.filter(group_uuid.eq(target_group_uuid))).execute(conn)?;
+
+            // Cached permissions of every replica are cleared
+            permission_cache::notify_changed(conn)
+        })?;
+
+        Ok("Deleted")
+    })
+    .await
+    .unwrap()
+}
@@ -0,0 +1,69 @@
+use actix_web::{web, HttpRequest};
+use diesel::{dsl::exists, prelude::*, select};
+use uuid::Uuid;
+
+use crate::{
+    error::{RouterError, RouterErrorDetailBuilder},
+    validate::validate,
+    DbPool,
+};
+
+use super::ReqGroup;
+
+/// Edits the group
+pub async fn edit_group(
+    path: web::Path<Uuid>,
+    info: web::Json<ReqGroup>,
+    pool: web::Data<DbPool>,
+    req: HttpRequest,
+) -> Result<&'static str, RouterError> {
+    use crate::schema::app_groups::dsl::{app_groups, description, name, uuid as group_uuid};
+
+    let target_group_uuid = path.into_inner();
+    let new_group = info.into_inner();
+
+    validate(&new_group)?;
+
+    let pool = pool.into_inner();
+
+    let error_detail = RouterErrorDetailBuilder::from_http_request(&req)
+        .request_body(
+            serde_json::to_string(&new_group)
+                .unwrap()
+                .as_bytes()
+                .to_vec(),
+        )
+        .build();
+
+    web::block(move || {
+        let mut conn = pool.get().unwrap();
+
+        // Name must be unique between groups
+        let name_taken = select(exists(
+            app_groups
+                .filter(name.eq(&new_group.name))
+                .filter(group_uuid.ne(target_group_uuid)),
+        ))
+        .get_result::<bool>(&mut conn)?;
+
+        if name_taken {
+            return Err(RouterError::from_predefined("GROUP_NAME_NOT_AVAILABLE")
+                .log_to_db(pool, error_detail));
+        }
+
+        let updated = diesel::update(app_groups.filter(group_uuid.eq(target_group_uuid)))
+            .set((
+                name.eq(new_group.name),
+                description.eq(new_group.description),
+            ))
+            .execute(&mut conn)?;
+
+        if updated == 0 {
+            return Err(RouterError::from_predefined("NOT_FOUND"));
+        }
+
+        Ok("Updated")
+    })
+    .await
+    .unwrap()
+}
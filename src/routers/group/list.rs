@@ -0,0 +1,20 @@
+use crate::{error::RouterError, models::Group, DbPool};
+use actix_web::web;
+use diesel::prelude::*;
+
+/// Returns the list of groups
+pub async fn get_list_of_groups(
+    pool: web::Data<DbPool>,
+) -> Result<web::Json<Vec<Group>>, RouterError> {
+    use crate::schema::app_groups::dsl::{app_groups, name};
+
+    web::block(move || {
+        let mut conn = pool.get().unwrap();
+
+        let groups: Vec<Group> = app_groups.order(name.asc()).load(&mut conn)?;
+
+        Ok(web::Json(groups))
+    })
+    .await
+    .unwrap()
+}
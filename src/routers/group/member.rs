@@ -0,0 +1,185 @@
+use actix_web::{
+    web::{self, ReqData},
+    HttpRequest,
+};
+use diesel::{dsl::exists, prelude::*, select};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{
+    error::{RouterError, RouterErrorDetailBuilder},
+    models::NewGroupMember,
+    permission_cache, DbPool,
+};
+
+#[derive(Serialize, Deserialize)]
+pub struct GroupMember {
+    /// Account uuid
+    account: Uuid,
+}
+
+#[derive(Serialize)]
+pub struct GroupMemberAccount {
+    uuid: Uuid,
+    username: String,
+}
+
+/// Returns the member accounts of the group
+pub async fn members(
+    path: web::Path<Uuid>,
+    pool: web::Data<DbPool>,
+) -> Result<web::Json<Vec<GroupMemberAccount>>, RouterError> {
+    use crate::schema::app_accounts::dsl::{app_accounts, username, uuid as account_uuid};
+    use crate::schema::app_group_members::dsl::{app_group_members, group_id};
+    use crate::schema::app_groups::dsl::{app_groups, id as g_id, uuid as group_uuid};
+
+    let target_group_uuid = path.into_inner();
+
+    web::block(move || {
+        let mut conn = pool.get().unwrap();
+
+        let group: i32 = app_groups
+            .filter(group_uuid.eq(target_group_uuid))
+            .select(g_id)
+            .get_result(&mut conn)?;
+
+        let accounts: Vec<(Uuid, String)> = app_group_members
+            .filter(group_id.eq(group))
+            .inner_join(app_accounts)
+            .select((account_uuid, username))
+            .order(username.asc())
+            .load(&mut conn)?;
+
+        Ok(web::Json(
+            accounts
+                .into_iter()
+                .map(|(uuid, account_username)| GroupMemberAccount {
+                    uuid,
+                    username: account_username,
+                })
+                .collect(),
+        ))
+    })
+    .await
+    .unwrap()
+}
+
+/// Adds the account to the group
+pub async fn add_member(
+    path: web::Path<Uuid>,
+    info: web::Json<GroupMember>,
+    pool: web::Data<DbPool>,
+    data: ReqData<u32>,
+    req: HttpRequest,
+) -> Result<&'static str, RouterError> {
+    use crate::schema::app_accounts::dsl::{app_accounts, id as acc_id, uuid as account_uuid};
+    use crate::schema::app_group_members::dsl::{account_id, app_group_members, group_id};
+    use crate::schema::app_groups::dsl::{app_groups, id as g_id, uuid as group_uuid};
+    use crate::schema::app_users::dsl::{account_id as user_acc_id, app_users, id as user_id};
+
+    let target_group_uuid = path.into_inner();
+    let member = info.into_inner();
+    let user_account_id = data.into_inner();
+
+    let pool = pool.into_inner();
+
+    let error_detail = RouterErrorDetailBuilder::from_http_request(&req)
+        .request_body(serde_json::to_string(&member).unwrap().as_bytes().to_vec())
+        .build();
+
+    web::block(move || {
+        let mut conn = pool.get().unwrap();
+
+        let group: i32 = app_groups
+            .filter(group_uuid.eq(target_group_uuid))
+            .select(g_id)
+            .get_result(&mut conn)?;
+
+        let account: i32 = app_accounts
+            .filter(account_uuid.eq(member.account))
+            .select(acc_id)
+            .get_result(&mut conn)?;
+
+        let already_member = select(exists(
+            app_group_members
+                .filter(group_id.eq(group))
+                .filter(account_id.eq(account)),
+        ))
+        .get_result::<bool>(&mut conn)?;
+
+        if already_member {
+            return Err(RouterError::from_predefined("GROUP_MEMBER_ALREADY_EXISTS")
+                .log_to_db(pool, error_detail));
+        }
+
+        let user: i32 = app_users
+            .filter(user_acc_id.eq(user_account_id as i32))
+            .select(user_id)
+            .get_result(&mut conn)?;
+
+        conn.transaction::<_, diesel::result::Error, _>(|conn| {
+            NewGroupMember {
+                creator_user_id: user,
+                group_id: group,
+                account_id: account,
+            }
+            .insert_into(app_group_members)
+            .execute(conn)?;
+
+            // Cached permissions of every replica are cleared
+            permission_cache::notify_changed(conn)
+        })?;
+
+        Ok("Added")
+    })
+    .await
+    .unwrap()
+}
+
+/// Removes the account from the group
+pub async fn remove_member(
+    path: web::Path<(Uuid, Uuid)>,
+    pool: web::Data<DbPool>,
+) -> Result<&'static str, RouterError> {
+    use crate::schema::app_accounts::dsl::{app_accounts, id as acc_id, uuid as account_uuid};
+    use crate::schema::app_group_members::dsl::{account_id, app_group_members, group_id};
+    use crate::schema::app_groups::dsl::{app_groups, id as g_id, uuid as group_uuid};
+
+    let (target_group_uuid, target_account_uuid) = path.into_inner();
+
+    web::block(move || {
+        let mut conn = pool.get().unwrap();
+
+        let group: i32 = app_groups
+            .filter(group_uuid.eq(target_group_uuid))
+            .select(g_id)
+            .get_result(&mut conn)?;
+
+        let account: i32 = app_accounts
+            .filter(account_uuid.eq(target_account_uuid))
+            .select(acc_id)
+            .get_result(&mut conn)?;
+
+        let deleted = conn.transaction::<_, diesel::result::Error, _>(|conn| {
+            let deleted = diesel::delete(
+                app_group_members
+                    .filter(group_id.eq(group))
+                    .filter(account_id.eq(account)),
+            )
+            .execute(conn)?;
+
+            // Cached permissions of every replica are cleared
+            permission_cache::notify_changed(conn)?;
+
+            Ok(deleted)
+        })?;
+
+        if deleted == 0 {
+            return Err(RouterError::from_predefined("NOT_FOUND"));
+        }
+
+        Ok("Removed")
+    })
+    .await
+    .unwrap()
+}
@@ -0,0 +1,51 @@
+use crate::{
+    error::RouterError,
+    models::{Group, Permission},
+    routers::permission::GrantedPermission,
+    DbPool,
+};
+use actix_web::web;
+use diesel::prelude::*;
+use serde::Serialize;
+use uuid::Uuid;
+
+#[derive(Serialize)]
+pub struct GroupWithPermissions {
+    #[serde(flatten)]
+    group: Group,
+    permissions: Vec<GrantedPermission>,
+}
+
+/// Returns the group with its permissions
+pub async fn view(
+    path: web::Path<Uuid>,
+    pool: web::Data<DbPool>,
+) -> Result<web::Json<GroupWithPermissions>, RouterError> {
+    use crate::schema::app_groups::dsl::{app_groups, uuid as group_uuid};
+    use crate::schema::app_permissions::dsl::{app_permissions, group_id};
+
+    let requested_group_uuid = path.into_inner();
+
+    web::block(move || {
+        let mut conn = pool.get().unwrap();
+
+        let group: Group = app_groups
+            .filter(group_uuid.eq(requested_group_uuid))
+            .get_result(&mut conn)?;
+
+        let permissions: Vec<Permission> = app_permissions
+            .filter(group_id.eq(group.id))
+            .select(Permission::as_select())
+            .load(&mut conn)?;
+
+        Ok(web::Json(GroupWithPermissions {
+            group,
+            permissions: permissions
+                .into_iter()
+                .map(GrantedPermission::from)
+                .collect(),
+        }))
+    })
+    .await
+    .unwrap()
+}
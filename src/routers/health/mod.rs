@@ -0,0 +1,124 @@
+use crate::email::EmailManager;
+use crate::{DbPool, MIGRATIONS};
+use actix_web::{web, HttpResponse};
+use diesel::prelude::*;
+use diesel::sql_query;
+use diesel_migrations::MigrationHarness;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+/// Probes are failed instead of waiting for a busy pool
+const DATABASE_TIMEOUT: Duration = Duration::from_secs(2);
+
+#[derive(Serialize)]
+pub struct Check {
+    ok: bool,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+impl<E: ToString> From<Result<(), E>> for Check {
+    fn from(value: Result<(), E>) -> Self {
+        Self {
+            ok: value.is_ok(),
+            error: value.err().map(|err| err.to_string()),
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct Readiness {
+    ready: bool,
+    checks: BTreeMap<&'static str, Check>,
+}
+
+impl Readiness {
+    fn new(checks: BTreeMap<&'static str, Check>) -> Self {
+        Self {
+            ready: checks.values().all(|check| check.ok),
+            checks,
+        }
+    }
+
+    /// 503 tells the orchestrator to not route the traffic here
+    fn response(&self) -> HttpResponse {
+        if self.ready {
+            HttpResponse::Ok().json(self)
+        } else {
+            HttpResponse::ServiceUnavailable().json(self)
+        }
+    }
+}
+
+/// The process is up, nothing else is checked
+pub async fn live() -> HttpResponse {
+    HttpResponse::Ok().json(serde_json::json!({ "live": true }))
+}
+
+/// Database is reachable, every migration is applied
+/// and the email transport is configured
+pub async fn ready(pool: web::Data<DbPool>, mailer: web::Data<EmailManager>) -> HttpResponse {
+    let (database, migrations) = web::block(move || {
+        let mut conn = match pool.get_timeout(DATABASE_TIMEOUT) {
+            Ok(conn) => conn,
+            Err(err) => {
+                let skipped = Err("Database is not reachable".to_string());
+
+                return (Err(err.to_string()), skipped);
+            }
+        };
+
+        let database = sql_query("SELECT 1")
+            .execute(&mut conn)
+            .map(|_| ())
+            .map_err(|err| err.to_string());
+
+        let migrations = match conn.pending_migrations(MIGRATIONS) {
+            Ok(pending) if pending.is_empty() => Ok(()),
+            Ok(pending) => Err(format!("{} migrations are pending", pending.len())),
+            Err(err) => Err(err.to_string()),
+        };
+
+        (database, migrations)
+    })
+    .await
+    .unwrap();
+
+    Readiness::new(BTreeMap::from([
+        ("database", Check::from(database)),
+        ("migrations", Check::from(migrations)),
+        ("email", Check::from(mailer.check().await)),
+    ]))
+    .response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Check, Readiness};
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn test_readiness() {
+        let ready = Readiness::new(BTreeMap::from([
+            ("database", Check::from(Ok::<(), String>(()))),
+            ("email", Check::from(Ok::<(), String>(()))),
+        ]));
+
+        assert!(ready.ready);
+        assert_eq!(ready.response().status(), 200);
+
+        let not_ready = Readiness::new(BTreeMap::from([
+            ("database", Check::from(Ok::<(), String>(()))),
+            ("migrations", Check::from(Err("2 migrations are pending"))),
+        ]));
+
+        assert!(!not_ready.ready);
+        assert_eq!(not_ready.response().status(), 503);
+        assert_eq!(
+            not_ready.checks["migrations"].error.as_deref(),
+            Some("2 migrations are pending")
+        );
+    }
+}
@@ -6,7 +6,11 @@ pub mod permission;
 pub mod translation;
 pub mod profile;
 pub mod error;
+pub mod email;
 pub mod phrase;
+pub mod role;
+pub mod group;
+pub mod health;
 
 use std::collections::BTreeMap;
 use std::hash::Hash;
@@ -0,0 +1,225 @@
+use actix_web::{
+    web::{self, ReqData},
+    HttpRequest,
+};
+use chrono::NaiveDateTime;
+use diesel::{dsl::exists, prelude::*, select};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{
+    error::{RouterError, RouterErrorDetailBuilder},
+    models::NewEmployee,
+    permission_cache, DbPool,
+};
+
+use super::organization_account_id;
+
+#[derive(Serialize, Deserialize)]
+pub struct ReqEmployee {
+    /// Account uuid
+    pub account: Uuid,
+}
+
+#[derive(Serialize)]
+pub struct OrganizationEmployee {
+    uuid: Uuid,
+    username: String,
+    is_owner: bool,
+    created_at: NaiveDateTime,
+}
+
+/// Returns the employees of the organization
+pub async fn employees(
+    path: web::Path<Uuid>,
+    pool: web::Data<DbPool>,
+) -> Result<web::Json<Vec<OrganizationEmployee>>, RouterError> {
+    use crate::schema::app_accounts::dsl::{
+        app_accounts, id as acc_id, username, uuid as account_uuid,
+    };
+    use crate::schema::app_employees::dsl::{
+        app_employees, created_at, employee_account_id, org_account_id,
+    };
+    use crate::schema::app_organizations::dsl::{
+        account_id as organization_account, app_organizations, owner_account_id,
+    };
+
+    let org_uuid = path.into_inner();
+
+    web::block(move || {
+        let mut conn = pool.get().unwrap();
+
+        let org = organization_account_id(&mut conn, org_uuid)?;
+
+        let owner: i32 = app_organizations
+            .filter(organization_account.eq(org))
+            .select(owner_account_id)
+            .get_result(&mut conn)?;
+
+        let employees: Vec<(i32, Uuid, String, NaiveDateTime)> = app_employees
+            .filter(org_account_id.eq(org))
+            .inner_join(app_accounts.on(acc_id.eq(employee_account_id)))
+            .select((acc_id, account_uuid, username, created_at))
+            .order(created_at.asc())
+            .load(&mut conn)?;
+
+        Ok(web::Json(
+            employees
+                .into_iter()
+                .map(
+                    |(account, uuid, account_username, employed_at)| OrganizationEmployee {
+                        uuid,
+                        username: account_username,
+                        is_owner: account == owner,
+                        created_at: employed_at,
+                    },
+                )
+                .collect(),
+        ))
+    })
+    .await
+    .unwrap()
+}
+
+/// Adds the account to the organization employees
+pub async fn add_employee(
+    path: web::Path<Uuid>,
+    info: web::Json<ReqEmployee>,
+    pool: web::Data<DbPool>,
+    data: ReqData<u32>,
+    req: HttpRequest,
+) -> Result<&'static str, RouterError> {
+    use crate::schema::app_accounts::dsl::{
+        account_type, app_accounts, id as acc_id, uuid as account_uuid,
+    };
+    use crate::schema::app_employees::dsl::{app_employees, employee_account_id, org_account_id};
+    use crate::schema::app_users::dsl::{account_id as user_acc_id, app_users, id as user_id};
+
+    let org_uuid = path.into_inner();
+    let employee = info.into_inner();
+    let user_account_id = data.into_inner();
+
+    let pool = pool.into_inner();
+
+    let error_detail = RouterErrorDetailBuilder::from_http_request(&req)
+        .request_body(
+            serde_json::to_string(&employee)
+                .unwrap()
+                .as_bytes()
+                .to_vec(),
+        )
+        .build();
+
+    web::block(move || {
+        let mut conn = pool.get().unwrap();
+
+        let org = organization_account_id(&mut conn, org_uuid)?;
+
+        // Only users can be employees
+        let account: i32 = app_accounts
+            .filter(account_uuid.eq(employee.account))
+            .filter(account_type.eq("user"))
+            .select(acc_id)
+            .get_result(&mut conn)?;
+
+        let already_employee = select(exists(
+            app_employees
+                .filter(org_account_id.eq(org))
+                .filter(employee_account_id.eq(account)),
+        ))
+        .get_result::<bool>(&mut conn)?;
+
+        if already_employee {
+            return Err(RouterError::from_predefined("EMPLOYEE_ALREADY_EXISTS")
+                .log_to_db(pool, error_detail));
+        }
+
+        let user: i32 = app_users
+            .filter(user_acc_id.eq(user_account_id as i32))
+            .select(user_id)
+            .get_result(&mut conn)?;
+
+        conn.transaction::<_, diesel::result::Error, _>(|conn| {
+            NewEmployee {
+                org_account_id: org,
+                creator_user_id: user,
+                employee_account_id: account,
+            }
+            .insert_into(app_employees)
+            .execute(conn)?;
+
+            // Cached permissions of every replica are cleared
+            permission_cache::notify_changed(conn)
+        })?;
+
+        Ok("Added")
+    })
+    .await
+    .unwrap()
+}
+
+/// Removes the account from the organization employees
+///
+/// The owner can't be removed, ownership must be transferred first
+pub async fn remove_employee(
+    path: web::Path<(Uuid, Uuid)>,
+    pool: web::Data<DbPool>,
+    req: HttpRequest,
+) -> Result<&'static str, RouterError> {
+    use crate::schema::app_accounts::dsl::{app_accounts, id as acc_id, uuid as account_uuid};
+    use crate::schema::app_employees::dsl::{app_employees, employee_account_id, org_account_id};
+    use crate::schema::app_organizations::dsl::{
+        account_id as organization_account, app_organizations, owner_account_id,
+    };
+
+    let (org_uuid, target_account_uuid) = path.into_inner();
+
+    let pool = pool.into_inner();
+
+    let error_detail = RouterErrorDetailBuilder::from_http_request(&req).build();
+
+    web::block(move || {
+        let mut conn = pool.get().unwrap();
+
+        let org = organization_account_id(&mut conn, org_uuid)?;
+
+        let account: i32 = app_accounts
+            .filter(account_uuid.eq(target_account_uuid))
+            .select(acc_id)
+            .get_result(&mut conn)?;
+
+        let owner: i32 = app_organizations
+            .filter(organization_account.eq(org))
+            .select(owner_account_id)
+            .get_result(&mut conn)?;
+
+        if account == owner {
+            return Err(
+                RouterError::from_predefined("ORGANIZATION_OWNER_NOT_REMOVABLE")
+                    .log_to_db(pool, error_detail),
+            );
+        }
+
+        let deleted = conn.transaction::<_, diesel::result::Error, _>(|conn| {
+            let deleted = diesel::delete(
+                app_employees
+                    .filter(org_account_id.eq(org))
+                    .filter(employee_account_id.eq(account)),
+            )
+            .execute(conn)?;
+
+            // Cached permissions of every replica are cleared
+            permission_cache::notify_changed(conn)?;
+
+            Ok(deleted)
+        })?;
+
+        if deleted == 0 {
+            return Err(RouterError::from_predefined("NOT_FOUND"));
+        }
+
+        Ok("Removed")
+    })
+    .await
+    .unwrap()
+}
@@ -0,0 +1,408 @@
+use actix_web::{
+    web::{self, ReqData},
+    HttpRequest,
+};
+use chrono::{Duration, NaiveDateTime, Utc};
+use diesel::{dsl::exists, prelude::*, select};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::{
+    email_outbox,
+    email_template::EmailTemplate,
+    error::{RouterError, RouterErrorDetailBuilder},
+    models::{NewEmployee, NewOrganizationInvitation, OrganizationInvitation},
+    permission_cache,
+    validate::validate,
+    DbPool,
+};
+
+use super::organization_account_id;
+
+/// Invitations can be accepted until this many days
+const INVITATION_EXPIRE_DAYS: i64 = 7;
+
+#[derive(Serialize, Deserialize, Validate)]
+pub struct ReqInvitation {
+    #[validate(email)]
+    pub email: String,
+}
+
+#[derive(Serialize)]
+pub struct AccountInvitation {
+    uuid: Uuid,
+
+    /// Organization username
+    organization: String,
+
+    expires_at: NaiveDateTime,
+    created_at: NaiveDateTime,
+}
+
+/// Returns the pending invitations of the organization
+pub async fn invitations(
+    path: web::Path<Uuid>,
+    pool: web::Data<DbPool>,
+) -> Result<web::Json<Vec<OrganizationInvitation>>, RouterError> {
+    use crate::schema::app_organization_invitations::dsl::{
+        accepted_at, app_organization_invitations, created_at, org_account_id,
+    };
+
+    let org_uuid = path.into_inner();
+
+    web::block(move || {
+        let mut conn = pool.get().unwrap();
+
+        let org = organization_account_id(&mut conn, org_uuid)?;
+
+        let pending: Vec<OrganizationInvitation> = app_organization_invitations
+            .filter(org_account_id.eq(org))
+            .filter(accepted_at.is_null())
+            .order(created_at.desc())
+            .load(&mut conn)?;
+
+        Ok(web::Json(pending))
+    })
+    .await
+    .unwrap()
+}
+
+/// Invites the email to the organization,
+/// the invitation is sent by email
+pub async fn invite(
+    path: web::Path<Uuid>,
+    info: web::Json<ReqInvitation>,
+    pool: web::Data<DbPool>,
+    data: ReqData<u32>,
+    req: HttpRequest,
+) -> Result<&'static str, RouterError> {
+    use crate::schema::app_accounts::dsl::{app_accounts, username};
+    use crate::schema::app_emails::dsl::{
+        account_id as email_account_id, app_emails, deleted, email as email_address,
+    };
+    use crate::schema::app_employees::dsl::{app_employees, employee_account_id, org_account_id};
+    use crate::schema::app_organization_invitations::dsl::{
+        accepted_at, app_organization_invitations, email as invitation_email, expires_at,
+        org_account_id as invitation_org,
+    };
+    use crate::schema::app_organization_names::dsl::{
+        account_id as name_account_id, app_organization_names, language as name_language, name,
+    };
+    use crate::schema::app_users::dsl::{
+        account_id as user_acc_id, app_users, id as user_id, language,
+    };
+
+    let org_uuid = path.into_inner();
+    let invitation = info.into_inner();
+    let user_account_id = data.into_inner();
+
+    validate(&invitation)?;
+
+    let pool = pool.into_inner();
+
+    let error_detail = RouterErrorDetailBuilder::from_http_request(&req)
+        .request_body(
+            serde_json::to_string(&invitation)
+                .unwrap()
+                .as_bytes()
+                .to_vec(),
+        )
+        .build();
+
+    web::block(move || {
+        let mut conn = pool.get().unwrap();
+
+        let org = organization_account_id(&mut conn, org_uuid)?;
+
+        // Accounts that own the invited email
+        let email_accounts = app_emails
+            .filter(email_address.eq(&invitation.email))
+            .filter(deleted.eq(false))
+            .select(email_account_id);
+
+        let already_employee = select(exists(
+            app_employees
+                .filter(org_account_id.eq(org))
+                .filter(employee_account_id.eq_any(email_accounts)),
+        ))
+        .get_result::<bool>(&mut conn)?;
+
+        if already_employee {
+            return Err(RouterError::from_predefined("EMPLOYEE_ALREADY_EXISTS")
+                .log_to_db(pool, error_detail));
+        }
+
+        let now = Utc::now().naive_utc();
+
+        let pending_invitations = app_organization_invitations
+            .filter(invitation_org.eq(org))
+            .filter(invitation_email.eq(&invitation.email))
+            .filter(accepted_at.is_null());
+
+        // Expired invitations can be sent again
+        diesel::delete(pending_invitations.filter(expires_at.le(now))).execute(&mut conn)?;
+
+        let already_invited = select(exists(pending_invitations)).get_result::<bool>(&mut conn)?;
+
+        if already_invited {
+            return Err(
+                RouterError::from_predefined("ORGANIZATION_INVITATION_ALREADY_EXISTS")
+                    .log_to_db(pool, error_detail),
+            );
+        }
+
+        let (user, inviter): (i32, String) = app_users
+            .inner_join(app_accounts)
+            .filter(user_acc_id.eq(user_account_id as i32))
+            .select((user_id, username))
+            .get_result(&mut conn)?;
+
+        let organization: String = app_organization_names
+            .filter(name_account_id.eq(org))
+            .filter(name_language.eq("default"))
+            .select(name)
+            .get_result(&mut conn)?;
+
+        // Emails are sent with the invited user language
+        let invited_language: Option<String> = app_users
+            .filter(user_acc_id.eq_any(email_accounts))
+            .select(language)
+            .first::<Option<String>>(&mut conn)
+            .optional()?
+            .flatten();
+
+        let email_message = EmailTemplate::Invitation {
+            organization: &organization,
+            inviter: &inviter,
+        }
+        .render(invited_language.as_deref())?;
+
+        let invitation_expires_at = now + Duration::days(INVITATION_EXPIRE_DAYS);
+
+        // The invitation and its email are saved together
+        conn.transaction::<_, diesel::result::Error, _>(|conn| {
+            NewOrganizationInvitation {
+                org_account_id: org,
+                creator_user_id: user,
+                email: &invitation.email,
+                expires_at: invitation_expires_at,
+            }
+            .insert_into(app_organization_invitations)
+            .execute(conn)?;
+
+            email_outbox::enqueue(
+                conn,
+                &invitation.email,
+                &email_message,
+                Some(invitation_expires_at),
+            )
+        })?;
+
+        Ok("Invited")
+    })
+    .await
+    .unwrap()
+}
+
+/// Deletes the pending invitation
+pub async fn revoke_invitation(
+    path: web::Path<(Uuid, Uuid)>,
+    pool: web::Data<DbPool>,
+) -> Result<&'static str, RouterError> {
+    use crate::schema::app_organization_invitations::dsl::{
+        accepted_at, app_organization_invitations, org_account_id, uuid as invitation_uuid,
+    };
+
+    let (org_uuid, target_invitation_uuid) = path.into_inner();
+
+    web::block(move || {
+        let mut conn = pool.get().unwrap();
+
+        let org = organization_account_id(&mut conn, org_uuid)?;
+
+        let deleted = diesel::delete(
+            app_organization_invitations
+                .filter(org_account_id.eq(org))
+                .filter(invitation_uuid.eq(target_invitation_uuid))
+                .filter(accepted_at.is_null()),
+        )
+        .execute(&mut conn)?;
+
+        if deleted == 0 {
+            return Err(RouterError::from_predefined("NOT_FOUND"));
+        }
+
+        Ok("Revoked")
+    })
+    .await
+    .unwrap()
+}
+
+/// Returns the pending invitations that are sent to
+/// the emails of the account
+pub async fn account_invitations(
+    pool: web::Data<DbPool>,
+    data: ReqData<u32>,
+) -> Result<web::Json<Vec<AccountInvitation>>, RouterError> {
+    use crate::schema::app_accounts::dsl::{app_accounts, username};
+    use crate::schema::app_emails::dsl::{
+        account_id as email_account_id, app_emails, deleted, email as email_address,
+    };
+    use crate::schema::app_organization_invitations::dsl::{
+        accepted_at, app_organization_invitations, created_at, email, expires_at, uuid,
+    };
+
+    let user_account_id = data.into_inner();
+
+    web::block(move || {
+        let mut conn = pool.get().unwrap();
+
+        let account_emails = app_emails
+            .filter(email_account_id.eq(user_account_id as i32))
+            .filter(deleted.eq(false))
+            .select(email_address);
+
+        let pending: Vec<(Uuid, String, NaiveDateTime, NaiveDateTime)> =
+            app_organization_invitations
+                .inner_join(app_accounts)
+                .filter(email.eq_any(account_emails))
+                .filter(accepted_at.is_null())
+                .filter(expires_at.gt(Utc::now().naive_utc()))
+                .select((uuid, username, expires_at, created_at))
+                .order(created_at.desc())
+                .load(&mut conn)?;
+
+        Ok(web::Json(
+            pending
+                .into_iter()
+                .map(
+                    |(invitation_uuid, organization, expires, created)| AccountInvitation {
+                        uuid: invitation_uuid,
+                        organization,
+                        expires_at: expires,
+                        created_at: created,
+                    },
+                )
+                .collect(),
+        ))
+    })
+    .await
+    .unwrap()
+}
+
+/// Returns the pending invitation if it's sent to the
+/// emails of the account
+fn find_account_invitation(
+    conn: &mut PgConnection,
+    account: i32,
+    target_invitation_uuid: Uuid,
+) -> QueryResult<OrganizationInvitation> {
+    use crate::schema::app_emails::dsl::{
+        account_id as email_account_id, app_emails, deleted, email as email_address,
+    };
+    use crate::schema::app_organization_invitations::dsl::{
+        accepted_at, app_organization_invitations, email, uuid as invitation_uuid,
+    };
+
+    app_organization_invitations
+        .filter(invitation_uuid.eq(target_invitation_uuid))
+        .filter(accepted_at.is_null())
+        .filter(
+            email.eq_any(
+                app_emails
+                    .filter(email_account_id.eq(account))
+                    .filter(deleted.eq(false))
+                    .select(email_address),
+            ),
+        )
+        .get_result(conn)
+}
+
+/// Accepts the invitation, the account
+/// becomes an employee of the organization
+pub async fn accept_invitation(
+    path: web::Path<Uuid>,
+    pool: web::Data<DbPool>,
+    data: ReqData<u32>,
+    req: HttpRequest,
+) -> Result<&'static str, RouterError> {
+    use crate::schema::app_employees::dsl::{app_employees, employee_account_id, org_account_id};
+    use crate::schema::app_organization_invitations::dsl::{accepted_at, updated_at};
+
+    let target_invitation_uuid = path.into_inner();
+    let user_account_id = data.into_inner() as i32;
+
+    let pool = pool.into_inner();
+
+    let error_detail = RouterErrorDetailBuilder::from_http_request(&req).build();
+
+    web::block(move || {
+        let mut conn = pool.get().unwrap();
+
+        let invitation =
+            find_account_invitation(&mut conn, user_account_id, target_invitation_uuid)?;
+
+        let now = Utc::now().naive_utc();
+
+        if invitation.expires_at <= now {
+            return Err(
+                RouterError::from_predefined("ORGANIZATION_INVITATION_EXPIRED")
+                    .log_to_db(pool, error_detail),
+            );
+        }
+
+        conn.transaction::<_, diesel::result::Error, _>(|conn| {
+            let already_employee = select(exists(
+                app_employees
+                    .filter(org_account_id.eq(invitation.org_account_id))
+                    .filter(employee_account_id.eq(user_account_id)),
+            ))
+            .get_result::<bool>(conn)?;
+
+            if !already_employee {
+                NewEmployee {
+                    org_account_id: invitation.org_account_id,
+                    creator_user_id: invitation.creator_user_id,
+                    employee_account_id: user_account_id,
+                }
+                .insert_into(app_employees)
+                .execute(conn)?;
+            }
+
+            diesel::update(&invitation)
+                .set((accepted_at.eq(Some(now)), updated_at.eq(now)))
+                .execute(conn)?;
+
+            // Cached permissions of every replica are cleared
+            permission_cache::notify_changed(conn)
+        })?;
+
+        Ok("Accepted")
+    })
+    .await
+    .unwrap()
+}
+
+/// Declines the invitation
+pub async fn decline_invitation(
+    path: web::Path<Uuid>,
+    pool: web::Data<DbPool>,
+    data: ReqData<u32>,
+) -> Result<&'static str, RouterError> {
+    let target_invitation_uuid = path.into_inner();
+    let user_account_id = data.into_inner() as i32;
+
+    web::block(move || {
+        let mut conn = pool.get().unwrap();
+
+        let invitation =
+            find_account_invitation(&mut conn, user_account_id, target_invitation_uuid)?;
+
+        diesel::delete(&invitation).execute(&mut conn)?;
+
+        Ok("Declined")
+    })
+    .await
+    .unwrap()
+}
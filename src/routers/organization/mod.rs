@@ -1,13 +1,18 @@
 pub mod add;
 pub mod delete;
 pub mod edit;
+pub mod employee;
+pub mod invitation;
 pub mod list;
 pub mod name;
+pub mod transfer;
 pub mod view;
 
 use crate::datetime::{parse_date_time_with_format, validate_date_time};
 use chrono::NaiveDate;
+use diesel::prelude::*;
 use serde::{Deserialize, Serialize};
+use uuid::Uuid;
 use validator::Validate;
 
 #[derive(Deserialize, Validate, Serialize)]
@@ -29,3 +34,16 @@ pub struct ReqOrganization {
     #[validate(length(equal = 11))]
     pub national_id: String,
 }
+
+/// Returns the account id of the organization
+/// with the account uuid
+pub fn organization_account_id(conn: &mut PgConnection, org_uuid: Uuid) -> QueryResult<i32> {
+    use crate::schema::app_accounts::dsl::{app_accounts, id as acc_id, uuid as account_uuid};
+    use crate::schema::app_organizations::dsl::app_organizations;
+
+    app_organizations
+        .inner_join(app_accounts)
+        .filter(account_uuid.eq(org_uuid))
+        .select(acc_id)
+        .get_result(conn)
+}
@@ -0,0 +1,82 @@
+use actix_web::{web, HttpRequest};
+use chrono::Utc;
+use diesel::{dsl::exists, prelude::*, select};
+use uuid::Uuid;
+
+use crate::{
+    error::{RouterError, RouterErrorDetailBuilder},
+    permission_cache, DbPool,
+};
+
+use super::{employee::ReqEmployee, organization_account_id};
+
+/// Transfers the ownership of the organization
+///
+/// The new owner must be an employee of the organization
+pub async fn transfer_ownership(
+    path: web::Path<Uuid>,
+    info: web::Json<ReqEmployee>,
+    pool: web::Data<DbPool>,
+    req: HttpRequest,
+) -> Result<&'static str, RouterError> {
+    use crate::schema::app_accounts::dsl::{app_accounts, id as acc_id, uuid as account_uuid};
+    use crate::schema::app_employees::dsl::{app_employees, employee_account_id, org_account_id};
+    use crate::schema::app_organizations::dsl::{
+        account_id as organization_account, app_organizations, owner_account_id, updated_at,
+    };
+
+    let org_uuid = path.into_inner();
+    let new_owner = info.into_inner();
+
+    let pool = pool.into_inner();
+
+    let error_detail = RouterErrorDetailBuilder::from_http_request(&req)
+        .request_body(
+            serde_json::to_string(&new_owner)
+                .unwrap()
+                .as_bytes()
+                .to_vec(),
+        )
+        .build();
+
+    web::block(move || {
+        let mut conn = pool.get().unwrap();
+
+        let org = organization_account_id(&mut conn, org_uuid)?;
+
+        let account: i32 = app_accounts
+            .filter(account_uuid.eq(new_owner.account))
+            .select(acc_id)
+            .get_result(&mut conn)?;
+
+        let is_employee = select(exists(
+            app_employees
+                .filter(org_account_id.eq(org))
+                .filter(employee_account_id.eq(account)),
+        ))
+        .get_result::<bool>(&mut conn)?;
+
+        if !is_employee {
+            return Err(
+                RouterError::from_predefined("ORGANIZATION_NEW_OWNER_NOT_EMPLOYEE")
+                    .log_to_db(pool, error_detail),
+            );
+        }
+
+        conn.transaction::<_, diesel::result::Error, _>(|conn| {
+            diesel::update(app_organizations.filter(organization_account.eq(org)))
+                .set((
+                    owner_account_id.eq(account),
+                    updated_at.eq(Utc::now().naive_utc()),
+                ))
+                .execute(conn)?;
+
+            // Cached permissions of every replica are cleared
+            permission_cache::notify_changed(conn)
+        })?;
+
+        Ok("Transferred")
+    })
+    .await
+    .unwrap()
+}
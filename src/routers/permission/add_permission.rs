@@ -1,19 +1,18 @@
 use crate::{
     error::RouterError,
     models::{NewPermission, NewPermissionCondition, Permission},
-    DbPool,
+    permission_cache, DbPool,
 };
 use actix_web::web;
 use diesel::prelude::*;
 
-use super::NewPermissionData;
+use super::{permission_subject, NewPermissionData};
 
 pub async fn add_permission(
     data: web::ReqData<u32>,
     new_permission: web::Json<NewPermissionData>,
     pool: web::Data<DbPool>,
 ) -> Result<&'static str, RouterError> {
-    use crate::schema::app_accounts::dsl::{app_accounts, id as acc_id, uuid as acc_uuid};
     use crate::schema::app_permission_conditions::dsl::app_permission_conditions;
     use crate::schema::app_permissions::dsl::app_permissions;
     use crate::schema::app_users::dsl::{account_id as user_acc_id, app_users, id as user_id};
@@ -24,45 +23,57 @@ pub async fn add_permission(
     web::block(move || {
         let mut conn = pool.get().unwrap();
 
-        let account: i32 = app_accounts
-            .filter(acc_uuid.eq(new_permission_data.subject))
-            .select(acc_id)
-            .get_result(&mut conn)?;
+        let (account, role, group, pseudo_subject) = permission_subject(
+            &mut conn,
+            new_permission_data.subject_type,
+            new_permission_data.subject,
+        )?;
 
         let user: i32 = app_users
             .filter(user_acc_id.eq(data as i32))
             .select(user_id)
             .get_result(&mut conn)?;
 
-        // First Insert a brand new Permission
-        let new_permission: Permission = NewPermission {
-            creator_user_id: user,
-            account_id: account,
-            object: &new_permission_data.object,
-            action: &new_permission_data.action,
+        // Conditions must be valid before anything is inserted
+        for condition in &new_permission_data.conditions {
+            condition.validate()?;
         }
-        .insert_into(app_permissions)
-        .get_result(&mut conn)?;
 
-        // Now We must insert the Conditions
-        // however We must make sure the request conditions
-        // actually exists
-        let mut insertable_conditions: Vec<NewPermissionCondition> = Vec::new();
+        // The permission is never saved without its conditions
+        conn.transaction::<_, diesel::result::Error, _>(|conn| {
+            // First Insert a brand new Permission
+            let new_permission: Permission = NewPermission {
+                creator_user_id: user,
+                account_id: account,
+                role_id: role,
+                group_id: group,
+                object: &new_permission_data.object,
+                action: &new_permission_data.action,
+                condition_operator: new_permission_data.condition_operator.as_str(),
+                pseudo_subject,
+            }
+            .insert_into(app_permissions)
+            .get_result(conn)?;
 
-        for condition in new_permission_data.conditions {
-            condition.validate()?;
+            // Now We must insert the Conditions
+            let insertable_conditions: Vec<NewPermissionCondition> = new_permission_data
+                .conditions
+                .into_iter()
+                .map(|condition| NewPermissionCondition {
+                    creator_user_id: user,
+                    permission_id: new_permission.id,
+                    name: condition.name,
+                    value: condition.value,
+                })
+                .collect();
 
-            insertable_conditions.push(NewPermissionCondition {
-                creator_user_id: user,
-                permission_id: new_permission.id,
-                name: condition.name,
-                value: condition.value,
-            });
-        }
+            insertable_conditions
+                .insert_into(app_permission_conditions)
+                .execute(conn)?;
 
-        insertable_conditions
-            .insert_into(app_permission_conditions)
-            .execute(&mut conn)?;
+            // Cached permissions of every replica are cleared
+            permission_cache::notify_changed(conn)
+        })?;
 
         Ok("Added")
     })
@@ -1,4 +1,4 @@
-use crate::{error::RouterError, DbPool};
+use crate::{error::RouterError, permission_cache, DbPool};
 use actix_web::web::{self, Path};
 use diesel::prelude::*;
 use uuid::Uuid;
@@ -18,6 +18,9 @@ pub async fn delete_permission(
         diesel::delete(app_permissions.filter(uuid_from_permission.eq(target_permission_uuid)))
             .execute(&mut conn)?;
 
+        // Cached permissions of every replica are cleared
+        permission_cache::notify_changed(&mut conn)?;
+
         Ok("Deleted")
     })
     .await
@@ -3,6 +3,7 @@ use crate::{
     difference::{Difference, DifferenceContext, DifferenceResult},
     error::RouterError,
     models::{NewPermissionCondition, Permission, PermissionCondition},
+    permission_cache,
     routers::permission::SimpleCondition,
     DbPool,
 };
@@ -10,7 +11,7 @@ use actix_web::web::{self, Path};
 use diesel::prelude::*;
 use uuid::Uuid;
 
-use super::NewPermissionData;
+use super::{permission_subject, NewPermissionData};
 
 /// Edit's the target permission
 ///
@@ -23,13 +24,14 @@ pub async fn edit_permission(
     pool: web::Data<DbPool>,
     data: web::ReqData<u32>,
 ) -> Result<&'static str, RouterError> {
-    use crate::schema::app_accounts::dsl::{app_accounts, id as acc_id, uuid as acc_uuid};
     use crate::schema::app_permission_conditions::dsl::{
         app_permission_conditions, id as condition_id, name as condition_name,
         value as condition_value,
     };
     use crate::schema::app_permissions::dsl::{
-        action, app_permissions, object, account_id as permission_account_id, uuid as uuid_of_permission,
+        account_id as permission_account_id, action, app_permissions, condition_operator,
+        group_id as permission_group_id, object, pseudo_subject, role_id as permission_role_id,
+        uuid as uuid_of_permission,
     };
     use crate::schema::app_users::dsl::{account_id as user_acc_id, app_users};
 
@@ -40,69 +42,85 @@ pub async fn edit_permission(
     web::block(move || {
         let mut conn = pool.get().unwrap();
 
-        let account: i32 = app_accounts
-            .filter(acc_uuid.eq(new_permission.subject))
-            .select(acc_id)
-            .get_result(&mut conn)?;
+        let (account, role, group, pseudo) = permission_subject(
+            &mut conn,
+            new_permission.subject_type,
+            new_permission.subject,
+        )?;
+
+        // Conditions must be valid before anything is changed
+        for condition in &new_permission.conditions {
+            condition.validate()?;
+        }
 
-        let permission: Permission = diesel::update(app_permissions)
-            .filter(uuid_of_permission.eq(target_permission))
-            .set((
-                permission_account_id.eq(account),
-                object.eq(new_permission.object),
-                action.eq(new_permission.action),
-            ))
+        // Get the user form account_id so we can set the creator property
+        let user: User = app_users
+            .filter(user_acc_id.eq(data as i32))
             .get_result(&mut conn)?;
 
-        // Get existing conditions
-        let target_conditions: Vec<PermissionCondition> =
-            PermissionCondition::belonging_to(&permission).get_results(&mut conn)?;
+        // The permission is never left with only some of its conditions
+        conn.transaction::<_, diesel::result::Error, _>(|conn| {
+            let permission: Permission = diesel::update(app_permissions)
+                .filter(uuid_of_permission.eq(target_permission))
+                .set((
+                    permission_account_id.eq(account),
+                    permission_role_id.eq(role),
+                    permission_group_id.eq(group),
+                    pseudo_subject.eq(pseudo),
+                    object.eq(new_permission.object),
+                    action.eq(new_permission.action),
+                    condition_operator.eq(new_permission.condition_operator.as_str()),
+                ))
+                .get_result(conn)?;
 
-        // Turn PermissionCondition into SimpleCondition
-        let target_conditions: Vec<SimpleCondition> = target_conditions
-            .into_iter()
-            .map(SimpleCondition::from)
-            .collect();
+            // Get existing conditions
+            let target_conditions: Vec<PermissionCondition> =
+                PermissionCondition::belonging_to(&permission).get_results(conn)?;
 
-        // Provide required data
-        let difference_context =
-            DifferenceContext::new(target_conditions, new_permission.conditions);
+            // Turn PermissionCondition into SimpleCondition
+            let target_conditions: Vec<SimpleCondition> = target_conditions
+                .into_iter()
+                .map(SimpleCondition::from)
+                .collect();
 
-        // Create Difference Object from context
-        let mut difference = Difference::from(difference_context);
+            // Provide required data
+            let difference_context =
+                DifferenceContext::new(target_conditions, new_permission.conditions);
 
-        // Found the difference between Existing conditions and new conditions,
-        let difference_result = difference.diff();
+            // Create Difference Object from context
+            let mut difference = Difference::from(difference_context);
 
-        // Get the user form account_id so we can set the creator property
-        let user: User = app_users
-            .filter(user_acc_id.eq(data as i32))
-            .get_result(&mut conn)?;
+            // Found the difference between Existing conditions and new conditions,
+            let difference_result = difference.diff();
 
-        // Now we gonna walk the results and do what they say :)
-        for diff_action in difference_result {
-            match diff_action {
-                DifferenceResult::Update(old, new) => {
-                    diesel::update(app_permission_conditions.filter(condition_id.eq(old.id)))
-                        .set((condition_name.eq(new.name), condition_value.eq(new.value)))
-                        .execute(&mut conn)?;
-                }
-                DifferenceResult::Insert(new) => {
-                    NewPermissionCondition {
-                        creator_user_id: user.id,
-                        name: new.name,
-                        value: new.value,
-                        permission_id: permission.id,
+            // Now we gonna walk the results and do what they say :)
+            for diff_action in difference_result {
+                match diff_action {
+                    DifferenceResult::Update(old, new) => {
+                        diesel::update(app_permission_conditions.filter(condition_id.eq(old.id)))
+                            .set((condition_name.eq(new.name), condition_value.eq(new.value)))
+                            .execute(conn)?;
+                    }
+                    DifferenceResult::Insert(new) => {
+                        NewPermissionCondition {
+                            creator_user_id: user.id,
+                            name: new.name,
+                            value: new.value,
+                            permission_id: permission.id,
+                        }
+                        .insert_into(app_permission_conditions)
+                        .execute(conn)?;
+                    }
+                    DifferenceResult::Remove(old) => {
+                        diesel::delete(app_permission_conditions.filter(condition_id.eq(old.id)))
+                            .execute(conn)?;
                     }
-                    .insert_into(app_permission_conditions)
-                    .execute(&mut conn)?;
-                }
-                DifferenceResult::Remove(old) => {
-                    diesel::delete(app_permission_conditions.filter(condition_id.eq(old.id)))
-                        .execute(&mut conn)?;
                 }
             }
-        }
+
+            // Cached permissions of every replica are cleared
+            permission_cache::notify_changed(conn)
+        })?;
 
         Ok("Updated")
     })
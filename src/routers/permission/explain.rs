@@ -0,0 +1,68 @@
+use crate::{
+    authz::{AuthZController, Evaluation},
+    error::RouterError,
+    DbPool,
+};
+use actix_web::{http::Method, web};
+use auth_z::{DeclaredRoutes, Subject};
+use diesel::prelude::*;
+use serde::Deserialize;
+use uuid::Uuid;
+
+#[derive(Deserialize)]
+pub struct ExplainQuery {
+    /// Account uuid that the request is checked for,
+    /// the request is anonymous without it
+    pub subject: Option<Uuid>,
+
+    /// Request method, example: `POST`
+    pub method: String,
+
+    /// Request path with its query string, example: `/organization/{uuid}`
+    pub path: String,
+}
+
+/// Returns the evaluation trace of the permissions
+///
+/// Nothing is logged or changed, this is a dry-run of the
+/// AuthZ check for the subject account with the metadata
+/// that the route declares
+pub async fn explain_permission(
+    web::Query(query): web::Query<ExplainQuery>,
+    pool: web::Data<DbPool>,
+    controller: web::Data<AuthZController>,
+    declared_routes: web::Data<DeclaredRoutes>,
+) -> Result<web::Json<Evaluation>, RouterError> {
+    use crate::schema::app_accounts::dsl::{app_accounts, id as acc_id, uuid as account_uuid};
+
+    let account: Option<i32> = match query.subject {
+        Some(subject) => Some(
+            web::block(move || {
+                let mut conn = pool.get().unwrap();
+
+                app_accounts
+                    .filter(account_uuid.eq(subject))
+                    .select(acc_id)
+                    .get_result(&mut conn)
+            })
+            .await
+            .unwrap()?,
+        ),
+        None => None,
+    };
+
+    let method = Method::from_bytes(query.method.to_uppercase().as_bytes())
+        .map_err(|_| RouterError::from_predefined("BAD_QUERY"))?;
+
+    let route = declared_routes.resolve(&method, &query.path);
+
+    let evaluation = controller
+        .evaluate(
+            Subject::from(account.map(|id| id as u32)),
+            &route,
+            method.as_str(),
+        )
+        .await?;
+
+    Ok(web::Json(evaluation))
+}
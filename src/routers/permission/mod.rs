@@ -1,24 +1,123 @@
 use crate::{
-    authz::{Condition, ConditionValueType, ModelAttrib, ModelAttribResult},
+    authz::{Condition, ModelAttrib, ModelAttribResult},
+    condition::{ConditionOperator, ConditionValueType},
     difference::GetKey,
     error::RouterError,
-    models::PermissionCondition,
+    models::{Permission, PermissionCondition},
 };
+use auth_z::PseudoSubject;
+use diesel::prelude::*;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
 pub mod add_permission;
 pub mod delete_permission;
 pub mod edit_permission;
+pub mod explain;
 pub mod permissions_list;
 pub mod view_permission;
 
+/// Who the permission is granted to
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum PermissionSubjectType {
+    #[default]
+    Account,
+    Role,
+    Group,
+
+    /// Every request, anonymous ones included
+    Everyone,
+
+    /// Every request with a valid token
+    Authenticated,
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct NewPermissionData {
-    subject: Uuid,
+    /// Not needed for the pseudo subjects
+    #[serde(default)]
+    subject: Option<Uuid>,
+
+    /// Type of the subject uuid, default is account
+    #[serde(default)]
+    subject_type: PermissionSubjectType,
+
     object: String,
     action: String,
     conditions: Vec<SimpleCondition>,
+
+    /// How the conditions are combined, default is and
+    #[serde(default)]
+    condition_operator: ConditionOperator,
+}
+
+/// (account_id, role_id, group_id, pseudo_subject) of the permission
+pub type PermissionSubjectRow = (Option<i32>, Option<i32>, Option<i32>, Option<&'static str>);
+
+/// Finds the subject and returns the
+/// (account_id, role_id, group_id, pseudo_subject) of the permission
+pub fn permission_subject(
+    conn: &mut PgConnection,
+    subject_type: PermissionSubjectType,
+    subject: Option<Uuid>,
+) -> Result<PermissionSubjectRow, RouterError> {
+    use crate::schema::app_accounts::dsl::{app_accounts, id as acc_id, uuid as acc_uuid};
+    use crate::schema::app_groups::dsl::{app_groups, id as group_id, uuid as group_uuid};
+    use crate::schema::app_roles::dsl::{app_roles, id as role_id, uuid as role_uuid};
+
+    // Only the pseudo subjects have no uuid
+    let subject_uuid =
+        || subject.ok_or_else(|| RouterError::from_predefined("PERMISSION_SUBJECT_NOT_DEFINED"));
+
+    Ok(match subject_type {
+        PermissionSubjectType::Account => (
+            Some(
+                app_accounts
+                    .filter(acc_uuid.eq(subject_uuid()?))
+                    .select(acc_id)
+                    .get_result(conn)?,
+            ),
+            None,
+            None,
+            None,
+        ),
+
+        PermissionSubjectType::Role => (
+            None,
+            Some(
+                app_roles
+                    .filter(role_uuid.eq(subject_uuid()?))
+                    .select(role_id)
+                    .get_result(conn)?,
+            ),
+            None,
+            None,
+        ),
+
+        PermissionSubjectType::Group => (
+            None,
+            None,
+            Some(
+                app_groups
+                    .filter(group_uuid.eq(subject_uuid()?))
+                    .select(group_id)
+                    .get_result(conn)?,
+            ),
+            None,
+        ),
+
+        PermissionSubjectType::Everyone => {
+            (None, None, None, Some(PseudoSubject::Everyone.as_str()))
+        }
+
+        PermissionSubjectType::Authenticated => (
+            None,
+            None,
+            None,
+            Some(PseudoSubject::Authenticated.as_str()),
+        ),
+    })
 }
 
 #[derive(PartialEq, Eq, Hash, Serialize, Deserialize, Debug, Clone)]
@@ -51,11 +150,10 @@ impl SimpleCondition {
     fn validate(&self) -> Result<(), RouterError> {
         let model_attr = ModelAttrib::try_from(self.name.as_str())?;
         let attr_result = ModelAttribResult::from(model_attr);
-        let value_type = attr_result.get_value_type();
 
         let self_value_type = ConditionValueType::try_from(self.value.as_str())?;
 
-        if value_type != self_value_type {
+        if !attr_result.get_value_types().contains(&self_value_type) {
             // TODO: log this to db
             return Err(RouterError::from_predefined(
                 "PERMISSION_CONDITION_VALUE_NOT_VALID",
@@ -66,6 +164,17 @@ impl SimpleCondition {
     }
 }
 
+/// (username, first name, last name, account uuid, role uuid, group uuid, permission)
+pub type PermissionAccountRow = (
+    Option<String>,
+    Option<String>,
+    Option<String>,
+    Option<Uuid>,
+    Option<Uuid>,
+    Option<Uuid>,
+    Permission,
+);
+
 #[derive(Serialize, Eq, Ord, Hash, Debug, Clone, PartialEq, PartialOrd)]
 pub struct PermissionAccount {
     uuid: Option<Uuid>,
@@ -80,8 +189,19 @@ pub struct SimplePermission {
     id: i32,
     uuid: Uuid,
     account: PermissionAccount,
+
+    /// Role uuid if the permission is granted to a role
+    role: Option<Uuid>,
+
+    /// Group uuid if the permission is granted to a group
+    group: Option<Uuid>,
+
+    /// `everyone` or `authenticated` if the permission is granted to them
+    pseudo_subject: Option<String>,
+
     object: String,
     action: String,
+    condition_operator: String,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -90,3 +210,52 @@ pub struct PermissionWithConditions {
     permission: SimplePermission,
     conditions: Vec<PermissionCondition>,
 }
+
+/// Permission of a role or group, without the subject
+#[derive(Serialize, Debug, Clone)]
+pub struct GrantedPermission {
+    uuid: Uuid,
+    object: String,
+    action: String,
+}
+
+impl From<Permission> for GrantedPermission {
+    fn from(value: Permission) -> Self {
+        Self {
+            uuid: value.uuid,
+            object: value.object,
+            action: value.action,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{NewPermissionData, PermissionSubjectType};
+
+    #[test]
+    fn test_permission_subject_type() {
+        let data: NewPermissionData = serde_json::from_str(
+            r#"{"subject": "8b9a4c5e-3f6d-4c47-9a5e-1c0d8e6f7a21", "object": "surah", "action": "edit", "conditions": []}"#,
+        )
+        .unwrap();
+
+        // Account is the default subject
+        assert_eq!(data.subject_type, PermissionSubjectType::Account);
+
+        let data: NewPermissionData = serde_json::from_str(
+            r#"{"subject": "8b9a4c5e-3f6d-4c47-9a5e-1c0d8e6f7a21", "subject_type": "role", "object": "surah", "action": "edit", "conditions": []}"#,
+        )
+        .unwrap();
+
+        assert_eq!(data.subject_type, PermissionSubjectType::Role);
+
+        let data: NewPermissionData = serde_json::from_str(
+            r#"{"subject_type": "everyone", "object": "surah", "action": "view", "conditions": []}"#,
+        )
+        .unwrap();
+
+        assert_eq!(data.subject_type, PermissionSubjectType::Everyone);
+        assert_eq!(data.subject, None);
+    }
+}
@@ -5,13 +5,14 @@ use crate::{
     models::{Permission, PermissionCondition},
     routers::{
         multip,
-        permission::{PermissionAccount, PermissionWithConditions, SimplePermission},
+        permission::{
+            PermissionAccount, PermissionAccountRow, PermissionWithConditions, SimplePermission,
+        },
     },
     DbPool,
 };
 use actix_web::web;
 use diesel::prelude::*;
-use uuid::Uuid;
 
 /// Returns the list of Permissions
 ///
@@ -22,8 +23,10 @@ pub async fn get_list_of_permissions(
     use crate::schema::app_accounts::dsl::{
         app_accounts, username as acc_username, uuid as account_uuid,
     };
+    use crate::schema::app_groups::dsl::{app_groups, uuid as group_uuid};
     use crate::schema::app_permission_conditions::dsl::app_permission_conditions;
     use crate::schema::app_permissions::dsl::app_permissions;
+    use crate::schema::app_roles::dsl::{app_roles, uuid as role_uuid};
     use crate::schema::app_user_names::dsl::{
         app_user_names, first_name as f_name, last_name as l_name,
     };
@@ -32,38 +35,35 @@ pub async fn get_list_of_permissions(
         let mut conn = pool.get().unwrap();
 
         // TODO: fix None Condition
-        let permissions_with_conditions: Vec<(
-            (
-                Option<String>,
-                Option<String>,
-                Option<String>,
-                Option<Uuid>,
-                Permission,
-            ),
-            Option<PermissionCondition>,
-        )> = app_permissions
-            .left_join(app_permission_conditions)
-            .left_join(app_accounts.left_join(app_user_names))
-            .select((
-                acc_username.nullable(),
-                f_name.nullable(),
-                l_name.nullable(),
-                account_uuid.nullable(),
-                Permission::as_select(),
-                // This is for situation that there is no
-                // condition related to this Permission
-                Option::<PermissionCondition>::as_select(),
-            ))
-            .load(&mut conn)?
-            .into_iter()
-            .map(|(un, fname, lname, u, p, pc)| ((un, fname, lname, u, p), pc))
-            .collect();
+        let permissions_with_conditions: Vec<(PermissionAccountRow, Option<PermissionCondition>)> =
+            app_permissions
+                .left_join(app_permission_conditions)
+                .left_join(app_accounts.left_join(app_user_names))
+                .left_join(app_roles)
+                .left_join(app_groups)
+                .select((
+                    acc_username.nullable(),
+                    f_name.nullable(),
+                    l_name.nullable(),
+                    account_uuid.nullable(),
+                    role_uuid.nullable(),
+                    group_uuid.nullable(),
+                    Permission::as_select(),
+                    // This is for situation that there is no
+                    // condition related to this Permission
+                    Option::<PermissionCondition>::as_select(),
+                ))
+                .load(&mut conn)?
+                .into_iter()
+                .map(|(un, fname, lname, u, r, g, p, pc)| ((un, fname, lname, u, r, g, p), pc))
+                .collect();
 
         let permissions_with_conditions_map: BTreeMap<
             SimplePermission,
             Vec<Option<PermissionCondition>>,
-        > = multip(permissions_with_conditions, |(un, fname, lname, u, p)| {
-            SimplePermission {
+        > = multip(
+            permissions_with_conditions,
+            |(un, fname, lname, u, r, g, p)| SimplePermission {
                 id: p.id,
                 uuid: p.uuid,
                 account: PermissionAccount {
@@ -72,10 +72,14 @@ pub async fn get_list_of_permissions(
                     first_name: fname,
                     last_name: lname,
                 },
+                role: r,
+                group: g,
+                pseudo_subject: p.pseudo_subject,
                 object: p.object,
                 action: p.action,
-            }
-        });
+                condition_operator: p.condition_operator,
+            },
+        );
 
         let result: Vec<PermissionWithConditions> = permissions_with_conditions_map
             .into_iter()
@@ -1,7 +1,7 @@
 use crate::{
     error::RouterError,
     models::{Permission, PermissionCondition},
-    routers::permission::{PermissionWithConditions, SimplePermission},
+    routers::permission::{PermissionAccountRow, PermissionWithConditions, SimplePermission},
     DbPool,
 };
 use actix_web::web;
@@ -18,8 +18,10 @@ pub async fn get_permission(
     use crate::schema::app_accounts::dsl::{
         app_accounts, username as acc_username, uuid as account_uuid,
     };
+    use crate::schema::app_groups::dsl::{app_groups, uuid as group_uuid};
     use crate::schema::app_permission_conditions::dsl::app_permission_conditions;
     use crate::schema::app_permissions::dsl::{app_permissions, uuid as uuid_from_permissions};
+    use crate::schema::app_roles::dsl::{app_roles, uuid as role_uuid};
     use crate::schema::app_user_names::dsl::{
         app_user_names, first_name as f_name, last_name as l_name,
     };
@@ -29,21 +31,19 @@ pub async fn get_permission(
     let permission: Result<PermissionWithConditions, RouterError> = web::block(move || {
         let mut conn = pool.get().unwrap();
 
-        let (account_username, first_name, last_name, a_uuid, permission): (
-            Option<String>,
-            Option<String>,
-            Option<String>,
-            Option<Uuid>,
-            Permission,
-        ) = app_permissions
+        let (account_username, first_name, last_name, a_uuid, r_uuid, g_uuid, permission): PermissionAccountRow = app_permissions
             .filter(uuid_from_permissions.eq(requested_permission_uuid))
             .left_join(app_permission_conditions)
             .left_join(app_accounts.left_join(app_user_names))
+            .left_join(app_roles)
+            .left_join(app_groups)
             .select((
                 acc_username.nullable(),
                 f_name.nullable(),
                 l_name.nullable(),
                 account_uuid.nullable(),
+                role_uuid.nullable(),
+                group_uuid.nullable(),
                 Permission::as_select(),
             ))
             .get_result(&mut conn)?;
@@ -61,8 +61,12 @@ pub async fn get_permission(
                     first_name,
                     last_name
                 },
+                role: r_uuid,
+                group: g_uuid,
+                pseudo_subject: permission.pseudo_subject,
                 object: permission.object,
                 action: permission.action,
+                condition_operator: permission.condition_operator,
             },
             conditions,
         })
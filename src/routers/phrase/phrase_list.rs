@@ -30,7 +30,7 @@ pub async fn list_phrase(
             .select((p_t_lang, phrase))
             .get_results(&mut conn)?;
 
-        let multi: BTreeMap<String, Vec<String>> = multip(list, |val| String::from(val));
+        let multi: BTreeMap<String, Vec<String>> = multip(list, |val| val);
 
         let mut result: Vec<LangWithStatus> = vec![];
 
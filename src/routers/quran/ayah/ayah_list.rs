@@ -51,15 +51,11 @@ pub async fn ayah_list(
             .map(|(ayah, words)| match query.format {
                 Format::Text => AyahTy::Text(crate::AyahWithText {
                     ayah,
-                    text: words
-                        .into_iter()
-                        .map(|word| word)
-                        .collect::<Vec<String>>()
-                        .join(" "),
+                    text: words.join(" "),
                 }),
                 Format::Word => AyahTy::Words(crate::AyahWithWords {
                     ayah,
-                    words: words.into_iter().map(|word| word).collect(),
+                    words,
                 }),
             })
             .collect::<Vec<AyahTy>>();
@@ -12,19 +12,14 @@ use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
 /// The quran text format Each word has its own uuid
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, Default)]
 #[serde(rename_all = "lowercase")]
 pub enum Format {
+    #[default]
     Text,
     Word,
 }
 
-impl Default for Format {
-    fn default() -> Self {
-        Self::Text
-    }
-}
-
 /// The Ayah type that will return in the response
 #[derive(PartialOrd, Ord, Eq, Hash, PartialEq, Serialize, Clone, Debug)]
 pub struct SimpleAyah {
@@ -86,12 +86,9 @@ pub async fn surah_view(
                 p = p.filter(p_t_lang.eq("en"));
             }
 
-            let result = p
-                .filter(p_phrase.eq(phrase))
+            p.filter(p_phrase.eq(phrase))
                 .select(p_t_text.nullable())
-                .get_result(&mut conn)?;
-
-            result
+                .get_result(&mut conn)?
         } else {
             None
         };
@@ -0,0 +1,73 @@
+use actix_web::{
+    web::{self, ReqData},
+    HttpRequest,
+};
+use diesel::{dsl::exists, prelude::*, select};
+
+use crate::{
+    error::{RouterError, RouterErrorDetailBuilder},
+    models::NewRole,
+    validate::validate,
+    DbPool,
+};
+
+use super::ReqRole;
+
+/// Add a new Role
+///
+/// Permissions are granted to the role from /permission
+/// with the `role` subject_type
+pub async fn add(
+    pool: web::Data<DbPool>,
+    new_role: web::Json<ReqRole>,
+    data: ReqData<u32>,
+    req: HttpRequest,
+) -> Result<&'static str, RouterError> {
+    use crate::schema::app_roles::dsl::{app_roles, name};
+    use crate::schema::app_users::dsl::{account_id as user_acc_id, app_users, id as user_id};
+
+    let new_role_info = new_role.into_inner();
+    let user_account_id = data.into_inner();
+
+    validate(&new_role_info)?;
+
+    let pool = pool.into_inner();
+
+    let error_detail = RouterErrorDetailBuilder::from_http_request(&req)
+        .request_body(
+            serde_json::to_string(&new_role_info)
+                .unwrap()
+                .as_bytes()
+                .to_vec(),
+        )
+        .build();
+
+    web::block(move || {
+        let mut conn = pool.get().unwrap();
+
+        let role_exists = select(exists(app_roles.filter(name.eq(&new_role_info.name))))
+            .get_result::<bool>(&mut conn)?;
+
+        if role_exists {
+            return Err(RouterError::from_predefined("ROLE_NAME_NOT_AVAILABLE")
+                .log_to_db(pool, error_detail));
+        }
+
+        let user: i32 = app_users
+            .filter(user_acc_id.eq(user_account_id as i32))
+            .select(user_id)
+            .get_result(&mut conn)?;
+
+        NewRole {
+            creator_user_id: user,
+            name: &new_role_info.name,
+            description: new_role_info.description.as_ref(),
+        }
+        .insert_into(app_roles)
+        .execute(&mut conn)?;
+
+        Ok("Created")
+    })
+    .await
+    .unwrap()
+}
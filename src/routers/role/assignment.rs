@@ -0,0 +1,234 @@
+use actix_web::{
+    web::{self, ReqData},
+    HttpRequest,
+};
+use diesel::{dsl::exists, prelude::*, select};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{
+    error::{RouterError, RouterErrorDetailBuilder},
+    models::NewRoleAssignment,
+    permission_cache, DbPool,
+};
+
+/// Roles are assigned to accounts or groups
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum AssignmentSubjectType {
+    #[default]
+    Account,
+    Group,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct RoleAssignment {
+    subject: Uuid,
+
+    #[serde(default)]
+    subject_type: AssignmentSubjectType,
+}
+
+/// Returns the accounts and groups that the role is assigned to
+pub async fn assignments(
+    path: web::Path<Uuid>,
+    pool: web::Data<DbPool>,
+) -> Result<web::Json<Vec<RoleAssignment>>, RouterError> {
+    use crate::schema::app_accounts::dsl::{app_accounts, uuid as account_uuid};
+    use crate::schema::app_groups::dsl::{app_groups, uuid as group_uuid};
+    use crate::schema::app_role_assignments::dsl::{app_role_assignments, role_id};
+    use crate::schema::app_roles::dsl::{app_roles, id as r_id, uuid as role_uuid};
+
+    let target_role_uuid = path.into_inner();
+
+    web::block(move || {
+        let mut conn = pool.get().unwrap();
+
+        let role: i32 = app_roles
+            .filter(role_uuid.eq(target_role_uuid))
+            .select(r_id)
+            .get_result(&mut conn)?;
+
+        let subjects: Vec<(Option<Uuid>, Option<Uuid>)> = app_role_assignments
+            .filter(role_id.eq(role))
+            .left_join(app_accounts)
+            .left_join(app_groups)
+            .select((account_uuid.nullable(), group_uuid.nullable()))
+            .load(&mut conn)?;
+
+        let result = subjects
+            .into_iter()
+            .filter_map(|subject| match subject {
+                (Some(account), _) => Some(RoleAssignment {
+                    subject: account,
+                    subject_type: AssignmentSubjectType::Account,
+                }),
+                (None, Some(group)) => Some(RoleAssignment {
+                    subject: group,
+                    subject_type: AssignmentSubjectType::Group,
+                }),
+                (None, None) => None,
+            })
+            .collect();
+
+        Ok(web::Json(result))
+    })
+    .await
+    .unwrap()
+}
+
+/// Assigns the role to an account or group
+pub async fn assign(
+    path: web::Path<Uuid>,
+    info: web::Json<RoleAssignment>,
+    pool: web::Data<DbPool>,
+    data: ReqData<u32>,
+    req: HttpRequest,
+) -> Result<&'static str, RouterError> {
+    use crate::schema::app_accounts::dsl::{app_accounts, id as acc_id, uuid as account_uuid};
+    use crate::schema::app_groups::dsl::{app_groups, id as g_id, uuid as group_uuid};
+    use crate::schema::app_role_assignments::dsl::{
+        account_id, app_role_assignments, group_id, role_id,
+    };
+    use crate::schema::app_roles::dsl::{app_roles, id as r_id, uuid as role_uuid};
+    use crate::schema::app_users::dsl::{account_id as user_acc_id, app_users, id as user_id};
+
+    let target_role_uuid = path.into_inner();
+    let assignment = info.into_inner();
+    let user_account_id = data.into_inner();
+
+    let pool = pool.into_inner();
+
+    let error_detail = RouterErrorDetailBuilder::from_http_request(&req)
+        .request_body(
+            serde_json::to_string(&assignment)
+                .unwrap()
+                .as_bytes()
+                .to_vec(),
+        )
+        .build();
+
+    web::block(move || {
+        let mut conn = pool.get().unwrap();
+
+        let role: i32 = app_roles
+            .filter(role_uuid.eq(target_role_uuid))
+            .select(r_id)
+            .get_result(&mut conn)?;
+
+        let (account, group): (Option<i32>, Option<i32>) = match assignment.subject_type {
+            AssignmentSubjectType::Account => (
+                Some(
+                    app_accounts
+                        .filter(account_uuid.eq(assignment.subject))
+                        .select(acc_id)
+                        .get_result(&mut conn)?,
+                ),
+                None,
+            ),
+
+            AssignmentSubjectType::Group => (
+                None,
+                Some(
+                    app_groups
+                        .filter(group_uuid.eq(assignment.subject))
+                        .select(g_id)
+                        .get_result(&mut conn)?,
+                ),
+            ),
+        };
+
+        let already_assigned = select(exists(
+            app_role_assignments
+                .filter(role_id.eq(role))
+                .filter(account_id.eq(account).or(group_id.eq(group))),
+        ))
+        .get_result::<bool>(&mut conn)?;
+
+        if already_assigned {
+            return Err(
+                RouterError::from_predefined("ROLE_ALREADY_ASSIGNED").log_to_db(pool, error_detail)
+            );
+        }
+
+        let user: i32 = app_users
+            .filter(user_acc_id.eq(user_account_id as i32))
+            .select(user_id)
+            .get_result(&mut conn)?;
+
+        conn.transaction::<_, diesel::result::Error, _>(|conn| {
+            NewRoleAssignment {
+                creator_user_id: user,
+                role_id: role,
+                account_id: account,
+                group_id: group,
+            }
+            .insert_into(app_role_assignments)
+            .execute(conn)?;
+
+            // Cached permissions of every replica are cleared
+            permission_cache::notify_changed(conn)
+        })?;
+
+        Ok("Assigned")
+    })
+    .await
+    .unwrap()
+}
+
+/// Removes the role from the account or group
+pub async fn unassign(
+    path: web::Path<(Uuid, Uuid)>,
+    pool: web::Data<DbPool>,
+) -> Result<&'static str, RouterError> {
+    use crate::schema::app_accounts::dsl::{app_accounts, id as acc_id, uuid as account_uuid};
+    use crate::schema::app_groups::dsl::{app_groups, id as g_id, uuid as group_uuid};
+    use crate::schema::app_role_assignments::dsl::{
+        account_id, app_role_assignments, group_id, role_id,
+    };
+    use crate::schema::app_roles::dsl::{app_roles, id as r_id, uuid as role_uuid};
+
+    let (target_role_uuid, subject_uuid) = path.into_inner();
+
+    web::block(move || {
+        let mut conn = pool.get().unwrap();
+
+        let role: i32 = app_roles
+            .filter(role_uuid.eq(target_role_uuid))
+            .select(r_id)
+            .get_result(&mut conn)?;
+
+        let deleted = conn.transaction::<_, diesel::result::Error, _>(|conn| {
+            // The subject uuid can be an account or a group
+            let deleted = diesel::delete(
+                app_role_assignments.filter(role_id.eq(role)).filter(
+                    account_id
+                        .eq_any(
+                            app_accounts
+                                .filter(account_uuid.eq(subject_uuid))
+                                .select(acc_id.nullable()),
+                        )
+                        .or(group_id.eq_any(
+                            app_groups
+                                .filter(group_uuid.eq(subject_uuid))
+                                .select(g_id.nullable()),
+                        )),
+                ),
+            )
+            .execute(conn)?;
+
+            // Cached permissions of every replica are cleared
+            permission_cache::notify_changed(conn)?;
+
+            Ok(deleted)
+        })?;
+
+        if deleted == 0 {
+            return Err(RouterError::from_predefined("NOT_FOUND"));
+        }
+
+        Ok("Unassigned")
+    })
+    .await
+    .unwrap()
+}
@@ -0,0 +1,66 @@
+use actix_web::{web, HttpRequest};
+use diesel::{dsl::exists, prelude::*, select};
+use uuid::Uuid;
+
+use crate::{
+    error::{RouterError, RouterErrorDetailBuilder},
+    validate::validate,
+    DbPool,
+};
+
+use super::ReqRole;
+
+/// Edits the role
+pub async fn edit_role(
+    path: web::Path<Uuid>,
+    info: web::Json<ReqRole>,
+    pool: web::Data<DbPool>,
+    req: HttpRequest,
+) -> Result<&'static str, RouterError> {
+    use crate::schema::app_roles::dsl::{app_roles, description, name, uuid as role_uuid};
+
+    let target_role_uuid = path.into_inner();
+    let new_role = info.into_inner();
+
+    validate(&new_role)?;
+
+    let pool = pool.into_inner();
+
+    let error_detail = RouterErrorDetailBuilder::from_http_request(&req)
+        .request_body(
+            serde_json::to_string(&new_role)
+                .unwrap()
+                .as_bytes()
+                .to_vec(),
+        )
+        .build();
+
+    web::block(move || {
+        let mut conn = pool.get().unwrap();
+
+        // Name must be unique between roles
+        let name_taken = select(exists(
+            app_roles
+                .filter(name.eq(&new_role.name))
+                .filter(role_uuid.ne(target_role_uuid)),
+        ))
+        .get_result::<bool>(&mut conn)?;
+
+        if name_taken {
+            return Err(RouterError::from_predefined("ROLE_NAME_NOT_AVAILABLE")
+                .log_to_db(pool, error_detail));
+        }
+
+        let updated = diesel::update(app_roles.filter(role_uuid.eq(target_role_uuid)))
+            .set((name.eq(new_role.name), description.eq(new_role.description)))
+            .execute(&mut conn)?;
+
+        if updated == 0 {
+            return Err(RouterError::from_predefined("NOT_FOUND"));
+        }
+
+        Ok("Updated")
+    })
+    .await
+    .unwrap()
+}
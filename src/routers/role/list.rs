@@ -0,0 +1,20 @@
+use crate::{error::RouterError, models::Role, DbPool};
+use actix_web::web;
+use diesel::prelude::*;
+
+/// Returns the list of roles
+pub async fn get_list_of_roles(
+    pool: web::Data<DbPool>,
+) -> Result<web::Json<Vec<Role>>, RouterError> {
+    use crate::schema::app_roles::dsl::{app_roles, name};
+
+    web::block(move || {
+        let mut conn = pool.get().unwrap();
+
+        let roles: Vec<Role> = app_roles.order(name.asc()).load(&mut conn)?;
+
+        Ok(web::Json(roles))
+    })
+    .await
+    .unwrap()
+}
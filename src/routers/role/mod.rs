@@ -0,0 +1,18 @@
+pub mod add;
+pub mod assignment;
+pub mod delete;
+pub mod edit;
+pub mod list;
+pub mod view;
+
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+#[derive(Deserialize, Validate, Serialize)]
+/// Role (named permission bundle) which will be recived from client request
+pub struct ReqRole {
+    #[validate(length(min = 1, max = 255))]
+    pub name: String,
+
+    pub description: Option<String>,
+}
@@ -0,0 +1,51 @@
+use crate::{
+    error::RouterError,
+    models::{Permission, Role},
+    routers::permission::GrantedPermission,
+    DbPool,
+};
+use actix_web::web;
+use diesel::prelude::*;
+use serde::Serialize;
+use uuid::Uuid;
+
+#[derive(Serialize)]
+pub struct RoleWithPermissions {
+    #[serde(flatten)]
+    role: Role,
+    permissions: Vec<GrantedPermission>,
+}
+
+/// Returns the role with its permissions
+pub async fn view(
+    path: web::Path<Uuid>,
+    pool: web::Data<DbPool>,
+) -> Result<web::Json<RoleWithPermissions>, RouterError> {
+    use crate::schema::app_permissions::dsl::{app_permissions, role_id};
+    use crate::schema::app_roles::dsl::{app_roles, uuid as role_uuid};
+
+    let requested_role_uuid = path.into_inner();
+
+    web::block(move || {
+        let mut conn = pool.get().unwrap();
+
+        let role: Role = app_roles
+            .filter(role_uuid.eq(requested_role_uuid))
+            .get_result(&mut conn)?;
+
+        let permissions: Vec<Permission> = app_permissions
+            .filter(role_id.eq(role.id))
+            .select(Permission::as_select())
+            .load(&mut conn)?;
+
+        Ok(web::Json(RoleWithPermissions {
+            role,
+            permissions: permissions
+                .into_iter()
+                .map(GrantedPermission::from)
+                .collect(),
+        }))
+    })
+    .await
+    .unwrap()
+}
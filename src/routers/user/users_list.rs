@@ -5,6 +5,16 @@ use actix_web::web;
 use diesel::prelude::*;
 use uuid::Uuid;
 
+/// (account uuid, username, user, email, first name, last name)
+type UserRow = (
+    Uuid,
+    String,
+    User,
+    Option<String>,
+    Option<String>,
+    Option<String>,
+);
+
 /// Returns the list of all users
 pub async fn users_list(
     pool: web::Data<DbPool>,
@@ -26,7 +36,7 @@ pub async fn users_list(
         // this is the best way to make query in this situation
         //
         // good luck if you gonna read this :)
-        let users: Vec<(Uuid, String, User, Option<String>, Option<String>, Option<String>)> = app_users
+        let users: Vec<UserRow> = app_users
             .inner_join(
                 // Join the accounts, emails and user_names
                 // tables together
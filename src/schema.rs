@@ -9,6 +9,25 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    app_email_outbox (id) {
+        id -> Int4,
+        uuid -> Uuid,
+        recipient -> Text,
+        subject -> Text,
+        text_body -> Text,
+        html_body -> Text,
+        status -> Varchar,
+        attempts -> Int4,
+        last_error -> Nullable<Text>,
+        next_attempt_at -> Timestamptz,
+        sent_at -> Nullable<Timestamptz>,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+        expires_at -> Nullable<Timestamptz>,
+    }
+}
+
 diesel::table! {
     app_emails (id) {
         id -> Int4,
@@ -57,6 +76,65 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    app_group_members (id) {
+        id -> Int4,
+        creator_user_id -> Int4,
+        group_id -> Int4,
+        account_id -> Int4,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    app_groups (id) {
+        id -> Int4,
+        uuid -> Uuid,
+        creator_user_id -> Int4,
+        name -> Varchar,
+        description -> Nullable<Text>,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    app_oidc_identities (id) {
+        id -> Int4,
+        account_id -> Int4,
+        issuer -> Text,
+        subject -> Text,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    app_oidc_sessions (id) {
+        id -> Int4,
+        state -> Varchar,
+        nonce -> Varchar,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+        expires_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    app_organization_invitations (id) {
+        id -> Int4,
+        uuid -> Uuid,
+        org_account_id -> Int4,
+        creator_user_id -> Int4,
+        email -> Text,
+        expires_at -> Timestamptz,
+        accepted_at -> Nullable<Timestamptz>,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+    }
+}
+
 diesel::table! {
     app_organization_names (id) {
         id -> Int4,
@@ -100,11 +178,15 @@ diesel::table! {
         id -> Int4,
         uuid -> Uuid,
         creator_user_id -> Int4,
-        account_id -> Int4,
+        account_id -> Nullable<Int4>,
         object -> Varchar,
         action -> Varchar,
         created_at -> Timestamptz,
         updated_at -> Timestamptz,
+        role_id -> Nullable<Int4>,
+        group_id -> Nullable<Int4>,
+        condition_operator -> Varchar,
+        pseudo_subject -> Nullable<Varchar>,
     }
 }
 
@@ -130,6 +212,30 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    app_role_assignments (id) {
+        id -> Int4,
+        creator_user_id -> Int4,
+        role_id -> Int4,
+        account_id -> Nullable<Int4>,
+        group_id -> Nullable<Int4>,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    app_roles (id) {
+        id -> Int4,
+        uuid -> Uuid,
+        creator_user_id -> Int4,
+        name -> Varchar,
+        description -> Nullable<Text>,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+    }
+}
+
 diesel::table! {
     app_tokens (id) {
         id -> Int4,
@@ -268,6 +374,13 @@ diesel::table! {
 diesel::joinable!(app_emails -> app_accounts (account_id));
 diesel::joinable!(app_emails -> app_users (creator_user_id));
 diesel::joinable!(app_employees -> app_users (creator_user_id));
+diesel::joinable!(app_group_members -> app_accounts (account_id));
+diesel::joinable!(app_group_members -> app_groups (group_id));
+diesel::joinable!(app_group_members -> app_users (creator_user_id));
+diesel::joinable!(app_groups -> app_users (creator_user_id));
+diesel::joinable!(app_oidc_identities -> app_accounts (account_id));
+diesel::joinable!(app_organization_invitations -> app_accounts (org_account_id));
+diesel::joinable!(app_organization_invitations -> app_users (creator_user_id));
 diesel::joinable!(app_organization_names -> app_accounts (account_id));
 diesel::joinable!(app_organization_names -> app_users (creator_user_id));
 diesel::joinable!(app_organizations -> app_accounts (account_id));
@@ -275,8 +388,15 @@ diesel::joinable!(app_organizations -> app_users (creator_user_id));
 diesel::joinable!(app_permission_conditions -> app_permissions (permission_id));
 diesel::joinable!(app_permission_conditions -> app_users (creator_user_id));
 diesel::joinable!(app_permissions -> app_accounts (account_id));
+diesel::joinable!(app_permissions -> app_groups (group_id));
+diesel::joinable!(app_permissions -> app_roles (role_id));
 diesel::joinable!(app_permissions -> app_users (creator_user_id));
 diesel::joinable!(app_phrase_translations -> app_phrases (phrase_id));
+diesel::joinable!(app_role_assignments -> app_accounts (account_id));
+diesel::joinable!(app_role_assignments -> app_groups (group_id));
+diesel::joinable!(app_role_assignments -> app_roles (role_id));
+diesel::joinable!(app_role_assignments -> app_users (creator_user_id));
+diesel::joinable!(app_roles -> app_users (creator_user_id));
 diesel::joinable!(app_tokens -> app_accounts (account_id));
 diesel::joinable!(app_user_names -> app_accounts (account_id));
 diesel::joinable!(app_user_names -> app_users (creator_user_id));
@@ -297,15 +417,23 @@ diesel::joinable!(quran_words -> quran_ayahs (ayah_id));
 
 diesel::allow_tables_to_appear_in_same_query!(
     app_accounts,
+    app_email_outbox,
     app_emails,
     app_employees,
     app_error_logs,
+    app_group_members,
+    app_groups,
+    app_oidc_identities,
+    app_oidc_sessions,
+    app_organization_invitations,
     app_organization_names,
     app_organizations,
     app_permission_conditions,
     app_permissions,
     app_phrase_translations,
     app_phrases,
+    app_role_assignments,
+    app_roles,
     app_tokens,
     app_user_names,
     app_users,
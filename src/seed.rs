@@ -0,0 +1,249 @@
+use crate::condition::ConditionOperator;
+use crate::models::{
+    Account, NewPermission, NewQuranAyah, NewQuranMushaf, NewQuranSurah, NewQuranWord,
+    NewUserNames, QuranAyah, QuranMushaf, QuranSurah, User,
+};
+use crate::routers::account::{create_user_with_email, generate_token};
+use crate::DbPool;
+use diesel::dsl::exists;
+use diesel::prelude::*;
+use diesel::select;
+use std::error::Error;
+
+/// Objects that the seeded admin can do every action on
+const ADMIN_PERMISSION_OBJECTS: [&str; 13] = [
+    "surah",
+    "ayah",
+    "word",
+    "mushaf",
+    "translation",
+    "user",
+    "organization",
+    "permission",
+    "error",
+    "phrase",
+    "email",
+    "role",
+    "group",
+];
+
+const PERMISSION_ACTIONS: [&str; 4] = ["create", "edit", "delete", "view"];
+
+/// Actions of the objects that are more than the common ones
+const ADMIN_OBJECT_ACTIONS: [(&str, &str); 2] = [
+    ("organization", "manage_employees"),
+    ("organization", "transfer"),
+];
+
+const SAMPLE_MUSHAF_SHORT_NAME: &str = "hafs";
+
+/// Al-Fatiha, the sample surah
+const SAMPLE_SURAH_AYAHS: [&str; 7] = [
+    "بسم الله الرحمن الرحيم",
+    "الحمد لله رب العالمين",
+    "الرحمن الرحيم",
+    "مالك يوم الدين",
+    "إياك نعبد وإياك نستعين",
+    "اهدنا الصراط المستقيم",
+    "صراط الذين أنعمت عليهم غير المغضوب عليهم ولا الضالين",
+];
+
+/// Seeded account and its fresh token
+pub struct SeededAccount {
+    pub username: String,
+    pub email: String,
+    pub token: String,
+}
+
+/// Fills the database with the development data
+///
+/// Creates an admin with every permission, a normal user and
+/// a sample mushaf. Running it again will not duplicate the data,
+/// only new tokens are generated.
+pub fn seed(pool: &DbPool) -> Result<Vec<SeededAccount>, Box<dyn Error>> {
+    let mut conn = pool.get()?;
+
+    let accounts = conn.transaction::<_, diesel::result::Error, _>(|conn| {
+        let admin = seed_account(conn, "admin", "admin@natiq.local", "Admin")?;
+        seed_admin_permissions(conn, &admin)?;
+
+        let user = seed_account(conn, "user", "user@natiq.local", "User")?;
+
+        seed_sample_mushaf(conn, &admin)?;
+
+        Ok(vec![
+            (admin, "admin", "admin@natiq.local"),
+            (user, "user", "user@natiq.local"),
+        ])
+    })?;
+
+    let mut result = vec![];
+
+    for (user, username, email) in accounts {
+        result.push(SeededAccount {
+            username: username.to_string(),
+            email: email.to_string(),
+            token: generate_token(&mut conn, user.account_id)?,
+        });
+    }
+
+    Ok(result)
+}
+
+/// Returns the existing user with the username or creates it
+fn seed_account(
+    conn: &mut PgConnection,
+    account_username: &str,
+    email: &str,
+    name: &str,
+) -> QueryResult<User> {
+    use crate::schema::app_accounts::dsl::{app_accounts, id as acc_id, username};
+    use crate::schema::app_user_names::dsl::app_user_names;
+
+    let existing: Option<Account> = app_accounts
+        .filter(username.eq(account_username))
+        .first(conn)
+        .optional()?;
+
+    if let Some(account) = existing {
+        return User::belonging_to(&account).get_result(conn);
+    }
+
+    let user = create_user_with_email(conn, &email.to_string())?;
+
+    // Seeded accounts have known usernames instead of u{id}
+    diesel::update(app_accounts.filter(acc_id.eq(user.account_id)))
+        .set(username.eq(account_username))
+        .execute(conn)?;
+
+    NewUserNames {
+        account_id: user.account_id,
+        primary_name: true,
+        creator_user_id: user.id,
+        first_name: Some(name.to_string()),
+        last_name: Some("Natiq".to_string()),
+        language: Some("en".to_string()),
+    }
+    .insert_into(app_user_names)
+    .execute(conn)?;
+
+    Ok(user)
+}
+
+/// Grants every action on every object to the admin
+fn seed_admin_permissions(conn: &mut PgConnection, admin: &User) -> QueryResult<()> {
+    for permission_object in ADMIN_PERMISSION_OBJECTS {
+        for permission_action in PERMISSION_ACTIONS {
+            seed_admin_permission(conn, admin, permission_object, permission_action)?;
+        }
+    }
+
+    for (permission_object, permission_action) in ADMIN_OBJECT_ACTIONS {
+        seed_admin_permission(conn, admin, permission_object, permission_action)?;
+    }
+
+    Ok(())
+}
+
+/// Grants the action if the admin doesn't have it yet
+fn seed_admin_permission(
+    conn: &mut PgConnection,
+    admin: &User,
+    permission_object: &str,
+    permission_action: &str,
+) -> QueryResult<()> {
+    use crate::schema::app_permissions::dsl::{account_id, action, app_permissions, object};
+
+    let permission_exists: bool = select(exists(
+        app_permissions
+            .filter(account_id.eq(admin.account_id))
+            .filter(object.eq(permission_object))
+            .filter(action.eq(permission_action)),
+    ))
+    .get_result(conn)?;
+
+    if permission_exists {
+        return Ok(());
+    }
+
+    NewPermission {
+        creator_user_id: admin.id,
+        account_id: Some(admin.account_id),
+        role_id: None,
+        group_id: None,
+        object: &permission_object.to_string(),
+        action: &permission_action.to_string(),
+        condition_operator: ConditionOperator::default().as_str(),
+        pseudo_subject: None,
+    }
+    .insert_into(app_permissions)
+    .execute(conn)?;
+
+    Ok(())
+}
+
+/// Creates the sample mushaf with a single surah
+fn seed_sample_mushaf(conn: &mut PgConnection, creator: &User) -> QueryResult<()> {
+    use crate::schema::quran_ayahs::dsl::quran_ayahs;
+    use crate::schema::quran_mushafs::dsl::{quran_mushafs, short_name};
+    use crate::schema::quran_surahs::dsl::quran_surahs;
+    use crate::schema::quran_words::dsl::quran_words;
+
+    let mushaf_exists: bool = select(exists(
+        quran_mushafs.filter(short_name.eq(SAMPLE_MUSHAF_SHORT_NAME)),
+    ))
+    .get_result(conn)?;
+
+    if mushaf_exists {
+        return Ok(());
+    }
+
+    let mushaf: QuranMushaf = NewQuranMushaf {
+        creator_user_id: creator.id,
+        short_name: Some(SAMPLE_MUSHAF_SHORT_NAME),
+        name: Some("Hafs an Asim"),
+        source: Some("tanzil"),
+        bismillah_text: Some(SAMPLE_SURAH_AYAHS[0].to_string()),
+    }
+    .insert_into(quran_mushafs)
+    .get_result(conn)?;
+
+    let surah: QuranSurah = NewQuranSurah {
+        creator_user_id: creator.id,
+        name: "الفاتحة".to_string(),
+        period: Some("makki".to_string()),
+        number: 1,
+        bismillah_status: true,
+        bismillah_as_first_ayah: true,
+        mushaf_id: mushaf.id,
+        name_pronunciation: Some("Al-Fatiha".to_string()),
+        name_translation_phrase: None,
+        name_transliteration: Some("al-Fātiḥah".to_string()),
+    }
+    .insert_into(quran_surahs)
+    .get_result(conn)?;
+
+    for (index, text) in SAMPLE_SURAH_AYAHS.iter().enumerate() {
+        let ayah: QuranAyah = NewQuranAyah {
+            creator_user_id: creator.id,
+            surah_id: surah.id,
+            ayah_number: index as i32 + 1,
+            sajdah: None,
+        }
+        .insert_into(quran_ayahs)
+        .get_result(conn)?;
+
+        let words: Vec<NewQuranWord> = text
+            .split(' ')
+            .map(|word| NewQuranWord {
+                creator_user_id: creator.id,
+                ayah_id: ayah.id,
+                word,
+            })
+            .collect();
+
+        words.insert_into(quran_words).execute(conn)?;
+    }
+
+    Ok(())
+}
@@ -1,5 +1,8 @@
 use crate::{
-    models::{Organization, User},
+    models::{
+        Group, Organization, Permission, QuranAyah, QuranMushaf, QuranSurah, QuranWord, Role,
+        Translation, User,
+    },
     select_model, DbPool,
 };
 
@@ -7,13 +10,58 @@ use crate::{
 use actix_web::web::block;
 use diesel::prelude::*;
 use async_trait::async_trait;
+use uuid::Uuid;
 
 #[async_trait]
 pub trait SelectModel {
-    async fn from_id(conn: DbPool, id: i32) -> Self
+    /// Returns None when there is no model with this uuid
+    async fn from_uuid(conn: DbPool, uuid: Uuid) -> Option<Self>
     where
         Self: Sized;
 }
 
-select_model!(Organization, app_organizations);
-select_model!(User, app_users);
+/// Model that is owned by the account of its creator
+#[derive(Debug, Clone)]
+pub struct OwnedModel<T> {
+    pub model: T,
+    pub owner_account_id: i32,
+}
+
+select_model!(Organization, app_organizations, account);
+select_model!(User, app_users, account);
+select_model!(Translation, quran_translations);
+select_model!(QuranMushaf, quran_mushafs, creator);
+select_model!(QuranSurah, quran_surahs, creator);
+select_model!(QuranAyah, quran_ayahs, creator);
+select_model!(QuranWord, quran_words, creator);
+select_model!(Permission, app_permissions, creator);
+select_model!(Role, app_roles, creator);
+select_model!(Group, app_groups, creator);
+
+impl Organization {
+    /// Selects the organization that the name belongs to
+    pub async fn from_name_uuid(conn: DbPool, name_uuid: Uuid) -> Option<Self> {
+        use crate::schema::app_organization_names::dsl::{
+            account_id as name_account_id, app_organization_names, uuid as name_uuid_column,
+        };
+        use crate::schema::app_organizations::dsl::{account_id, app_organizations};
+
+        let mut conn = conn.get().ok()?;
+
+        block(move || {
+            app_organizations
+                .filter(
+                    account_id.eq_any(
+                        app_organization_names
+                            .filter(name_uuid_column.eq(name_uuid))
+                            .select(name_account_id),
+                    ),
+                )
+                .select(Organization::as_select())
+                .first::<Organization>(&mut conn)
+                .ok()
+        })
+        .await
+        .ok()?
+    }
+}
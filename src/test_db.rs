@@ -0,0 +1,27 @@
+use crate::{run_migrations, DbPool};
+use diesel::r2d2::{ConnectionManager, Pool};
+use std::{env, sync::OnceLock};
+
+/// Shared pool of the test database
+static POOL: OnceLock<DbPool> = OnceLock::new();
+
+/// Returns a pool of the migrated test database
+///
+/// The tests that call this are marked `#[ignore]`,
+/// run them with `--ignored` and TEST_DATABASE_URL set
+pub fn pool() -> DbPool {
+    POOL.get_or_init(|| {
+        let url = env::var("TEST_DATABASE_URL")
+            .expect("TEST_DATABASE_URL must be set for the database tests");
+
+        let pool = Pool::builder()
+            .max_size(4)
+            .build(ConnectionManager::new(url))
+            .expect("Can't connect to TEST_DATABASE_URL");
+
+        run_migrations(&mut pool.get().unwrap()).unwrap();
+
+        pool
+    })
+    .clone()
+}
@@ -3,6 +3,7 @@ use std::{net::SocketAddr, sync::Arc};
 use crate::{
     error::{RouterError, RouterErrorDetail},
     models::Token,
+    routers::account::time_deference,
     DbPool,
 };
 use actix_web::{
@@ -18,11 +19,17 @@ use diesel::prelude::*;
 #[derive(Clone)]
 pub struct UserIdFromToken {
     db_pool: DbPool,
+
+    /// Tokens older than this are expired, they never expire if None
+    token_lifetime_seconds: Option<i64>,
 }
 
 impl UserIdFromToken {
-    pub fn new(db_pool: DbPool) -> Self {
-        Self { db_pool }
+    pub fn new(db_pool: DbPool, token_lifetime_seconds: Option<i64>) -> Self {
+        Self {
+            db_pool,
+            token_lifetime_seconds,
+        }
     }
 }
 
@@ -51,6 +58,8 @@ impl TokenChecker<u32> for UserIdFromToken {
             .req_address(req_addr)
             .request_url_parsed(uri.path());
 
+        error_detail_builder.request_id_from_headers(&headers);
+
         if let Some(user_agent) = headers.get("User-agent") {
             error_detail_builder.user_agent(user_agent.to_str().unwrap().to_string());
         }
@@ -92,6 +101,16 @@ impl TokenChecker<u32> for UserIdFromToken {
             return Err(token_invalid_error);
         }
 
+        if let Some(lifetime) = self.token_lifetime_seconds {
+            if time_deference(last_token.created_at).num_seconds() >= lifetime {
+                let token_expired_error =
+                    Box::new(RouterError::from_predefined("AUTHN_TOKEN_EXPIRED"));
+
+                token_expired_error.log_to_db(Arc::new(self.db_pool.clone()), error_detail);
+                return Err(token_expired_error);
+            }
+        }
+
         Ok(last_token.user_id as u32)
     }
 
@@ -1,24 +1,371 @@
-use validator::Validate;
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::{collections::BTreeMap, fmt::Display};
+use validator::{Validate, ValidationError, ValidationErrors, ValidationErrorsKind};
 
-use crate::error::RouterError;
+use crate::{error::RouterError, error_language::ERROR_LANGUAGES};
+
+/// Errors of the body that aren't about a single field
+pub const BODY_ERRORS: &str = "__all__";
+
+/// Field path to its errors
+///
+/// `name`, `organization.name` and `members[0].email` are field paths
+pub type FieldErrors = BTreeMap<String, Vec<FieldError>>;
+
+#[derive(Clone, Debug, Serialize)]
+pub struct FieldError {
+    /// Validator code, example: `length`
+    pub code: String,
+
+    /// Validator params, example: `{"min": 1, "max": 255, "value": ""}`
+    pub params: BTreeMap<String, Value>,
+
+    /// Message of the validator, used when the code has no translation
+    #[serde(skip)]
+    pub message: Option<String>,
+}
+
+impl From<ValidationError> for FieldError {
+    fn from(value: ValidationError) -> Self {
+        Self {
+            code: value.code.into_owned(),
+            params: value
+                .params
+                .into_iter()
+                .map(|(name, value)| (name.into_owned(), value))
+                .collect(),
+            message: value.message.map(|message| message.into_owned()),
+        }
+    }
+}
+
+impl FieldError {
+    fn new(code: &str) -> Self {
+        Self {
+            code: code.to_string(),
+            params: BTreeMap::new(),
+            message: None,
+        }
+    }
+
+    /// Message in the language, the params are filled in
+    pub fn message(&self, language: &str) -> String {
+        // Translations are in the order of ERROR_LANGUAGES
+        let index = ERROR_LANGUAGES
+            .into_iter()
+            .position(|supported| supported == language)
+            .unwrap_or(0);
+
+        match (translations(&self.code, &self.params), &self.message) {
+            (Some(translations), _) => self.fill_params(translations[index]),
+            (None, Some(message)) => message.clone(),
+            (None, None) => INVALID[index].to_string(),
+        }
+    }
+
+    fn fill_params(&self, template: &str) -> String {
+        self.params
+            .iter()
+            .fold(template.to_string(), |message, (name, value)| {
+                let value = match value {
+                    Value::String(value) => value.clone(),
+
+                    // Range bounds are floats, `1.0` is shown as `1`
+                    Value::Number(number) => match number.as_f64() {
+                        Some(float) if float.fract() == 0.0 && number.is_f64() => {
+                            (float as i64).to_string()
+                        }
+                        _ => number.to_string(),
+                    },
+
+                    value => value.to_string(),
+                };
+
+                message.replace(&format!("{{{}}}", name), &value)
+            })
+    }
+}
+
+const INVALID: [&str; 3] = ["Invalid value", "مقدار نامعتبر است", "قيمة غير صالحة"];
+
+/// en, fa and ar messages of the validator codes
+fn translations(code: &str, params: &BTreeMap<String, Value>) -> Option<[&'static str; 3]> {
+    let has = |name: &str| params.contains_key(name);
+
+    Some(match code {
+        "required" => [
+            "This field is required",
+            "این فیلد الزامی است",
+            "هذا الحقل مطلوب",
+        ],
+        "unknown_field" => ["Unknown field", "فیلد ناشناخته است", "حقل غير معروف"],
+        "email" => [
+            "Must be a valid email address",
+            "باید یک آدرس ایمیل معتبر باشد",
+            "يجب أن يكون عنوان بريد إلكتروني صالحًا",
+        ],
+        "url" => [
+            "Must be a valid URL",
+            "باید یک آدرس اینترنتی معتبر باشد",
+            "يجب أن يكون رابطًا صالحًا",
+        ],
+        "length" if has("equal") => [
+            "Length must be {equal}",
+            "طول باید {equal} باشد",
+            "يجب أن يكون الطول {equal}",
+        ],
+        "length" if has("min") && has("max") => [
+            "Length must be between {min} and {max}",
+            "طول باید بین {min} و {max} باشد",
+            "يجب أن يكون الطول بين {min} و {max}",
+        ],
+        "length" if has("min") => [
+            "Length must be at least {min}",
+            "طول باید حداقل {min} باشد",
+            "يجب أن يكون الطول {min} على الأقل",
+        ],
+        "length" if has("max") => [
+            "Length must be at most {max}",
+            "طول باید حداکثر {max} باشد",
+            "يجب ألا يتجاوز الطول {max}",
+        ],
+        "range" if has("min") && has("max") => [
+            "Must be between {min} and {max}",
+            "باید بین {min} و {max} باشد",
+            "يجب أن يكون بين {min} و {max}",
+        ],
+        "range" if has("min") => [
+            "Must be at least {min}",
+            "باید حداقل {min} باشد",
+            "يجب أن يكون {min} على الأقل",
+        ],
+        "range" if has("max") => [
+            "Must be at most {max}",
+            "باید حداکثر {max} باشد",
+            "يجب ألا يتجاوز {max}",
+        ],
+        "date_in_future" => [
+            "The date must not be in the future",
+            "تاریخ نباید در آینده باشد",
+            "يجب ألا يكون التاريخ في المستقبل",
+        ],
+        "date_too_old" => [
+            "The date must not be older than {limit} years",
+            "تاریخ نباید قدیمی‌تر از {limit} سال باشد",
+            "يجب ألا يكون التاريخ أقدم من {limit} سنة",
+        ],
+        "invalid_type" | "invalid_value" | "invalid_length" | "unknown_variant" | "invalid" => {
+            INVALID
+        }
+
+        _ => return None,
+    })
+}
+
+/// Flattens the nested validator errors into field paths
+pub fn field_errors(errors: ValidationErrors) -> FieldErrors {
+    let mut fields = FieldErrors::new();
+
+    flatten(errors, "", &mut fields);
+
+    fields
+}
+
+fn flatten(errors: ValidationErrors, prefix: &str, fields: &mut FieldErrors) {
+    for (field, kind) in errors.into_errors() {
+        let path = if prefix.is_empty() {
+            field.to_string()
+        } else {
+            format!("{}.{}", prefix, field)
+        };
+
+        match kind {
+            ValidationErrorsKind::Field(errors) => fields
+                .entry(path)
+                .or_default()
+                .extend(errors.into_iter().map(FieldError::from)),
+
+            ValidationErrorsKind::Struct(errors) => flatten(*errors, &path, fields),
+
+            ValidationErrorsKind::List(items) => {
+                for (index, errors) in items {
+                    flatten(*errors, &format!("{}[{}]", path, index), fields);
+                }
+            }
+        }
+    }
+}
+
+/// Field errors of a JSON body or query string that doesn't match the expected type
+///
+/// serde only names the field when it's missing or unknown,
+/// the other errors are reported for the whole body
+pub fn deserialize_field_errors(error: &impl Display) -> FieldErrors {
+    let message = error.to_string();
+
+    // `missing field `name` at line 1 column 2`
+    let reason = message
+        .rsplit_once(" at line ")
+        .map_or(message.as_str(), |(reason, _)| reason);
+
+    let quoted = || reason.split('`').nth(1).unwrap_or_default().to_string();
+
+    let (field, mut field_error) = if reason.starts_with("missing field ") {
+        (quoted(), FieldError::new("required"))
+    } else if reason.starts_with("unknown field ") {
+        (quoted(), FieldError::new("unknown_field"))
+    } else {
+        let code = match reason {
+            reason if reason.starts_with("invalid type:") => "invalid_type",
+            reason if reason.starts_with("invalid value:") => "invalid_value",
+            reason if reason.starts_with("invalid length ") => "invalid_length",
+            reason if reason.starts_with("unknown variant ") => "unknown_variant",
+            _ => "invalid",
+        };
+
+        (BODY_ERRORS.to_string(), FieldError::new(code))
+    };
+
+    field_error
+        .params
+        .insert("reason".to_string(), json!(reason));
+
+    FieldErrors::from([(field, vec![field_error])])
+}
+
+/// Field errors with the messages in the language
+pub fn localized_field_errors(fields: &FieldErrors, language: &str) -> Value {
+    fields
+        .iter()
+        .map(|(path, errors)| {
+            let errors: Vec<Value> = errors
+                .iter()
+                .map(|error| {
+                    json!({
+                        "code": error.code,
+                        "params": error.params,
+                        "message": error.message(language),
+                    })
+                })
+                .collect();
+
+            (path.clone(), Value::from(errors))
+        })
+        .collect::<serde_json::Map<String, Value>>()
+        .into()
+}
 
 /// Validate the value and return actix error
 pub fn validate<T>(data: &T) -> Result<(), RouterError>
 where
     T: Validate,
 {
-    let validation = data.validate();
-
-    match validation {
-        Err(error_detail) => {
-            // TODO: This error in response is so ugly.
-            // TODO: log to database
-            Err(RouterError::from_predefined_with_detail(
-                "VALIDATION_ERROR",
-                &error_detail.to_string(),
-            ))
-        }
+    // TODO: log to database
+    data.validate()
+        .map_err(|errors| RouterError::from_validation(field_errors(errors)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{deserialize_field_errors, field_errors, localized_field_errors, BODY_ERRORS};
+    use serde::Deserialize;
+    use serde_json::json;
+    use validator::Validate;
+
+    #[derive(Debug, Deserialize, Validate)]
+    #[serde(deny_unknown_fields)]
+    struct Member {
+        #[validate(email)]
+        email: String,
+    }
+
+    #[derive(Debug, Deserialize, Validate)]
+    struct Team {
+        #[validate(length(min = 1, max = 4))]
+        name: String,
+
+        #[validate(range(min = 1, max = 10))]
+        size: u32,
+
+        #[validate]
+        members: Vec<Member>,
+    }
+
+    #[test]
+    fn test_field_errors() {
+        let team = Team {
+            name: String::new(),
+            size: 5,
+            members: vec![
+                Member {
+                    email: "a@b.com".to_string(),
+                },
+                Member {
+                    email: "ab".to_string(),
+                },
+            ],
+        };
+
+        let fields = field_errors(team.validate().unwrap_err());
+
+        assert_eq!(
+            fields.keys().collect::<Vec<_>>(),
+            vec!["members[1].email", "name"]
+        );
+        assert_eq!(fields["name"][0].code, "length");
+        assert_eq!(fields["name"][0].params["max"], json!(4));
+
+        let localized = localized_field_errors(&fields, "en");
+
+        assert_eq!(
+            localized["name"][0]["message"],
+            "Length must be between 1 and 4"
+        );
+        assert_eq!(
+            localized["members[1].email"][0]["message"],
+            "Must be a valid email address"
+        );
+        assert_eq!(
+            localized_field_errors(&fields, "fa")["name"][0]["message"],
+            "طول باید بین 1 و 4 باشد"
+        );
+    }
+
+    #[test]
+    fn test_range_message() {
+        let team = Team {
+            name: "nq".to_string(),
+            size: 11,
+            members: vec![],
+        };
+
+        let fields = field_errors(team.validate().unwrap_err());
+
+        assert_eq!(fields["size"][0].message("en"), "Must be between 1 and 10");
+    }
+
+    #[test]
+    fn test_deserialize_field_errors() {
+        let error = serde_json::from_str::<Team>(r#"{"name": "nq", "members": []}"#).unwrap_err();
+        let fields = deserialize_field_errors(&error);
+
+        assert_eq!(fields["size"][0].code, "required");
+        assert_eq!(fields["size"][0].params["reason"], "missing field `size`");
+
+        let error =
+            serde_json::from_str::<Member>(r#"{"email": "a@b.com", "name": 1}"#).unwrap_err();
+
+        assert_eq!(
+            deserialize_field_errors(&error)["name"][0].code,
+            "unknown_field"
+        );
+
+        let error = serde_json::from_str::<Team>(r#"{"name": "nq", "size": "1", "members": []}"#)
+            .unwrap_err();
+        let fields = deserialize_field_errors(&error);
 
-        Ok(()) => Ok(()),
+        assert_eq!(fields[BODY_ERRORS][0].code, "invalid_type");
+        assert_eq!(fields[BODY_ERRORS][0].message("ar"), "قيمة غير صالحة");
     }
 }